@@ -0,0 +1,278 @@
+//! Exercises the swapchain of a headless surface: configuring, acquiring, presenting
+//! and resizing, without a window. Requires `VK_EXT_headless_surface`, and exits
+//! successfully without doing anything if it's not available.
+//!
+//! Validation errors make the example panic, so it can be used as a test.
+
+#[cfg(feature = "vulkan")]
+extern crate gfx_backend_vulkan as back;
+
+#[cfg(feature = "vulkan")]
+use hal::{command, format as f, image as i, pass, pool, prelude::*, pso, window as w};
+
+#[cfg(feature = "vulkan")]
+use std::{borrow::Borrow, iter};
+
+#[cfg(feature = "vulkan")]
+type B = back::Backend;
+
+#[cfg(feature = "vulkan")]
+const EXTENTS: [w::Extent2D; 3] = [
+    w::Extent2D {
+        width: 64,
+        height: 64,
+    },
+    w::Extent2D {
+        width: 256,
+        height: 128,
+    },
+    w::Extent2D {
+        width: 32,
+        height: 96,
+    },
+];
+
+#[cfg(feature = "vulkan")]
+const FRAMES_PER_EXTENT: usize = 4;
+
+#[cfg(feature = "vulkan")]
+fn main() {
+    env_logger::init();
+
+    let mut desc = hal::InstanceDesc::new("gfx-rs headless", 1);
+    desc.headless = true;
+    let instance = back::Instance::create_with_debug_options(
+        &desc,
+        back::DebugOptions {
+            panic_on_error: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to create an instance!");
+
+    let mut surface = match instance.create_headless_surface() {
+        Ok(surface) => surface,
+        Err(e) => {
+            println!("Headless surfaces are not available ({}), skipping", e);
+            return;
+        }
+    };
+
+    let adapter = instance
+        .enumerate_adapters()
+        .into_iter()
+        .find(|adapter| {
+            adapter.queue_families.iter().any(|family| {
+                surface.supports_queue_family(family) && family.queue_type().supports_graphics()
+            })
+        })
+        .expect("No adapter can present to a headless surface");
+    let family = adapter
+        .queue_families
+        .iter()
+        .find(|family| {
+            surface.supports_queue_family(family) && family.queue_type().supports_graphics()
+        })
+        .unwrap();
+    let mut gpu = unsafe {
+        adapter
+            .physical_device
+            .open(&[(family, &[1.0])], hal::Features::empty())
+            .unwrap()
+    };
+    let device = &gpu.device;
+    let queue_group = &mut gpu.queue_groups[0];
+
+    let format = surface
+        .supported_formats(&adapter.physical_device)
+        .map_or(f::Format::Rgba8Srgb, |formats| formats[0]);
+
+    let render_pass = {
+        let attachment = pass::Attachment {
+            format: Some(format),
+            samples: 1,
+            ops: pass::AttachmentOps::new(
+                pass::AttachmentLoadOp::Clear,
+                pass::AttachmentStoreOp::Store,
+            ),
+            stencil_ops: pass::AttachmentOps::DONT_CARE,
+            layouts: i::Layout::Undefined..i::Layout::Present,
+        };
+        let subpass = pass::SubpassDesc {
+            colors: &[(0, i::Layout::ColorAttachmentOptimal)],
+            depth_stencil: None,
+            inputs: &[],
+            resolves: &[],
+            preserves: &[],
+        };
+        unsafe {
+            device.create_render_pass(iter::once(attachment), iter::once(subpass), iter::empty())
+        }
+        .expect("Can't create render pass")
+    };
+
+    let mut cmd_pool = unsafe {
+        device.create_command_pool(
+            queue_group.family,
+            pool::CommandPoolCreateFlags::RESET_INDIVIDUAL,
+        )
+    }
+    .expect("Can't create command pool");
+    let mut cmd_buffer = unsafe { cmd_pool.allocate_one(command::Level::Primary) };
+    let mut submission_complete_semaphore = device.create_semaphore().unwrap();
+    let mut submission_complete_fence = device.create_fence(false).unwrap();
+    let queue = &mut queue_group.queues[0];
+
+    for &requested_extent in EXTENTS.iter() {
+        // Configuring an already configured surface resizes its swapchain.
+        let mut framebuffer = configure(
+            &mut surface,
+            &adapter,
+            device,
+            &render_pass,
+            format,
+            requested_extent,
+        );
+
+        let mut frame = 0;
+        while frame < FRAMES_PER_EXTENT {
+            let (image, suboptimal) = match unsafe { surface.acquire_image(!0) } {
+                Ok(acquired) => acquired,
+                Err(w::AcquireError::OutOfDate(_)) => {
+                    println!("Swapchain out of date on acquire, reconfiguring");
+                    unsafe { device.destroy_framebuffer(framebuffer.0) };
+                    framebuffer = configure(
+                        &mut surface,
+                        &adapter,
+                        device,
+                        &render_pass,
+                        format,
+                        requested_extent,
+                    );
+                    continue;
+                }
+                Err(e) => panic!("Failed to acquire an image: {}", e),
+            };
+            if suboptimal.is_some() {
+                println!("Swapchain is suboptimal on acquire");
+            }
+
+            unsafe {
+                cmd_buffer.begin_primary(command::CommandBufferFlags::ONE_TIME_SUBMIT);
+                cmd_buffer.begin_render_pass(
+                    &render_pass,
+                    &framebuffer.0,
+                    framebuffer.1,
+                    iter::once(command::RenderAttachmentInfo {
+                        image_view: image.borrow(),
+                        clear_value: command::ClearValue {
+                            color: command::ClearColor {
+                                float32: [0.0, 0.0, frame as f32 / FRAMES_PER_EXTENT as f32, 1.0],
+                            },
+                        },
+                    }),
+                    command::SubpassContents::Inline,
+                );
+                cmd_buffer.end_render_pass();
+                cmd_buffer.finish();
+
+                queue.submit(
+                    iter::once(&cmd_buffer),
+                    iter::empty(),
+                    iter::once(&submission_complete_semaphore),
+                    Some(&mut submission_complete_fence),
+                );
+
+                match queue.present(
+                    &mut surface,
+                    image,
+                    Some(&mut submission_complete_semaphore),
+                ) {
+                    Ok(None) => {}
+                    Ok(Some(_)) => println!("Swapchain is suboptimal on present"),
+                    Err(w::PresentError::OutOfDate(_)) => {
+                        println!("Swapchain out of date on present, reconfiguring");
+                        device
+                            .wait_for_fence(&submission_complete_fence, !0)
+                            .unwrap();
+                        device.destroy_framebuffer(framebuffer.0);
+                        framebuffer = configure(
+                            &mut surface,
+                            &adapter,
+                            device,
+                            &render_pass,
+                            format,
+                            requested_extent,
+                        );
+                    }
+                    Err(e) => panic!("Failed to present: {}", e),
+                }
+
+                device
+                    .wait_for_fence(&submission_complete_fence, !0)
+                    .unwrap();
+                device.reset_fence(&mut submission_complete_fence).unwrap();
+                cmd_buffer.reset(false);
+            }
+            frame += 1;
+        }
+
+        println!(
+            "Presented {} frames at {}x{}",
+            FRAMES_PER_EXTENT, framebuffer.1.w, framebuffer.1.h
+        );
+        unsafe { device.destroy_framebuffer(framebuffer.0) };
+    }
+
+    device.wait_idle().unwrap();
+    unsafe {
+        surface.unconfigure_swapchain(device);
+        device.destroy_fence(submission_complete_fence);
+        device.destroy_semaphore(submission_complete_semaphore);
+        cmd_pool.free(iter::once(cmd_buffer));
+        device.destroy_command_pool(cmd_pool);
+        device.destroy_render_pass(render_pass);
+        instance.destroy_surface(surface);
+    }
+}
+
+/// Configure the swapchain for the requested extent, and create a framebuffer matching it.
+#[cfg(feature = "vulkan")]
+fn configure(
+    surface: &mut <B as hal::Backend>::Surface,
+    adapter: &hal::adapter::Adapter<B>,
+    device: &<B as hal::Backend>::Device,
+    render_pass: &<B as hal::Backend>::RenderPass,
+    format: f::Format,
+    requested_extent: w::Extent2D,
+) -> (<B as hal::Backend>::Framebuffer, pso::Rect) {
+    let caps = surface.capabilities(&adapter.physical_device);
+    let config = w::SwapchainConfig::from_caps(&caps, format, requested_extent);
+    let extent = config.extent;
+    let framebuffer = unsafe {
+        device
+            .create_framebuffer(
+                render_pass,
+                iter::once(config.framebuffer_attachment()),
+                extent.to_extent(),
+            )
+            .unwrap()
+    };
+    unsafe {
+        surface
+            .configure_swapchain(device, config)
+            .expect("Can't configure swapchain");
+    }
+    let rect = pso::Rect {
+        x: 0,
+        y: 0,
+        w: extent.width as _,
+        h: extent.height as _,
+    };
+    (framebuffer, rect)
+}
+
+#[cfg(not(feature = "vulkan"))]
+fn main() {
+    println!("You need to enable the vulkan feature in order to run the example");
+}
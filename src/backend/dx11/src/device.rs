@@ -814,6 +814,134 @@ impl Device {
         };
         Ok((dxgi_swapchain, non_srgb_format))
     }
+
+    /// Write the descriptors into `set`, starting at `first_binding` and `array_offset`.
+    ///
+    /// Unlike `write_descriptor_set`, the lifetime of the set borrow is independent of
+    /// the one of the descriptors, so a set can be written several times from the same iterator.
+    unsafe fn write_descriptors<'a, I>(
+        &self,
+        set: &mut DescriptorSet,
+        first_binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        // Get baseline mapping
+        let mut mapping = set
+            .layout
+            .pool_mapping
+            .map_register(|mapping| mapping.offset);
+
+        // Iterate over layout bindings until the first binding is found.
+        let binding_start = set
+            .layout
+            .bindings
+            .iter()
+            .position(|binding| binding.binding == first_binding)
+            .unwrap();
+
+        // If we've skipped layout bindings, we need to add them to get the correct binding offset
+        for binding in &set.layout.bindings[..binding_start] {
+            let content = DescriptorContent::from(binding.ty);
+            mapping.add_content_many(content, binding.stage_flags, binding.count as _);
+        }
+
+        // We start at the given binding index and array index
+        let mut binding_index = binding_start;
+        let mut array_index = array_offset;
+
+        // If we're skipping array indices in the current binding, we need to add them to get the correct binding offset
+        if array_index > 0 {
+            let binding: &pso::DescriptorSetLayoutBinding = &set.layout.bindings[binding_index];
+            let content = DescriptorContent::from(binding.ty);
+            mapping.add_content_many(content, binding.stage_flags, array_index as _);
+        }
+
+        // Iterate over the descriptors, figuring out the corresponding binding, and adding
+        // it to the set of bindings.
+        //
+        // When we hit the end of an array of descriptors and there are still descriptors left
+        // over, we will spill into writing the next binding.
+        for descriptor in descriptors {
+            let binding: &pso::DescriptorSetLayoutBinding = &set.layout.bindings[binding_index];
+
+            let handles = match descriptor {
+                pso::Descriptor::Buffer(buffer, ref _sub) => RegisterData {
+                    c: match buffer.internal.disjoint_cb {
+                        Some(dj_buf) => dj_buf as *mut _,
+                        None => buffer.internal.raw as *mut _,
+                    },
+                    t: buffer.internal.srv.map_or(ptr::null_mut(), |p| p as *mut _),
+                    u: buffer.internal.uav.map_or(ptr::null_mut(), |p| p as *mut _),
+                    s: ptr::null_mut(),
+                },
+                pso::Descriptor::Image(image, _layout) => RegisterData {
+                    c: ptr::null_mut(),
+                    t: image.srv_handle.map_or(ptr::null_mut(), |h| h as *mut _),
+                    u: image.uav_handle.map_or(ptr::null_mut(), |h| h as *mut _),
+                    s: ptr::null_mut(),
+                },
+                pso::Descriptor::Sampler(sampler) => RegisterData {
+                    c: ptr::null_mut(),
+                    t: ptr::null_mut(),
+                    u: ptr::null_mut(),
+                    s: sampler.sampler_handle.as_raw() as *mut _,
+                },
+                pso::Descriptor::CombinedImageSampler(image, _layout, sampler) => RegisterData {
+                    c: ptr::null_mut(),
+                    t: image.srv_handle.map_or(ptr::null_mut(), |h| h as *mut _),
+                    u: image.uav_handle.map_or(ptr::null_mut(), |h| h as *mut _),
+                    s: sampler.sampler_handle.as_raw() as *mut _,
+                },
+                pso::Descriptor::TexelBuffer(_buffer_view) => unimplemented!(),
+            };
+
+            let content = DescriptorContent::from(binding.ty);
+            if content.contains(DescriptorContent::CBV) {
+                let offsets = mapping.map_other(|map| map.c);
+                set.assign_stages(&offsets, binding.stage_flags, handles.c);
+            };
+            if content.contains(DescriptorContent::SRV) {
+                let offsets = mapping.map_other(|map| map.t);
+                set.assign_stages(&offsets, binding.stage_flags, handles.t);
+            };
+            if content.contains(DescriptorContent::UAV) {
+                // If this binding is used by the graphics pipeline and is a UAV, it belongs to the "Output Merger"
+                // stage, so we only put them in the fragment stage to save redundant descriptor allocations.
+                let stage_flags = if binding
+                    .stage_flags
+                    .intersects(pso::ShaderStageFlags::ALL - pso::ShaderStageFlags::COMPUTE)
+                {
+                    let mut stage_flags = pso::ShaderStageFlags::FRAGMENT;
+                    stage_flags.set(
+                        pso::ShaderStageFlags::COMPUTE,
+                        binding.stage_flags.contains(pso::ShaderStageFlags::COMPUTE),
+                    );
+                    stage_flags
+                } else {
+                    binding.stage_flags
+                };
+
+                let offsets = mapping.map_other(|map| map.u);
+                set.assign_stages(&offsets, stage_flags, handles.u);
+            };
+            if content.contains(DescriptorContent::SAMPLER) {
+                let offsets = mapping.map_other(|map| map.s);
+                set.assign_stages(&offsets, binding.stage_flags, handles.s);
+            };
+
+            mapping.add_content_many(content, binding.stage_flags, 1);
+
+            array_index += 1;
+            if array_index >= binding.count {
+                // We've run out of array to write to, we should overflow to the next binding.
+                array_index = 0;
+                binding_index += 1;
+            }
+        }
+    }
 }
 
 impl device::Device<Backend> for Device {
@@ -1373,6 +1501,10 @@ impl device::Device<Backend> for Device {
         Ok(())
     }
 
+    unsafe fn get_buffer_device_address(&self, _buffer: &Buffer) -> buffer::DeviceAddress {
+        unreachable!("Feature BUFFER_DEVICE_ADDRESS is not supported")
+    }
+
     unsafe fn create_buffer_view(
         &self,
         _buffer: &Buffer,
@@ -1945,123 +2077,36 @@ impl device::Device<Backend> for Device {
     where
         I: Iterator<Item = pso::Descriptor<'a, Backend>>,
     {
-        // Get baseline mapping
-        let mut mapping = op
-            .set
-            .layout
-            .pool_mapping
-            .map_register(|mapping| mapping.offset);
-
-        // Iterate over layout bindings until the first binding is found.
-        let binding_start = op
-            .set
-            .layout
-            .bindings
-            .iter()
-            .position(|binding| binding.binding == op.binding)
-            .unwrap();
-
-        // If we've skipped layout bindings, we need to add them to get the correct binding offset
-        for binding in &op.set.layout.bindings[..binding_start] {
-            let content = DescriptorContent::from(binding.ty);
-            mapping.add_content_many(content, binding.stage_flags, binding.count as _);
-        }
-
-        // We start at the given binding index and array index
-        let mut binding_index = binding_start;
-        let mut array_index = op.array_offset;
-
-        // If we're skipping array indices in the current binding, we need to add them to get the correct binding offset
-        if array_index > 0 {
-            let binding: &pso::DescriptorSetLayoutBinding = &op.set.layout.bindings[binding_index];
-            let content = DescriptorContent::from(binding.ty);
-            mapping.add_content_many(content, binding.stage_flags, array_index as _);
-        }
-
-        // Iterate over the descriptors, figuring out the corresponding binding, and adding
-        // it to the set of bindings.
-        //
-        // When we hit the end of an array of descriptors and there are still descriptors left
-        // over, we will spill into writing the next binding.
-        for descriptor in op.descriptors {
-            let binding: &pso::DescriptorSetLayoutBinding = &op.set.layout.bindings[binding_index];
-
-            let handles = match descriptor {
-                pso::Descriptor::Buffer(buffer, ref _sub) => RegisterData {
-                    c: match buffer.internal.disjoint_cb {
-                        Some(dj_buf) => dj_buf as *mut _,
-                        None => buffer.internal.raw as *mut _,
-                    },
-                    t: buffer.internal.srv.map_or(ptr::null_mut(), |p| p as *mut _),
-                    u: buffer.internal.uav.map_or(ptr::null_mut(), |p| p as *mut _),
-                    s: ptr::null_mut(),
-                },
-                pso::Descriptor::Image(image, _layout) => RegisterData {
-                    c: ptr::null_mut(),
-                    t: image.srv_handle.map_or(ptr::null_mut(), |h| h as *mut _),
-                    u: image.uav_handle.map_or(ptr::null_mut(), |h| h as *mut _),
-                    s: ptr::null_mut(),
-                },
-                pso::Descriptor::Sampler(sampler) => RegisterData {
-                    c: ptr::null_mut(),
-                    t: ptr::null_mut(),
-                    u: ptr::null_mut(),
-                    s: sampler.sampler_handle.as_raw() as *mut _,
-                },
-                pso::Descriptor::CombinedImageSampler(image, _layout, sampler) => RegisterData {
-                    c: ptr::null_mut(),
-                    t: image.srv_handle.map_or(ptr::null_mut(), |h| h as *mut _),
-                    u: image.uav_handle.map_or(ptr::null_mut(), |h| h as *mut _),
-                    s: sampler.sampler_handle.as_raw() as *mut _,
-                },
-                pso::Descriptor::TexelBuffer(_buffer_view) => unimplemented!(),
-            };
-
-            let content = DescriptorContent::from(binding.ty);
-            if content.contains(DescriptorContent::CBV) {
-                let offsets = mapping.map_other(|map| map.c);
-                op.set
-                    .assign_stages(&offsets, binding.stage_flags, handles.c);
-            };
-            if content.contains(DescriptorContent::SRV) {
-                let offsets = mapping.map_other(|map| map.t);
-                op.set
-                    .assign_stages(&offsets, binding.stage_flags, handles.t);
-            };
-            if content.contains(DescriptorContent::UAV) {
-                // If this binding is used by the graphics pipeline and is a UAV, it belongs to the "Output Merger"
-                // stage, so we only put them in the fragment stage to save redundant descriptor allocations.
-                let stage_flags = if binding
-                    .stage_flags
-                    .intersects(pso::ShaderStageFlags::ALL - pso::ShaderStageFlags::COMPUTE)
-                {
-                    let mut stage_flags = pso::ShaderStageFlags::FRAGMENT;
-                    stage_flags.set(
-                        pso::ShaderStageFlags::COMPUTE,
-                        binding.stage_flags.contains(pso::ShaderStageFlags::COMPUTE),
-                    );
-                    stage_flags
-                } else {
-                    binding.stage_flags
-                };
-
-                let offsets = mapping.map_other(|map| map.u);
-                op.set.assign_stages(&offsets, stage_flags, handles.u);
-            };
-            if content.contains(DescriptorContent::SAMPLER) {
-                let offsets = mapping.map_other(|map| map.s);
-                op.set
-                    .assign_stages(&offsets, binding.stage_flags, handles.s);
-            };
+        self.write_descriptors(op.set, op.binding, op.array_offset, op.descriptors);
+    }
 
-            mapping.add_content_many(content, binding.stage_flags, 1);
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        _layout: &DescriptorSetLayout,
+        entries: I,
+    ) -> Result<Vec<pso::DescriptorUpdateTemplateEntry>, device::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorUpdateTemplateEntry>,
+    {
+        Ok(entries.collect())
+    }
 
-            array_index += 1;
-            if array_index >= binding.count {
-                // We've run out of array to write to, we should overflow to the next binding.
-                array_index = 0;
-                binding_index += 1;
-            }
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        set: &mut DescriptorSet,
+        template: &Vec<pso::DescriptorUpdateTemplateEntry>,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        let mut descriptors = descriptors;
+        for entry in template {
+            self.write_descriptors(
+                set,
+                entry.binding,
+                entry.array_offset,
+                descriptors.by_ref().take(entry.count),
+            );
         }
     }
 
@@ -2225,6 +2270,41 @@ impl device::Device<Backend> for Device {
         unimplemented!()
     }
 
+    unsafe fn allocate_exportable_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        _memory: &Memory,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        Ok(0)
+    }
+
     unsafe fn free_memory(&self, mut memory: Memory) {
         if !memory.host_ptr.is_null() {
             let _vec =
@@ -2320,6 +2400,12 @@ impl device::Device<Backend> for Device {
         //unimplemented!()
     }
 
+    unsafe fn destroy_descriptor_update_template(
+        &self,
+        _template: Vec<pso::DescriptorUpdateTemplateEntry>,
+    ) {
+    }
+
     unsafe fn destroy_fence(&self, _fence: Fence) {
         // unimplemented!()
     }
@@ -2328,6 +2414,64 @@ impl device::Device<Backend> for Device {
         //unimplemented!()
     }
 
+    fn create_timeline_semaphore(
+        &self,
+        _initial_value: u64,
+    ) -> Result<Semaphore, device::OutOfMemory> {
+        unimplemented!()
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        _semaphore: &Semaphore,
+    ) -> Result<u64, device::WaitError> {
+        unimplemented!()
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        _semaphores: I,
+        _wait: device::WaitFor,
+        _timeout_ns: u64,
+    ) -> Result<bool, device::WaitError>
+    where
+        I: Iterator<Item = (&'a Semaphore, u64)>,
+    {
+        unimplemented!()
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        _semaphore: &Semaphore,
+        _value: u64,
+    ) -> Result<(), device::OutOfMemory> {
+        unimplemented!()
+    }
+
+    fn create_exportable_semaphore(
+        &self,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<Semaphore, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        _semaphore: &Semaphore,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        _semaphore: &mut Semaphore,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
     unsafe fn destroy_event(&self, _event: ()) {
         //unimplemented!()
     }
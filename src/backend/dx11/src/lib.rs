@@ -444,7 +444,8 @@ impl hal::Instance<Backend> for Instance {
             Ok((library_dxgi, factory, dxgi_version)) => {
                 info!("DXGI version: {:?}", dxgi_version);
                 let library_d3d11 = Arc::new(unsafe {
-                    libloading::Library::new("d3d11.dll").map_err(|_| hal::UnsupportedBackend)?
+                    libloading::Library::new("d3d11.dll")
+                        .map_err(|_| hal::UnsupportedBackend::new("Unable to load d3d11.dll"))?
                 });
                 Ok(Instance {
                     factory,
@@ -455,7 +456,9 @@ impl hal::Instance<Backend> for Instance {
             }
             Err(hr) => {
                 info!("Failed on factory creation: {:?}", hr);
-                Err(hal::UnsupportedBackend)
+                Err(hal::UnsupportedBackend::new(
+                    "Unable to create a DXGI factory",
+                ))
             }
         }
     }
@@ -1201,6 +1204,18 @@ impl queue::Queue<Backend> for Queue {
         }
     }
 
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        _command_buffers: Ic,
+        _wait_semaphores: Iw,
+        _signal_semaphores: Is,
+        _fence: Option<&mut Fence>,
+    ) where
+        Ic: Iterator<Item = &'a CommandBuffer>,
+    {
+        unimplemented!()
+    }
+
     unsafe fn present(
         &mut self,
         surface: &mut Surface,
@@ -4275,6 +4290,7 @@ impl hal::Backend for Backend {
     type DescriptorSetLayout = DescriptorSetLayout;
     type DescriptorPool = DescriptorPool;
     type DescriptorSet = DescriptorSet;
+    type DescriptorUpdateTemplate = Vec<pso::DescriptorUpdateTemplateEntry>;
 
     type Fence = Fence;
     type Semaphore = Semaphore;
@@ -1247,6 +1247,188 @@ impl Device {
             requirements: image_unbound.requirements,
         });
     }
+
+    /// Write the descriptors into `set`, starting at `first_binding` and `array_offset`.
+    ///
+    /// Unlike `write_descriptor_set`, the lifetime of the set borrow is independent of
+    /// the one of the descriptors, so a set can be written several times from the same iterator.
+    unsafe fn write_descriptors<'a, I>(
+        &self,
+        set: &mut r::DescriptorSet,
+        first_binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+        let mut descriptor_updater = self.descriptor_updater.lock();
+        descriptor_updater.reset();
+
+        let mut accum = descriptors_cpu::MultiCopyAccumulator::default();
+        debug!("write_descriptor_set");
+
+        let mut offset = array_offset as u64;
+        let mut target_binding = first_binding as usize;
+        let base_sampler_offset = set.sampler_offset(first_binding, array_offset);
+        trace!("\tsampler offset {}", base_sampler_offset);
+        let mut sampler_offset = base_sampler_offset;
+        debug!("\tbinding {} array offset {}", target_binding, offset);
+
+        for descriptor in descriptors {
+            // spill over the writes onto the next binding
+            while offset >= set.binding_infos[target_binding].count {
+                target_binding += 1;
+                offset = 0;
+            }
+            let bind_info = &mut set.binding_infos[target_binding];
+            let mut src_cbv = None;
+            let mut src_srv = None;
+            let mut src_uav = None;
+
+            match descriptor {
+                pso::Descriptor::Buffer(buffer, ref sub) => {
+                    let buffer = buffer.expect_bound();
+
+                    if bind_info.content.is_dynamic() {
+                        // Root Descriptor
+                        let buffer_address = (*buffer.resource).GetGPUVirtualAddress();
+                        // Descriptor sets need to be externally synchronized according to specification
+                        bind_info.dynamic_descriptors[offset as usize].gpu_buffer_location =
+                            buffer_address + sub.offset;
+                    } else {
+                        // Descriptor table
+                        let size = sub.size_to(buffer.requirements.size);
+
+                        if bind_info.content.contains(r::DescriptorContent::CBV) {
+                            // Making the size field of buffer requirements for uniform
+                            // buffers a multiple of 256 and setting the required offset
+                            // alignment to 256 allows us to patch the size here.
+                            // We can always enforce the size to be aligned to 256 for
+                            // CBVs without going out-of-bounds.
+                            let mask = d3d12::D3D12_CONSTANT_BUFFER_DATA_PLACEMENT_ALIGNMENT - 1;
+                            let desc = d3d12::D3D12_CONSTANT_BUFFER_VIEW_DESC {
+                                BufferLocation: (*buffer.resource).GetGPUVirtualAddress()
+                                    + sub.offset,
+                                SizeInBytes: (size as u32 + mask) as u32 & !mask,
+                            };
+                            let handle = descriptor_updater.alloc_handle(self.raw);
+                            self.raw.CreateConstantBufferView(&desc, handle);
+                            src_cbv = Some(handle);
+                        }
+                        if bind_info.content.contains(r::DescriptorContent::SRV) {
+                            assert_eq!(size % 4, 0);
+                            let mut desc = d3d12::D3D12_SHADER_RESOURCE_VIEW_DESC {
+                                Format: dxgiformat::DXGI_FORMAT_R32_TYPELESS,
+                                Shader4ComponentMapping: IDENTITY_MAPPING,
+                                ViewDimension: d3d12::D3D12_SRV_DIMENSION_BUFFER,
+                                u: mem::zeroed(),
+                            };
+                            *desc.u.Buffer_mut() = d3d12::D3D12_BUFFER_SRV {
+                                FirstElement: sub.offset as _,
+                                NumElements: (size / 4) as _,
+                                StructureByteStride: 0,
+                                Flags: d3d12::D3D12_BUFFER_SRV_FLAG_RAW,
+                            };
+                            let handle = descriptor_updater.alloc_handle(self.raw);
+                            self.raw.CreateShaderResourceView(
+                                buffer.resource.as_mut_ptr(),
+                                &desc,
+                                handle,
+                            );
+                            src_srv = Some(handle);
+                        }
+                        if bind_info.content.contains(r::DescriptorContent::UAV) {
+                            assert_eq!(size % 4, 0);
+                            let mut desc = d3d12::D3D12_UNORDERED_ACCESS_VIEW_DESC {
+                                Format: dxgiformat::DXGI_FORMAT_R32_TYPELESS,
+                                ViewDimension: d3d12::D3D12_UAV_DIMENSION_BUFFER,
+                                u: mem::zeroed(),
+                            };
+                            *desc.u.Buffer_mut() = d3d12::D3D12_BUFFER_UAV {
+                                FirstElement: sub.offset as _,
+                                NumElements: (size / 4) as _,
+                                StructureByteStride: 0,
+                                CounterOffsetInBytes: 0,
+                                Flags: d3d12::D3D12_BUFFER_UAV_FLAG_RAW,
+                            };
+                            let handle = descriptor_updater.alloc_handle(self.raw);
+                            self.raw.CreateUnorderedAccessView(
+                                buffer.resource.as_mut_ptr(),
+                                ptr::null_mut(),
+                                &desc,
+                                handle,
+                            );
+                            src_uav = Some(handle);
+                        }
+                    }
+                }
+                pso::Descriptor::Image(image, _layout) => {
+                    if bind_info.content.contains(r::DescriptorContent::SRV) {
+                        src_srv = image.handle_srv.map(|h| h.raw);
+                    }
+                    if bind_info.content.contains(r::DescriptorContent::UAV) {
+                        src_uav = image.handle_uav.map(|h| h.raw);
+                    }
+                }
+                pso::Descriptor::CombinedImageSampler(image, _layout, sampler) => {
+                    src_srv = image.handle_srv.map(|h| h.raw);
+                    set.sampler_origins[sampler_offset] = sampler.handle.raw;
+                    sampler_offset += 1;
+                }
+                pso::Descriptor::Sampler(sampler) => {
+                    set.sampler_origins[sampler_offset] = sampler.handle.raw;
+                    sampler_offset += 1;
+                }
+                pso::Descriptor::TexelBuffer(buffer_view) => {
+                    if bind_info.content.contains(r::DescriptorContent::SRV) {
+                        let handle = buffer_view.handle_srv
+                            .expect("SRV handle of the storage texel buffer is zero (not supported by specified format)");
+                        src_srv = Some(handle.raw);
+                    }
+                    if bind_info.content.contains(r::DescriptorContent::UAV) {
+                        let handle = buffer_view.handle_uav
+                            .expect("UAV handle of the storage texel buffer is zero (not supported by specified format)");
+                        src_uav = Some(handle.raw);
+                    }
+                }
+            }
+
+            if let Some(handle) = src_cbv {
+                trace!("\tcbv offset {}", offset);
+                accum.src_views.add(handle, 1);
+                accum
+                    .dst_views
+                    .add(bind_info.view_range.as_ref().unwrap().at(offset), 1);
+            }
+            if let Some(handle) = src_srv {
+                trace!("\tsrv offset {}", offset);
+                accum.src_views.add(handle, 1);
+                accum
+                    .dst_views
+                    .add(bind_info.view_range.as_ref().unwrap().at(offset), 1);
+            }
+            if let Some(handle) = src_uav {
+                let uav_offset = if bind_info.content.contains(r::DescriptorContent::SRV) {
+                    bind_info.count + offset
+                } else {
+                    offset
+                };
+                trace!("\tuav offset {}", uav_offset);
+                accum.src_views.add(handle, 1);
+                accum
+                    .dst_views
+                    .add(bind_info.view_range.as_ref().unwrap().at(uav_offset), 1);
+            }
+
+            offset += 1;
+        }
+
+        if sampler_offset != base_sampler_offset {
+            set.update_samplers(&self.samplers.heap, &self.samplers.origins, &mut accum);
+        }
+
+        accum.flush(self.raw);
+    }
 }
 
 impl d::Device<B> for Device {
@@ -2110,13 +2292,16 @@ impl d::Device<B> for Device {
 
         // TODO: check maximum number of rtvs
         // Get associated subpass information
-        let pass = {
-            let subpass = &desc.subpass;
-            match subpass.main_pass.subpasses.get(subpass.index as usize) {
-                Some(subpass) => subpass,
-                None => return Err(pso::CreationError::InvalidSubpass(subpass.index)),
+        let subpass = match desc.target {
+            pso::PipelineTarget::Subpass(ref subpass) => subpass,
+            pso::PipelineTarget::Rendering { .. } => {
+                return Err(pso::CreationError::UnsupportedPipeline)
             }
         };
+        let pass = match subpass.main_pass.subpasses.get(subpass.index as usize) {
+            Some(subpass) => subpass,
+            None => return Err(pso::CreationError::InvalidSubpass(subpass.index)),
+        };
 
         // Get color attachment formats from subpass
         let (rtvs, num_rtvs) = {
@@ -2124,7 +2309,7 @@ impl d::Device<B> for Device {
                 d3d12::D3D12_SIMULTANEOUS_RENDER_TARGET_COUNT as usize];
             let mut num_rtvs = 0;
             for (rtv, target) in rtvs.iter_mut().zip(pass.color_attachments.iter()) {
-                let format = desc.subpass.main_pass.attachments[target.0].format;
+                let format = subpass.main_pass.attachments[target.0].format;
                 *rtv = format
                     .and_then(conv::map_format)
                     .unwrap_or(dxgiformat::DXGI_FORMAT_UNKNOWN);
@@ -2192,7 +2377,7 @@ impl d::Device<B> for Device {
             DSVFormat: pass
                 .depth_stencil_attachment
                 .and_then(|att_ref| {
-                    desc.subpass.main_pass.attachments[att_ref.0]
+                    subpass.main_pass.attachments[att_ref.0]
                         .format
                         .and_then(|f| conv::map_format_dsv(f.base_format().0))
                 })
@@ -2460,6 +2645,10 @@ impl d::Device<B> for Device {
         Ok(())
     }
 
+    unsafe fn get_buffer_device_address(&self, _buffer: &r::Buffer) -> buffer::DeviceAddress {
+        unreachable!("Feature BUFFER_DEVICE_ADDRESS is not supported")
+    }
+
     unsafe fn create_buffer_view(
         &self,
         buffer: &r::Buffer,
@@ -2989,174 +3178,37 @@ impl d::Device<B> for Device {
     where
         I: Iterator<Item = pso::Descriptor<'a, B>>,
     {
-        let mut descriptor_updater = self.descriptor_updater.lock();
-        descriptor_updater.reset();
-
-        let mut accum = descriptors_cpu::MultiCopyAccumulator::default();
-        debug!("write_descriptor_set");
-
-        let mut offset = op.array_offset as u64;
-        let mut target_binding = op.binding as usize;
-        let base_sampler_offset = op.set.sampler_offset(op.binding, op.array_offset);
-        trace!("\tsampler offset {}", base_sampler_offset);
-        let mut sampler_offset = base_sampler_offset;
-        debug!("\tbinding {} array offset {}", target_binding, offset);
-
-        for descriptor in op.descriptors {
-            // spill over the writes onto the next binding
-            while offset >= op.set.binding_infos[target_binding].count {
-                target_binding += 1;
-                offset = 0;
-            }
-            let bind_info = &mut op.set.binding_infos[target_binding];
-            let mut src_cbv = None;
-            let mut src_srv = None;
-            let mut src_uav = None;
-
-            match descriptor {
-                pso::Descriptor::Buffer(buffer, ref sub) => {
-                    let buffer = buffer.expect_bound();
-
-                    if bind_info.content.is_dynamic() {
-                        // Root Descriptor
-                        let buffer_address = (*buffer.resource).GetGPUVirtualAddress();
-                        // Descriptor sets need to be externally synchronized according to specification
-                        bind_info.dynamic_descriptors[offset as usize].gpu_buffer_location =
-                            buffer_address + sub.offset;
-                    } else {
-                        // Descriptor table
-                        let size = sub.size_to(buffer.requirements.size);
-
-                        if bind_info.content.contains(r::DescriptorContent::CBV) {
-                            // Making the size field of buffer requirements for uniform
-                            // buffers a multiple of 256 and setting the required offset
-                            // alignment to 256 allows us to patch the size here.
-                            // We can always enforce the size to be aligned to 256 for
-                            // CBVs without going out-of-bounds.
-                            let mask = d3d12::D3D12_CONSTANT_BUFFER_DATA_PLACEMENT_ALIGNMENT - 1;
-                            let desc = d3d12::D3D12_CONSTANT_BUFFER_VIEW_DESC {
-                                BufferLocation: (*buffer.resource).GetGPUVirtualAddress()
-                                    + sub.offset,
-                                SizeInBytes: (size as u32 + mask) as u32 & !mask,
-                            };
-                            let handle = descriptor_updater.alloc_handle(self.raw);
-                            self.raw.CreateConstantBufferView(&desc, handle);
-                            src_cbv = Some(handle);
-                        }
-                        if bind_info.content.contains(r::DescriptorContent::SRV) {
-                            assert_eq!(size % 4, 0);
-                            let mut desc = d3d12::D3D12_SHADER_RESOURCE_VIEW_DESC {
-                                Format: dxgiformat::DXGI_FORMAT_R32_TYPELESS,
-                                Shader4ComponentMapping: IDENTITY_MAPPING,
-                                ViewDimension: d3d12::D3D12_SRV_DIMENSION_BUFFER,
-                                u: mem::zeroed(),
-                            };
-                            *desc.u.Buffer_mut() = d3d12::D3D12_BUFFER_SRV {
-                                FirstElement: sub.offset as _,
-                                NumElements: (size / 4) as _,
-                                StructureByteStride: 0,
-                                Flags: d3d12::D3D12_BUFFER_SRV_FLAG_RAW,
-                            };
-                            let handle = descriptor_updater.alloc_handle(self.raw);
-                            self.raw.CreateShaderResourceView(
-                                buffer.resource.as_mut_ptr(),
-                                &desc,
-                                handle,
-                            );
-                            src_srv = Some(handle);
-                        }
-                        if bind_info.content.contains(r::DescriptorContent::UAV) {
-                            assert_eq!(size % 4, 0);
-                            let mut desc = d3d12::D3D12_UNORDERED_ACCESS_VIEW_DESC {
-                                Format: dxgiformat::DXGI_FORMAT_R32_TYPELESS,
-                                ViewDimension: d3d12::D3D12_UAV_DIMENSION_BUFFER,
-                                u: mem::zeroed(),
-                            };
-                            *desc.u.Buffer_mut() = d3d12::D3D12_BUFFER_UAV {
-                                FirstElement: sub.offset as _,
-                                NumElements: (size / 4) as _,
-                                StructureByteStride: 0,
-                                CounterOffsetInBytes: 0,
-                                Flags: d3d12::D3D12_BUFFER_UAV_FLAG_RAW,
-                            };
-                            let handle = descriptor_updater.alloc_handle(self.raw);
-                            self.raw.CreateUnorderedAccessView(
-                                buffer.resource.as_mut_ptr(),
-                                ptr::null_mut(),
-                                &desc,
-                                handle,
-                            );
-                            src_uav = Some(handle);
-                        }
-                    }
-                }
-                pso::Descriptor::Image(image, _layout) => {
-                    if bind_info.content.contains(r::DescriptorContent::SRV) {
-                        src_srv = image.handle_srv.map(|h| h.raw);
-                    }
-                    if bind_info.content.contains(r::DescriptorContent::UAV) {
-                        src_uav = image.handle_uav.map(|h| h.raw);
-                    }
-                }
-                pso::Descriptor::CombinedImageSampler(image, _layout, sampler) => {
-                    src_srv = image.handle_srv.map(|h| h.raw);
-                    op.set.sampler_origins[sampler_offset] = sampler.handle.raw;
-                    sampler_offset += 1;
-                }
-                pso::Descriptor::Sampler(sampler) => {
-                    op.set.sampler_origins[sampler_offset] = sampler.handle.raw;
-                    sampler_offset += 1;
-                }
-                pso::Descriptor::TexelBuffer(buffer_view) => {
-                    if bind_info.content.contains(r::DescriptorContent::SRV) {
-                        let handle = buffer_view.handle_srv
-                            .expect("SRV handle of the storage texel buffer is zero (not supported by specified format)");
-                        src_srv = Some(handle.raw);
-                    }
-                    if bind_info.content.contains(r::DescriptorContent::UAV) {
-                        let handle = buffer_view.handle_uav
-                            .expect("UAV handle of the storage texel buffer is zero (not supported by specified format)");
-                        src_uav = Some(handle.raw);
-                    }
-                }
-            }
-
-            if let Some(handle) = src_cbv {
-                trace!("\tcbv offset {}", offset);
-                accum.src_views.add(handle, 1);
-                accum
-                    .dst_views
-                    .add(bind_info.view_range.as_ref().unwrap().at(offset), 1);
-            }
-            if let Some(handle) = src_srv {
-                trace!("\tsrv offset {}", offset);
-                accum.src_views.add(handle, 1);
-                accum
-                    .dst_views
-                    .add(bind_info.view_range.as_ref().unwrap().at(offset), 1);
-            }
-            if let Some(handle) = src_uav {
-                let uav_offset = if bind_info.content.contains(r::DescriptorContent::SRV) {
-                    bind_info.count + offset
-                } else {
-                    offset
-                };
-                trace!("\tuav offset {}", uav_offset);
-                accum.src_views.add(handle, 1);
-                accum
-                    .dst_views
-                    .add(bind_info.view_range.as_ref().unwrap().at(uav_offset), 1);
-            }
+        self.write_descriptors(op.set, op.binding, op.array_offset, op.descriptors);
+    }
 
-            offset += 1;
-        }
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        _layout: &r::DescriptorSetLayout,
+        entries: I,
+    ) -> Result<Vec<pso::DescriptorUpdateTemplateEntry>, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorUpdateTemplateEntry>,
+    {
+        Ok(entries.collect())
+    }
 
-        if sampler_offset != base_sampler_offset {
-            op.set
-                .update_samplers(&self.samplers.heap, &self.samplers.origins, &mut accum);
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        set: &mut r::DescriptorSet,
+        template: &Vec<pso::DescriptorUpdateTemplateEntry>,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+        let mut descriptors = descriptors;
+        for entry in template {
+            self.write_descriptors(
+                set,
+                entry.binding,
+                entry.array_offset,
+                descriptors.by_ref().take(entry.count),
+            );
         }
-
-        accum.flush(self.raw);
     }
 
     unsafe fn copy_descriptor_set<'a>(&self, op: pso::DescriptorSetCopy<'a, B>) {
@@ -3396,6 +3448,41 @@ impl d::Device<B> for Device {
         unimplemented!()
     }
 
+    unsafe fn allocate_exportable_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<r::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        _memory: &r::Memory,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<r::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        Ok(0)
+    }
+
     unsafe fn free_memory(&self, memory: r::Memory) {
         memory.heap.destroy();
         if let Some(buffer) = memory.resource {
@@ -3665,6 +3752,13 @@ impl d::Device<B> for Device {
         // Just drop
     }
 
+    unsafe fn destroy_descriptor_update_template(
+        &self,
+        _template: Vec<pso::DescriptorUpdateTemplateEntry>,
+    ) {
+        // Just drop
+    }
+
     unsafe fn destroy_fence(&self, fence: r::Fence) {
         fence.raw.destroy();
     }
@@ -3673,6 +3767,64 @@ impl d::Device<B> for Device {
         semaphore.raw.destroy();
     }
 
+    fn create_timeline_semaphore(
+        &self,
+        _initial_value: u64,
+    ) -> Result<r::Semaphore, d::OutOfMemory> {
+        unimplemented!()
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        _semaphore: &r::Semaphore,
+    ) -> Result<u64, d::WaitError> {
+        unimplemented!()
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        _semaphores: I,
+        _wait: d::WaitFor,
+        _timeout_ns: u64,
+    ) -> Result<bool, d::WaitError>
+    where
+        I: Iterator<Item = (&'a r::Semaphore, u64)>,
+    {
+        unimplemented!()
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        _semaphore: &r::Semaphore,
+        _value: u64,
+    ) -> Result<(), d::OutOfMemory> {
+        unimplemented!()
+    }
+
+    fn create_exportable_semaphore(
+        &self,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<r::Semaphore, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        _semaphore: &r::Semaphore,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        _semaphore: &mut r::Semaphore,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
     unsafe fn destroy_event(&self, _event: ()) {
         unimplemented!()
     }
@@ -664,6 +664,18 @@ impl q::Queue<Backend> for Queue {
         }
     }
 
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        _command_buffers: Ic,
+        _wait_semaphores: Iw,
+        _signal_semaphores: Is,
+        _fence: Option<&mut resource::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a command::CommandBuffer>,
+    {
+        unimplemented!()
+    }
+
     unsafe fn present(
         &mut self,
         surface: &mut window::Surface,
@@ -925,7 +937,7 @@ impl hal::Instance<Backend> for Instance {
     fn create(_: &str, _: u32) -> Result<Self, hal::UnsupportedBackend> {
         let lib_main = match native::D3D12Lib::new() {
             Ok(lib) => lib,
-            Err(_) => return Err(hal::UnsupportedBackend),
+            Err(_) => return Err(hal::UnsupportedBackend::new("Unable to load d3d12.dll")),
         };
 
         #[cfg(debug_assertions)]
@@ -962,9 +974,11 @@ impl hal::Instance<Backend> for Instance {
             Ok((factory, hr)) if winerror::SUCCEEDED(hr) => factory,
             Ok((_, hr)) => {
                 info!("Failed on dxgi factory creation: {:?}", hr);
-                return Err(hal::UnsupportedBackend);
+                return Err(hal::UnsupportedBackend::new(
+                    "Unable to create a DXGI factory",
+                ));
             }
-            Err(_) => return Err(hal::UnsupportedBackend),
+            Err(_) => return Err(hal::UnsupportedBackend::new("Unable to load dxgi.dll")),
         };
 
         Ok(Instance {
@@ -1509,6 +1523,7 @@ impl hal::Backend for Backend {
     type DescriptorSetLayout = resource::DescriptorSetLayout;
     type DescriptorPool = resource::DescriptorPool;
     type DescriptorSet = resource::DescriptorSet;
+    type DescriptorUpdateTemplate = Vec<hal::pso::DescriptorUpdateTemplateEntry>;
 
     type Fence = resource::Fence;
     type Semaphore = resource::Semaphore;
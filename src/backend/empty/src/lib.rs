@@ -13,7 +13,14 @@ use crate::{
 use hal::{adapter, command, device, format, pass, pool, pso, query, queue, window};
 use log::debug;
 
-use std::{borrow::Borrow, ops::Range};
+use std::{
+    borrow::Borrow,
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 mod buffer;
 mod descriptor;
@@ -55,16 +62,36 @@ impl hal::Backend for Backend {
     type DescriptorSetLayout = DescriptorSetLayout;
     type DescriptorPool = DescriptorPool;
     type DescriptorSet = DescriptorSet;
+    type DescriptorUpdateTemplate = ();
 
     type Fence = ();
-    type Semaphore = ();
+    type Semaphore = Semaphore;
     type Event = ();
     type QueryPool = ();
 }
 
 /// Dummy physical device.
 #[derive(Debug)]
-pub struct PhysicalDevice;
+pub struct PhysicalDevice {
+    heap_usage: memory::HeapUsage,
+}
+
+impl PhysicalDevice {
+    fn new() -> Self {
+        let heap_count = memory_heaps().len();
+        PhysicalDevice {
+            heap_usage: Arc::new((0..heap_count).map(|_| AtomicU64::new(0)).collect()),
+        }
+    }
+}
+
+fn memory_heaps() -> Vec<adapter::MemoryHeap> {
+    // TODO: perhaps get an estimate of free RAM to report here?
+    vec![adapter::MemoryHeap {
+        size: 64 * 1024,
+        flags: hal::memory::HeapFlags::empty(),
+    }]
+}
 impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
     unsafe fn open(
         &self,
@@ -96,7 +123,9 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             vec![queue_group]
         };
         let gpu = adapter::Gpu {
-            device: Device,
+            device: Device {
+                heap_usage: Arc::clone(&self.heap_usage),
+            },
             queue_groups,
         };
         Ok(gpu)
@@ -130,19 +159,28 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             };
             vec![memory_type]
         };
-        // TODO: perhaps get an estimate of free RAM to report here?
-        let memory_heaps = vec![adapter::MemoryHeap {
-            size: 64 * 1024,
-            flags: hal::memory::HeapFlags::empty(),
-        }];
         adapter::MemoryProperties {
             memory_types,
-            memory_heaps,
+            memory_heaps: memory_heaps(),
         }
     }
 
+    fn memory_budget(&self) -> Option<Vec<adapter::MemoryHeapBudget>> {
+        let heaps = self.memory_properties().memory_heaps;
+        Some(
+            heaps
+                .iter()
+                .zip(self.heap_usage.iter())
+                .map(|(heap, usage)| adapter::MemoryHeapBudget {
+                    budget: heap.size,
+                    usage: usage.load(Ordering::Relaxed),
+                })
+                .collect(),
+        )
+    }
+
     fn features(&self) -> hal::Features {
-        hal::Features::empty()
+        hal::Features::TIMELINE_SEMAPHORE | hal::Features::DYNAMIC_RENDERING
     }
 
     fn properties(&self) -> hal::PhysicalDeviceProperties {
@@ -167,11 +205,27 @@ impl queue::Queue<Backend> for Queue {
     {
     }
 
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        _: Ic,
+        _: Iw,
+        signal_semaphores: Is,
+        _: Option<&mut ()>,
+    ) where
+        Ic: Iterator<Item = &'a CommandBuffer>,
+        Is: Iterator<Item = (&'a Semaphore, u64)>,
+    {
+        // Nothing is executed, so the submission completes right away.
+        for (semaphore, value) in signal_semaphores {
+            semaphore.signal(value);
+        }
+    }
+
     unsafe fn present(
         &mut self,
         _surface: &mut Surface,
         _image: SwapchainImage,
-        _wait_semaphore: Option<&mut ()>,
+        _wait_semaphore: Option<&mut Semaphore>,
     ) -> Result<Option<window::Suboptimal>, window::PresentError> {
         Ok(None)
     }
@@ -187,7 +241,9 @@ impl queue::Queue<Backend> for Queue {
 
 /// Dummy device doing nothing.
 #[derive(Debug)]
-pub struct Device;
+pub struct Device {
+    heap_usage: memory::HeapUsage,
+}
 impl device::Device<Backend> for Device {
     unsafe fn create_command_pool(
         &self,
@@ -204,7 +260,7 @@ impl device::Device<Backend> for Device {
         memory_type: hal::MemoryTypeId,
         size: u64,
     ) -> Result<Memory, device::AllocationError> {
-        Memory::allocate(memory_type, size)
+        Memory::allocate(memory_type, size, &self.heap_usage)
     }
 
     unsafe fn create_render_pass<'a, Ia, Is, Id>(
@@ -319,6 +375,10 @@ impl device::Device<Backend> for Device {
         Ok(())
     }
 
+    unsafe fn get_buffer_device_address(&self, _: &Buffer) -> hal::buffer::DeviceAddress {
+        unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
+    }
+
     unsafe fn create_buffer_view(
         &self,
         _: &Buffer,
@@ -396,20 +456,113 @@ impl device::Device<Backend> for Device {
         Ok(layout)
     }
 
+    unsafe fn create_push_descriptor_set_layout<'a, I, J>(
+        &self,
+        bindings: I,
+        samplers: J,
+    ) -> Result<DescriptorSetLayout, device::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
+        J: Iterator<Item = &'a ()>,
+    {
+        self.create_descriptor_set_layout(bindings, samplers)
+    }
+
     unsafe fn write_descriptor_set<'a, I>(&self, _: pso::DescriptorSetWrite<'a, Backend, I>)
     where
         I: Iterator<Item = pso::Descriptor<'a, Backend>>,
     {
     }
 
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        _: &DescriptorSetLayout,
+        _: I,
+    ) -> Result<(), device::OutOfMemory> {
+        Ok(())
+    }
+
+    unsafe fn update_descriptor_set_with_template<'a, I>(&self, _: &mut DescriptorSet, _: &(), _: I)
+    where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+    }
+
     unsafe fn copy_descriptor_set<'a>(&self, _: pso::DescriptorSetCopy<'a, Backend>) {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
-    fn create_semaphore(&self) -> Result<(), device::OutOfMemory> {
+    fn create_semaphore(&self) -> Result<Semaphore, device::OutOfMemory> {
+        Ok(Semaphore::default())
+    }
+
+    fn create_timeline_semaphore(
+        &self,
+        initial_value: u64,
+    ) -> Result<Semaphore, device::OutOfMemory> {
+        Ok(Semaphore {
+            value: AtomicU64::new(initial_value),
+        })
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        semaphore: &Semaphore,
+    ) -> Result<u64, device::WaitError> {
+        Ok(semaphore.value.load(Ordering::Acquire))
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        semaphores: I,
+        wait: device::WaitFor,
+        _: u64,
+    ) -> Result<bool, device::WaitError>
+    where
+        I: Iterator<Item = (&'a Semaphore, u64)>,
+    {
+        // All the submitted work is complete, so there is nothing to wait for.
+        let mut reached =
+            semaphores.map(|(semaphore, value)| semaphore.value.load(Ordering::Acquire) >= value);
+        Ok(match wait {
+            device::WaitFor::Any => reached.any(|r| r),
+            device::WaitFor::All => reached.all(|r| r),
+        })
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        semaphore: &Semaphore,
+        value: u64,
+    ) -> Result<(), device::OutOfMemory> {
+        semaphore.signal(value);
         Ok(())
     }
 
+    fn create_exportable_semaphore(
+        &self,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<Semaphore, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        _semaphore: &Semaphore,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        _semaphore: &mut Semaphore,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
     fn create_fence(&self, _: bool) -> Result<(), device::OutOfMemory> {
         Ok(())
     }
@@ -477,6 +630,41 @@ impl device::Device<Backend> for Device {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
+    unsafe fn allocate_exportable_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        _memory: &Memory,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        Ok(0)
+    }
+
     unsafe fn free_memory(&self, _memory: Memory) {
         // Let memory drop
     }
@@ -510,9 +698,11 @@ impl device::Device<Backend> for Device {
 
     unsafe fn destroy_descriptor_set_layout(&self, _: DescriptorSetLayout) {}
 
+    unsafe fn destroy_descriptor_update_template(&self, _: ()) {}
+
     unsafe fn destroy_fence(&self, _: ()) {}
 
-    unsafe fn destroy_semaphore(&self, _: ()) {}
+    unsafe fn destroy_semaphore(&self, _: Semaphore) {}
 
     unsafe fn destroy_event(&self, _: ()) {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
@@ -534,7 +724,7 @@ impl device::Device<Backend> for Device {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
-    unsafe fn set_semaphore_name(&self, _: &mut (), _: &str) {
+    unsafe fn set_semaphore_name(&self, _: &mut Semaphore, _: &str) {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
@@ -571,6 +761,18 @@ impl device::Device<Backend> for Device {
     }
 }
 
+/// Dummy semaphore, only tracking the counter of timeline semaphores.
+#[derive(Debug, Default)]
+pub struct Semaphore {
+    value: AtomicU64,
+}
+
+impl Semaphore {
+    fn signal(&self, value: u64) {
+        self.value.fetch_max(value, Ordering::AcqRel);
+    }
+}
+
 #[derive(Debug)]
 pub struct QueueFamily;
 impl queue::QueueFamily for QueueFamily {
@@ -744,6 +946,10 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
     unsafe fn end_render_pass(&mut self) {}
 
+    unsafe fn begin_rendering(&mut self, _: &command::RenderingInfo<Backend>) {}
+
+    unsafe fn end_rendering(&mut self) {}
+
     unsafe fn bind_graphics_pipeline(&mut self, _: &()) {}
 
     unsafe fn bind_graphics_descriptor_sets<'a, I, J>(&mut self, _: &(), _: usize, _: I, _: J)
@@ -764,6 +970,32 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         // Do nothing
     }
 
+    unsafe fn push_graphics_descriptor_set<'a, I>(
+        &mut self,
+        _: &(),
+        _: usize,
+        _: pso::DescriptorBinding,
+        _: pso::DescriptorArrayIndex,
+        _: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        // Do nothing
+    }
+
+    unsafe fn push_compute_descriptor_set<'a, I>(
+        &mut self,
+        _: &(),
+        _: usize,
+        _: pso::DescriptorBinding,
+        _: pso::DescriptorArrayIndex,
+        _: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        // Do nothing
+    }
+
     unsafe fn dispatch(&mut self, _: hal::WorkGroupCount) {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
@@ -1052,7 +1284,7 @@ impl hal::Instance<Backend> for Instance {
         };
         let adapter = adapter::Adapter {
             info,
-            physical_device: PhysicalDevice,
+            physical_device: PhysicalDevice::new(),
             // TODO: multiple queue families
             queue_families: vec![QueueFamily],
         };
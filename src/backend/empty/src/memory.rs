@@ -5,6 +5,13 @@ use hal::{
 };
 use std::cell::UnsafeCell;
 use std::convert::TryInto;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Total size of the live memory allocations of the devices of an adapter, per heap.
+pub(crate) type HeapUsage = Arc<Vec<AtomicU64>>;
 
 /// A memory allocation.
 #[derive(Debug)]
@@ -15,10 +22,16 @@ pub struct Memory {
     size: u64,
     /// The backing memory allocation.
     data: UnsafeCell<Box<[u8]>>,
+    /// Usage of the heaps, updated when the memory is freed.
+    heap_usage: HeapUsage,
 }
 
 impl Memory {
-    pub fn allocate(memory_type: MemoryTypeId, size: u64) -> Result<Self, AllocationError> {
+    pub fn allocate(
+        memory_type: MemoryTypeId,
+        size: u64,
+        heap_usage: &HeapUsage,
+    ) -> Result<Self, AllocationError> {
         assert_eq!(memory_type.0, 0, "We only support one memory type");
 
         // Allocate a buffer in RAM
@@ -36,7 +49,10 @@ impl Memory {
             memory_type,
             size,
             data: UnsafeCell::new(data),
+            heap_usage: Arc::clone(heap_usage),
         };
+        // The only memory type is in the first heap.
+        memory.heap_usage[0].fetch_add(size, Ordering::Relaxed);
         Ok(memory)
     }
 
@@ -55,4 +71,10 @@ impl Memory {
     }
 }
 
+impl Drop for Memory {
+    fn drop(&mut self) {
+        self.heap_usage[0].fetch_sub(self.size, Ordering::Relaxed);
+    }
+}
+
 unsafe impl Sync for Memory {}
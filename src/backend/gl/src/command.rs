@@ -9,6 +9,8 @@ use hal::{
 };
 
 use crate::{
+    device,
+    dump::ObjectLabels,
     info, native as n,
     pool::{self, BufferMemory},
     Backend, ColorSlot,
@@ -88,6 +90,7 @@ pub enum Command {
         depth_range_ptr: BufferSlice,
     },
     SetScissors(u32, BufferSlice),
+    SetScissorTest(bool),
     SetBlendColor(pso::ColorValue),
 
     /// Clear floating-point color drawbuffer of bound framebuffer.
@@ -108,6 +111,18 @@ pub enum Command {
         colors: ArrayVec<[n::ImageView; MAX_COLOR_ATTACHMENTS]>,
         depth_stencil: Option<n::ImageView>,
     },
+    /// Detach the given attachment points of the bound framebuffer.
+    DetachAttachments(FrameBufferTarget, AttachmentPoints),
+    /// Discard the contents of the given attachment points of the bound framebuffer.
+    InvalidateAttachments(FrameBufferTarget, AttachmentPoints),
+    /// Blit from the bound read framebuffer, reading from the given color buffer.
+    BlitFramebuffer {
+        read_buffer: u32,
+        src: pso::Rect,
+        dst: pso::Rect,
+        mask: u32,
+        filter: u32,
+    },
     SetDrawColorBuffers(ArrayVec<[u8; MAX_COLOR_ATTACHMENTS]>),
     SetPatchSize(i32),
     BindProgram(<GlContext as glow::HasContext>::Program),
@@ -133,22 +148,21 @@ pub enum Command {
         dst_buffer: n::RawBuffer,
         data: command::BufferImageCopy,
     },
-    CopyRenderbufferToBuffer(n::Renderbuffer, n::RawBuffer, command::BufferImageCopy),
-    CopyImageToTexture(
-        n::ImageType,
-        n::Texture,
-        n::TextureTarget,
-        command::ImageCopy,
-    ),
-    CopyImageToRenderbuffer {
-        src_image: n::ImageType,
-        dst_renderbuffer: n::Renderbuffer,
-        dst_format: n::TextureFormat,
-        data: command::ImageCopy,
+    /// Read pixels from the bound read framebuffer into a buffer.
+    ReadPixels {
+        read_buffer: u32,
+        rect: pso::Rect,
+        format: n::TextureFormat,
+        pixel_type: n::DataType,
+        dst_buffer: n::RawBuffer,
+        buffer_offset: buffer::Offset,
+        /// Length of the buffer rows, in texels.
+        row_length: u32,
     },
 
     BindBufferRange(u32, u32, n::RawBuffer, i32, i32),
     BindTexture(u32, n::Texture, n::TextureTarget),
+    BindImage(u32, n::ImageBinding),
     BindSampler(u32, n::Sampler),
     SetTextureSamplerSettings(u32, n::TextureTarget, image::SamplerDesc),
 
@@ -156,24 +170,39 @@ pub enum Command {
     SetDepthMask(bool),
     SetStencilMask(pso::StencilValue),
     SetStencilMaskSeparate(pso::Sided<pso::StencilValue>),
+    /// Set the stencil test, with the reference values and read masks resolved.
+    BindStencil(Option<pso::StencilTest>),
+    SetLineWidth(f32),
+    SetDepthBoundsTest(bool),
+    SetDepthBounds(Range<f32>),
 
     MemoryBarrier(u32),
+
+    InsertDebugMarker(BufferSlice),
+    PushDebugGroup(BufferSlice),
+    PopDebugGroup,
+
+    SetEvent(Arc<Mutex<n::EventState>>),
+    ResetEvent(Arc<Mutex<n::EventState>>),
+    WaitEvent(Arc<Mutex<n::EventState>>),
 }
 
 pub type FrameBufferTarget = u32;
 pub type DrawBuffer = u32;
+pub type AttachmentPoints = ArrayVec<[u32; MAX_COLOR_ATTACHMENTS + 1]>;
 
 #[derive(Clone, Debug)]
 struct AttachmentInfo {
-    //subpass_id: pass::SubpassId,
-    color_index: Option<u8>,
+    view: n::ImageView,
     clear_value: command::ClearValue,
 }
 
 #[derive(Debug)]
 pub struct RenderPassCache {
     render_pass: n::RenderPass,
+    framebuffer: n::RawFramebuffer,
     attachments: Vec<AttachmentInfo>,
+    render_area: pso::Rect,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -193,6 +222,8 @@ struct Cache {
     stencil_ref: Option<(pso::StencilValue, pso::StencilValue)>,
     // Blend color.
     blend_color: Option<pso::ColorValue>,
+    // Last set scissors (first scissor, rectangles).
+    scissors: Option<(u32, BufferSlice)>,
     ///
     framebuffer: Option<(FrameBufferTarget, n::RawFramebuffer)>,
     ///
@@ -216,6 +247,14 @@ struct Cache {
     depth_mask: Option<bool>,
     // Current stencil mask
     stencil_mask: Option<pso::Sided<pso::StencilValue>>,
+    // Stencil test of the current pipeline.
+    stencil: Option<pso::StencilTest>,
+    // Dynamic stencil read masks.
+    stencil_read_mask: Option<pso::Sided<pso::StencilValue>>,
+    // Dynamic stencil write masks.
+    stencil_write_mask: Option<pso::Sided<pso::StencilValue>>,
+    // Depth bounds test state of the current pipeline.
+    depth_bounds_test: Option<bool>,
     /// Currently bound samplers.
     samplers: Vec<Option<n::FatSampler>>,
     /// Current sampler redirection map.
@@ -229,6 +268,7 @@ impl Cache {
             index_type_range: None,
             stencil_ref: None,
             blend_color: None,
+            scissors: None,
             framebuffer: None,
             error_state: false,
             patch_size: None,
@@ -240,6 +280,10 @@ impl Cache {
             uniforms: Vec::new(),
             depth_mask: None,
             stencil_mask: None,
+            stencil: None,
+            stencil_read_mask: None,
+            stencil_write_mask: None,
+            depth_bounds_test: None,
             samplers: (0..MAX_SAMPLERS).map(|_| None).collect(),
             texture_slots: [TextureSlotInfo::default(); MAX_TEXTURE_SLOTS],
         }
@@ -251,12 +295,16 @@ impl Cache {
 #[derive(Debug, Clone, Copy)]
 pub struct Limits {
     max_viewports: usize,
+    min_uniform_buffer_offset_alignment: buffer::Offset,
+    min_storage_buffer_offset_alignment: buffer::Offset,
 }
 
 impl From<hal::Limits> for Limits {
     fn from(l: hal::Limits) -> Self {
         Limits {
             max_viewports: l.max_viewports,
+            min_uniform_buffer_offset_alignment: l.min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment: l.min_storage_buffer_offset_alignment,
         }
     }
 }
@@ -354,6 +402,8 @@ pub struct CommandBuffer {
     individual_reset: bool,
 
     fbo: Option<n::RawFramebuffer>,
+    read_fbo: Option<n::RawFramebuffer>,
+    render_fbo: Option<n::RawFramebuffer>,
     /// The framebuffer to use for rendering to the main targets (0 by default).
     ///
     /// Use this to set the framebuffer that will be used for the screen display targets created
@@ -372,14 +422,21 @@ pub struct CommandBuffer {
     limits: Limits,
     legacy_featues: info::LegacyFeatures,
     active_attribs: usize,
+    /// Debug name, used for a debug group around the submitted commands.
+    pub(crate) name: Option<String>,
+    /// Names of the objects, resolved in the dumps.
+    pub(crate) labels: Arc<ObjectLabels>,
 }
 
 impl CommandBuffer {
     pub(crate) fn new(
         fbo: Option<n::RawFramebuffer>,
+        read_fbo: Option<n::RawFramebuffer>,
+        render_fbo: Option<n::RawFramebuffer>,
         limits: Limits,
         memory: Arc<Mutex<BufferMemory>>,
         legacy_featues: info::LegacyFeatures,
+        labels: Arc<ObjectLabels>,
     ) -> Self {
         let (id, individual_reset) = {
             let mut memory = memory
@@ -409,6 +466,8 @@ impl CommandBuffer {
             },
             individual_reset,
             fbo,
+            read_fbo,
+            render_fbo,
             display_fb: None,
             cache: Cache::new(),
             pass_cache: None,
@@ -416,6 +475,8 @@ impl CommandBuffer {
             limits,
             active_attribs: 0,
             legacy_featues,
+            name: None,
+            labels,
         }
     }
 
@@ -518,63 +579,83 @@ impl CommandBuffer {
 
     fn begin_subpass(&mut self) {
         let state = self.pass_cache.as_ref().unwrap();
-        let subpass = &state.render_pass.subpasses[self.cur_subpass as usize];
+        let render_pass = &state.render_pass;
+        let subpass_id = self.cur_subpass;
+        let subpass = &render_pass.subpasses[subpass_id as usize];
 
-        // See `begin_renderpass_cache` for clearing strategy
+        // Only the attachments of the current sub-pass are bound, so that the
+        // other ones can be read as input attachments without a feedback loop.
+        self.data
+            .push_cmd(bind_subpass_attachments(state, subpass, None));
 
         // Bind draw buffers for mapping color output locations with
         // framebuffer attachments.
-        let attachment_indices = subpass
-            .color_attachments
-            .iter()
-            .map(|&index| state.attachments[index].color_index.unwrap())
-            .collect();
+        let attachment_indices = (0..subpass.color_attachments.len() as u8).collect();
         self.data
             .push_cmd(Command::SetDrawColorBuffers(attachment_indices));
         self.data
             .push_cmd(Command::SetColorMask(None, pso::ColorMask::ALL));
 
-        for (rat, info) in state
-            .render_pass
-            .attachments
+        // Contents of the attachments with `DontCare` load operation can be discarded.
+        let mut invalidate = AttachmentPoints::new();
+        for (draw_color_index, &index) in subpass.color_attachments.iter().enumerate() {
+            let rat = &render_pass.attachments[index];
+            if rat.ops.load == pass::AttachmentLoadOp::DontCare
+                && render_pass.is_first_use(index, subpass_id)
+            {
+                invalidate.push(glow::COLOR_ATTACHMENT0 + draw_color_index as u32);
+            }
+        }
+        if let Some(index) = subpass.depth_stencil {
+            let rat = &render_pass.attachments[index];
+            let view_format = rat.format.unwrap();
+            if render_pass.is_first_use(index, subpass_id) {
+                if view_format.is_depth() && rat.ops.load == pass::AttachmentLoadOp::DontCare {
+                    invalidate.push(glow::DEPTH_ATTACHMENT);
+                }
+                if view_format.is_stencil()
+                    && rat.stencil_ops.load == pass::AttachmentLoadOp::DontCare
+                {
+                    invalidate.push(glow::STENCIL_ATTACHMENT);
+                }
+            }
+        }
+        if !invalidate.is_empty() {
+            self.data.push_cmd(Command::InvalidateAttachments(
+                glow::DRAW_FRAMEBUFFER,
+                invalidate,
+            ));
+        }
+
+        // Clear the attachments when they are used for the first time.
+        let clears = subpass
+            .color_attachments
             .iter()
-            .zip(state.attachments.iter())
-        {
-            //TODO: check if the subpass is one where we need this clear
+            .enumerate()
+            .map(|(draw_color_index, &index)| (index, Some(draw_color_index as u8)))
+            .chain(subpass.depth_stencil.map(|index| (index, None)))
+            .filter(|&(index, _)| render_pass.is_first_use(index, subpass_id));
+
+        for (index, color_index) in clears {
+            let rat = &render_pass.attachments[index];
+            let info = &state.attachments[index];
 
             // View format needs to be known at this point.
             // All attachments specified in the renderpass must have a valid,
             // matching image view bound in the framebuffer.
             let view_format = rat.format.unwrap();
 
-            match info.color_index {
+            match color_index {
                 // Clear color target
                 Some(draw_color_index) => {
                     if rat.ops.load != pass::AttachmentLoadOp::Clear {
                         continue;
                     }
-                    let channel = view_format.base_format().1;
-                    let draw_color_index = draw_color_index as u32;
-
-                    self.data.push_cmd(match channel {
-                        ChannelType::Unorm
-                        | ChannelType::Snorm
-                        | ChannelType::Ufloat
-                        | ChannelType::Sfloat
-                        | ChannelType::Srgb
-                        | ChannelType::Uscaled
-                        | ChannelType::Sscaled => {
-                            Command::ClearBufferColorF(draw_color_index, unsafe {
-                                info.clear_value.color.float32
-                            })
-                        }
-                        ChannelType::Uint => Command::ClearBufferColorU(draw_color_index, unsafe {
-                            info.clear_value.color.uint32
-                        }),
-                        ChannelType::Sint => Command::ClearBufferColorI(draw_color_index, unsafe {
-                            info.clear_value.color.sint32
-                        }),
-                    });
+                    self.data.push_cmd(clear_color_command(
+                        draw_color_index as DrawBuffer,
+                        view_format.base_format().1,
+                        unsafe { info.clear_value.color },
+                    ));
                 }
                 // Clear depth-stencil target
                 None => {
@@ -586,9 +667,6 @@ impl CommandBuffer {
                         None
                     };
 
-                    // Only reset depth mask if it was non writable
-                    let depth_mask = self.cache.depth_mask.filter(|mask| !mask);
-
                     let stencil = if view_format.is_stencil()
                         && rat.stencil_ops.load == pass::AttachmentLoadOp::Clear
                     {
@@ -597,34 +675,105 @@ impl CommandBuffer {
                         None
                     };
 
-                    let stencil_mask = self
-                        .cache
-                        .stencil_mask
-                        .filter(|mask| mask.front != !0 || mask.back != !0);
+                    push_clear_depth_stencil(&mut self.data, &self.cache, depth, stencil);
+                }
+            }
+        }
+    }
 
-                    // Temporarily reset masks as they may prevent buffer clear in gl
-                    if depth_mask.is_some() || self.cache.depth_mask.is_none() {
-                        self.data.push_cmd(Command::SetDepthMask(true));
-                    }
-                    if stencil_mask.is_some() || self.cache.stencil_mask.is_none() {
-                        self.data.push_cmd(Command::SetStencilMask(!0));
-                    }
+    fn end_subpass(&mut self) {
+        let state = self.pass_cache.as_ref().unwrap();
+        let render_pass = &state.render_pass;
+        let subpass_id = self.cur_subpass;
+        let subpass = &render_pass.subpasses[subpass_id as usize];
+        let rect = state.render_area;
+
+        // Resolve the multisampled color attachments.
+        for (draw_color_index, &resolve) in subpass.resolve_attachments.iter().enumerate() {
+            if resolve == pass::ATTACHMENT_UNUSED {
+                continue;
+            }
+            match self.fbo {
+                Some(fbo) => {
+                    self.data.push_cmd(Command::BindFramebuffer {
+                        target: glow::READ_FRAMEBUFFER,
+                        framebuffer: state.framebuffer,
+                        colors: ArrayVec::new(),
+                        depth_stencil: None,
+                    });
+                    self.data.push_cmd(Command::BindFramebuffer {
+                        target: glow::DRAW_FRAMEBUFFER,
+                        framebuffer: fbo,
+                        colors: iter::once(state.attachments[resolve].view.clone()).collect(),
+                        depth_stencil: None,
+                    });
+                    self.data
+                        .push_cmd(Command::SetDrawColorBuffers(iter::once(0).collect()));
+                    self.data.push_cmd(Command::BlitFramebuffer {
+                        read_buffer: glow::COLOR_ATTACHMENT0 + draw_color_index as u32,
+                        src: rect,
+                        dst: rect,
+                        mask: glow::COLOR_BUFFER_BIT,
+                        filter: glow::NEAREST,
+                    });
+                    self.data.push_cmd(Command::DetachAttachments(
+                        glow::DRAW_FRAMEBUFFER,
+                        iter::once(glow::COLOR_ATTACHMENT0).collect(),
+                    ));
+                }
+                None => {
+                    error!("Can't resolve attachments without framebuffer objects");
+                    break;
+                }
+            }
+        }
 
-                    if depth.is_some() || stencil.is_some() {
-                        self.data
-                            .push_cmd(Command::ClearBufferDepthStencil(depth, stencil));
-                    }
+        self.data.push_cmd(Command::BindFramebuffer {
+            target: glow::DRAW_FRAMEBUFFER,
+            framebuffer: state.framebuffer,
+            colors: ArrayVec::new(),
+            depth_stencil: None,
+        });
 
-                    // Restore masks if they were reset
-                    if let Some(mask) = depth_mask {
-                        self.data.push_cmd(Command::SetDepthMask(mask));
-                    }
-                    if let Some(mask) = stencil_mask {
-                        self.data.push_cmd(Command::SetStencilMaskSeparate(mask));
-                    }
+        // Contents of the attachments with `DontCare` store operation can be discarded
+        // after their last use.
+        let mut invalidate = AttachmentPoints::new();
+        let mut detach = AttachmentPoints::new();
+        for (draw_color_index, &index) in subpass.color_attachments.iter().enumerate() {
+            let point = glow::COLOR_ATTACHMENT0 + draw_color_index as u32;
+            let rat = &render_pass.attachments[index];
+            if rat.ops.store == pass::AttachmentStoreOp::DontCare
+                && render_pass.is_last_use(index, subpass_id)
+            {
+                invalidate.push(point);
+            }
+            detach.push(point);
+        }
+        if let Some(index) = subpass.depth_stencil {
+            let rat = &render_pass.attachments[index];
+            let view_format = rat.format.unwrap();
+            if render_pass.is_last_use(index, subpass_id) {
+                if view_format.is_depth() && rat.ops.store == pass::AttachmentStoreOp::DontCare {
+                    invalidate.push(glow::DEPTH_ATTACHMENT);
+                }
+                if view_format.is_stencil()
+                    && rat.stencil_ops.store == pass::AttachmentStoreOp::DontCare
+                {
+                    invalidate.push(glow::STENCIL_ATTACHMENT);
                 }
             }
+            detach.push(state.attachments[index].view.depth_stencil_point());
+        }
+        if !invalidate.is_empty() {
+            self.data.push_cmd(Command::InvalidateAttachments(
+                glow::DRAW_FRAMEBUFFER,
+                invalidate,
+            ));
         }
+
+        // Leave the framebuffer clean for the next sub-pass.
+        self.data
+            .push_cmd(Command::DetachAttachments(glow::DRAW_FRAMEBUFFER, detach));
     }
 
     fn update_sampler_states(&mut self, dirty_textures: u32, dirty_samplers: u32) {
@@ -634,17 +783,11 @@ impl CommandBuffer {
                     || dirty_samplers & (1 << sampler_index) != 0
                 {
                     if let Some(ref sampler) = self.cache.samplers[sampler_index as usize] {
-                        let command = match *sampler {
-                            n::FatSampler::Sampler(object) => {
-                                Command::BindSampler(texture_index as u32, object)
-                            }
-                            n::FatSampler::Info(ref info) => Command::SetTextureSamplerSettings(
-                                texture_index as u32,
-                                slot.tex_target,
-                                info.clone(),
-                            ),
-                        };
-                        self.data.push_cmd(command);
+                        self.data.push_cmd(sampler_command(
+                            texture_index as u32,
+                            slot.tex_target,
+                            sampler,
+                        ));
                     }
                 }
             }
@@ -661,53 +804,122 @@ impl CommandBuffer {
         I: Iterator<Item = &'a n::DescriptorSet>,
         J: Iterator<Item = command::DescriptorSetOffset>,
     {
-        if let Some(_) = offsets.next() {
-            warn!("Dynamic offsets are not supported yet");
-        }
-
         let mut dirty_textures = 0u32;
         let mut dirty_samplers = 0u32;
         let mut set = first_set as usize;
         for desc_set in sets {
-            for (binding_layout, new_binding) in
-                desc_set.layout.iter().zip(desc_set.bindings.iter())
+            let set_layout = &desc_set.layout;
+            for (binding_layout, &first_index) in
+                set_layout.bindings.iter().zip(set_layout.offsets.iter())
             {
-                let binding = layout.sets[set].bindings[binding_layout.binding as usize] as u32;
-                match *new_binding {
-                    n::DescSetBindings::Buffer {
-                        register,
-                        buffer,
-                        offset,
-                        size,
-                    } => {
-                        let bind_point = match register {
-                            n::BindingRegister::UniformBuffers => glow::UNIFORM_BUFFER,
-                            n::BindingRegister::StorageBuffers => glow::SHADER_STORAGE_BUFFER,
-                            n::BindingRegister::Textures => panic!("Wrong desc set binding"),
-                        };
-                        self.data.push_cmd(Command::BindBufferRange(
-                            bind_point,
-                            binding,
+                let first_slot = layout.sets[set].bindings[binding_layout.binding as usize] as u32;
+                let is_dynamic = match binding_layout.ty {
+                    pso::DescriptorType::Buffer {
+                        format:
+                            pso::BufferDescriptorFormat::Structured {
+                                dynamic_offset: true,
+                            },
+                        ..
+                    } => true,
+                    _ => false,
+                };
+
+                for element in 0..binding_layout.count {
+                    let index = first_index + element;
+                    let binding = first_slot + element as u32;
+                    let immutable_sampler = set_layout.immutable_samplers[index].as_ref();
+
+                    // Dynamic offsets are consumed in the order of the bindings.
+                    let dynamic_offset = if is_dynamic {
+                        let dynamic_offset = offsets.next();
+                        if dynamic_offset.is_none() {
+                            error!(
+                                "Missing dynamic offset for binding {} of set {}",
+                                binding_layout.binding, set
+                            );
+                            self.cache.error_state = true;
+                        }
+                        dynamic_offset
+                    } else {
+                        None
+                    };
+
+                    match desc_set.bindings[index] {
+                        Some(n::DescSetBindings::Buffer {
+                            register,
                             buffer,
-                            offset as i32,
-                            size as i32,
-                        ));
-                    }
-                    n::DescSetBindings::Texture(texture, textype) => {
-                        dirty_textures |= 1 << binding;
-                        self.cache.texture_slots[binding as usize].tex_target = textype;
-                        self.data
-                            .push_cmd(Command::BindTexture(binding, texture, textype));
-                    }
-                    n::DescSetBindings::Sampler(sampler) => {
-                        dirty_samplers |= 1 << binding;
-                        self.cache.samplers[binding as usize] =
-                            Some(n::FatSampler::Sampler(sampler));
-                    }
-                    n::DescSetBindings::SamplerDesc(ref info) => {
-                        dirty_samplers |= 1 << binding;
-                        self.cache.samplers[binding as usize] =
-                            Some(n::FatSampler::Info(info.clone()));
+                            mut offset,
+                            size,
+                        }) => {
+                            let (bind_point, alignment) = match register {
+                                n::BindingRegister::UniformBuffers => (
+                                    glow::UNIFORM_BUFFER,
+                                    self.limits.min_uniform_buffer_offset_alignment,
+                                ),
+                                n::BindingRegister::StorageBuffers => (
+                                    glow::SHADER_STORAGE_BUFFER,
+                                    self.limits.min_storage_buffer_offset_alignment,
+                                ),
+                                n::BindingRegister::Textures | n::BindingRegister::Images => {
+                                    panic!("Wrong desc set binding")
+                                }
+                            };
+                            if let Some(dynamic_offset) = dynamic_offset {
+                                if dynamic_offset as buffer::Offset % alignment == 0 {
+                                    offset += dynamic_offset as i32;
+                                } else {
+                                    error!(
+                                        "Dynamic offset {} is not aligned to {}",
+                                        dynamic_offset, alignment
+                                    );
+                                    self.cache.error_state = true;
+                                }
+                            }
+                            self.data.push_cmd(Command::BindBufferRange(
+                                bind_point, binding, buffer, offset, size,
+                            ));
+                        }
+                        Some(n::DescSetBindings::Texture(texture, textype)) => {
+                            dirty_textures |= 1 << binding;
+                            self.cache.texture_slots[binding as usize].tex_target = textype;
+                            self.data
+                                .push_cmd(Command::BindTexture(binding, texture, textype));
+                            // combined image samplers may only provide the image
+                            if let Some(sampler) = immutable_sampler {
+                                self.data
+                                    .push_cmd(sampler_command(binding, textype, sampler));
+                            }
+                        }
+                        Some(n::DescSetBindings::CombinedImageSampler(
+                            texture,
+                            textype,
+                            ref sampler,
+                        )) => {
+                            dirty_textures |= 1 << binding;
+                            self.cache.texture_slots[binding as usize].tex_target = textype;
+                            self.data
+                                .push_cmd(Command::BindTexture(binding, texture, textype));
+                            let sampler = immutable_sampler.unwrap_or(sampler);
+                            self.data
+                                .push_cmd(sampler_command(binding, textype, sampler));
+                        }
+                        Some(n::DescSetBindings::Image(image)) => {
+                            self.data.push_cmd(Command::BindImage(binding, image));
+                        }
+                        Some(n::DescSetBindings::Sampler(ref sampler)) => {
+                            dirty_samplers |= 1 << binding;
+                            let sampler = immutable_sampler.unwrap_or(sampler);
+                            self.cache.samplers[binding as usize] = Some(sampler.clone());
+                        }
+                        None => {
+                            // immutable samplers don't need to be written
+                            if let (Some(sampler), pso::DescriptorType::Sampler) =
+                                (immutable_sampler, binding_layout.ty)
+                            {
+                                dirty_samplers |= 1 << binding;
+                                self.cache.samplers[binding as usize] = Some(sampler.clone());
+                            }
+                        }
                     }
                 }
             }
@@ -715,8 +927,609 @@ impl CommandBuffer {
             set += 1;
         }
 
+        if offsets.next().is_some() {
+            warn!("More dynamic offsets are provided than there are dynamic bindings");
+        }
+
         self.update_sampler_states(dirty_textures, dirty_samplers);
     }
+
+    /// Emulate a push descriptor set by binding a transient set holding the written descriptors.
+    /// Descriptors that are not written keep their previous bindings.
+    fn push_descriptor_set<'a, I>(
+        &mut self,
+        layout: &n::PipelineLayout,
+        set_index: usize,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        let set_layout = &layout.sets[set_index].layout;
+        let mut set = n::DescriptorSet {
+            layout: Arc::clone(set_layout),
+            bindings: vec![None; set_layout.immutable_samplers.len()],
+        };
+        device::write_descriptors(&mut set, binding, array_offset, descriptors);
+        self.bind_descriptor_sets(layout, set_index, iter::once(&set), iter::empty());
+    }
+
+    /// Apply the stencil test of the current pipeline, using the dynamic values where it has none.
+    fn update_stencil(&mut self) {
+        let reference = self
+            .cache
+            .stencil_ref
+            .map_or(pso::Sided::new(0), |(front, back)| pso::Sided {
+                front,
+                back,
+            });
+        let read_mask = self.cache.stencil_read_mask.unwrap_or(pso::Sided::new(!0));
+        let test = self.cache.stencil.map(|test| pso::StencilTest {
+            reference_values: pso::State::Static(test.reference_values.static_or(reference)),
+            read_masks: pso::State::Static(test.read_masks.static_or(read_mask)),
+            ..test
+        });
+        self.data.push_cmd(Command::BindStencil(test));
+    }
+
+    /// Apply the stencil write masks of the current pipeline, or the dynamic ones.
+    fn update_stencil_write_mask(&mut self) {
+        let mask = match self.cache.stencil {
+            Some(pso::StencilTest {
+                write_masks: pso::State::Static(mask),
+                ..
+            }) => mask,
+            Some(_) => match self.cache.stencil_write_mask {
+                Some(mask) => mask,
+                None => return,
+            },
+            None => return,
+        };
+        if self.cache.stencil_mask != Some(mask) {
+            self.cache.stencil_mask = Some(mask);
+            self.data.push_cmd(Command::SetStencilMaskSeparate(mask));
+        }
+    }
+
+    /// Attach a single image slice to a transfer framebuffer, returning its attachment point.
+    fn bind_transfer_framebuffer(
+        &mut self,
+        target: FrameBufferTarget,
+        framebuffer: n::RawFramebuffer,
+        view: n::ImageView,
+    ) -> u32 {
+        let (point, colors, depth_stencil) = if view.aspects().contains(Aspects::COLOR) {
+            (glow::COLOR_ATTACHMENT0, iter::once(view).collect(), None)
+        } else {
+            (view.depth_stencil_point(), ArrayVec::new(), Some(view))
+        };
+        self.data.push_cmd(Command::BindFramebuffer {
+            target,
+            framebuffer,
+            colors,
+            depth_stencil,
+        });
+        point
+    }
+
+    /// Blit a region between two images, one slice at a time, through the transfer framebuffers.
+    ///
+    /// This is the path of all image to image transfers, since renderbuffers
+    /// can only be accessed as framebuffer attachments.
+    fn blit_region(&mut self, src: &n::Image, dst: &n::Image, region: TransferRegion, filter: u32) {
+        let (read_fbo, draw_fbo) = match (self.read_fbo, self.fbo) {
+            (Some(read_fbo), Some(draw_fbo)) => (read_fbo, draw_fbo),
+            _ => {
+                error!("Image transfers require framebuffer objects");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+        if region.src_slices.len() != region.dst_slices.len() {
+            warn!(
+                "Transfer between {:?} source and {:?} destination slices",
+                region.src_slices, region.dst_slices
+            );
+        }
+
+        let aspects = region.src_layers.aspects;
+        let is_color = aspects.contains(Aspects::COLOR);
+        let mut points = None;
+        for (src_slice, dst_slice) in region.src_slices.clone().zip(region.dst_slices.clone()) {
+            let src_view = transfer_view(src, aspects, region.src_layers.level, src_slice);
+            let dst_view = transfer_view(
+                dst,
+                region.dst_layers.aspects,
+                region.dst_layers.level,
+                dst_slice,
+            );
+            let read_point =
+                self.bind_transfer_framebuffer(glow::READ_FRAMEBUFFER, read_fbo, src_view);
+            let draw_point =
+                self.bind_transfer_framebuffer(glow::DRAW_FRAMEBUFFER, draw_fbo, dst_view);
+            points = Some((read_point, draw_point));
+
+            if is_color {
+                self.data
+                    .push_cmd(Command::SetDrawColorBuffers(iter::once(0).collect()));
+            }
+            self.data.push_cmd(Command::BlitFramebuffer {
+                read_buffer: if is_color {
+                    glow::COLOR_ATTACHMENT0
+                } else {
+                    glow::NONE
+                },
+                src: region.src_rect,
+                dst: region.dst_rect,
+                mask: blit_mask(aspects),
+                // depth and stencil can only be blitted with nearest filtering
+                filter: if is_color { filter } else { glow::NEAREST },
+            });
+        }
+
+        if let Some((read_point, draw_point)) = points {
+            self.data.push_cmd(Command::DetachAttachments(
+                glow::READ_FRAMEBUFFER,
+                iter::once(read_point).collect(),
+            ));
+            self.data.push_cmd(Command::DetachAttachments(
+                glow::DRAW_FRAMEBUFFER,
+                iter::once(draw_point).collect(),
+            ));
+        }
+    }
+
+    /// Read a region of an image into a buffer, one slice at a time, through the read framebuffer.
+    ///
+    /// The pixels are packed into the buffer by the GPU, so the readback doesn't stall
+    /// the submission. Its completion is observed through the submission fence.
+    fn read_image_region(
+        &mut self,
+        src: &n::Image,
+        framebuffer: n::RawFramebuffer,
+        dst: n::RawBuffer,
+        r: &command::BufferImageCopy,
+    ) {
+        let (format, pixel_type) = match src.object_type {
+            n::ImageType::Renderbuffer {
+                format, pixel_type, ..
+            }
+            | n::ImageType::Texture {
+                format, pixel_type, ..
+            } => (format, pixel_type),
+        };
+        let row_length = if r.buffer_width == 0 {
+            r.image_extent.width
+        } else {
+            r.buffer_width
+        };
+        let image_height = if r.buffer_height == 0 {
+            r.image_extent.height
+        } else {
+            r.buffer_height
+        };
+        let slice_pitch = row_length as buffer::Offset
+            * image_height as buffer::Offset
+            * (src.format_desc.bits / 8) as buffer::Offset;
+        let bounds = r.image_offset..image::Offset {
+            x: r.image_offset.x + r.image_extent.width as i32,
+            y: r.image_offset.y + r.image_extent.height as i32,
+            z: r.image_offset.z + r.image_extent.depth as i32,
+        };
+        let aspects = r.image_layers.aspects;
+        let is_color = aspects.contains(Aspects::COLOR);
+
+        let mut point = None;
+        for (i, slice) in transfer_slices(src, &r.image_layers, &bounds).enumerate() {
+            let view = transfer_view(src, aspects, r.image_layers.level, slice);
+            point = Some(self.bind_transfer_framebuffer(glow::READ_FRAMEBUFFER, framebuffer, view));
+            self.data.push_cmd(Command::ReadPixels {
+                read_buffer: if is_color {
+                    glow::COLOR_ATTACHMENT0
+                } else {
+                    glow::NONE
+                },
+                rect: bounds_rect(&bounds),
+                format,
+                pixel_type,
+                dst_buffer: dst,
+                buffer_offset: r.buffer_offset + i as buffer::Offset * slice_pitch,
+                row_length,
+            });
+        }
+
+        if let Some(point) = point {
+            self.data.push_cmd(Command::DetachAttachments(
+                glow::READ_FRAMEBUFFER,
+                iter::once(point).collect(),
+            ));
+        }
+    }
+}
+
+/// Region of an image to image transfer, in framebuffer terms.
+struct TransferRegion {
+    src_layers: image::SubresourceLayers,
+    src_rect: pso::Rect,
+    /// Attached array layers, or depth slices of 3D images.
+    src_slices: Range<image::Layer>,
+    dst_layers: image::SubresourceLayers,
+    dst_rect: pso::Rect,
+    dst_slices: Range<image::Layer>,
+}
+
+impl TransferRegion {
+    fn new(
+        src: &n::Image,
+        src_layers: image::SubresourceLayers,
+        src_bounds: Range<image::Offset>,
+        dst: &n::Image,
+        dst_layers: image::SubresourceLayers,
+        dst_bounds: Range<image::Offset>,
+    ) -> Self {
+        TransferRegion {
+            src_rect: bounds_rect(&src_bounds),
+            src_slices: transfer_slices(src, &src_layers, &src_bounds),
+            src_layers,
+            dst_rect: bounds_rect(&dst_bounds),
+            dst_slices: transfer_slices(dst, &dst_layers, &dst_bounds),
+            dst_layers,
+        }
+    }
+
+    /// Region copied without scaling.
+    fn unscaled(
+        src: &n::Image,
+        src_layers: image::SubresourceLayers,
+        src_offset: image::Offset,
+        dst: &n::Image,
+        dst_layers: image::SubresourceLayers,
+        dst_offset: image::Offset,
+        extent: image::Extent,
+    ) -> Self {
+        let end = |offset: image::Offset| image::Offset {
+            x: offset.x + extent.width as i32,
+            y: offset.y + extent.height as i32,
+            z: offset.z + extent.depth as i32,
+        };
+        Self::new(
+            src,
+            src_layers,
+            src_offset..end(src_offset),
+            dst,
+            dst_layers,
+            dst_offset..end(dst_offset),
+        )
+    }
+}
+
+fn bounds_rect(bounds: &Range<image::Offset>) -> pso::Rect {
+    pso::Rect {
+        x: bounds.start.x as i16,
+        y: bounds.start.y as i16,
+        w: (bounds.end.x - bounds.start.x) as i16,
+        h: (bounds.end.y - bounds.start.y) as i16,
+    }
+}
+
+/// Slices of an image covered by a transfer: the array layers, or the depth range of 3D images.
+fn transfer_slices(
+    image: &n::Image,
+    layers: &image::SubresourceLayers,
+    bounds: &Range<image::Offset>,
+) -> Range<image::Layer> {
+    match image.kind {
+        image::Kind::D3(..) => bounds.start.z as image::Layer..bounds.end.z as image::Layer,
+        _ => layers.layers.clone(),
+    }
+}
+
+/// Target of a single layer of a texture attached to a framebuffer,
+/// and whether it needs to be attached by layer.
+///
+/// The faces of non-array cube maps can't be attached by layer on GLES
+/// and before GL 4.5, so they are attached as 2D textures instead.
+pub(crate) fn attachment_target(
+    target: n::TextureTarget,
+    layer: image::Layer,
+) -> (n::TextureTarget, bool) {
+    match target {
+        glow::TEXTURE_CUBE_MAP => (glow::TEXTURE_CUBE_MAP_POSITIVE_X + layer as u32, false),
+        glow::TEXTURE_1D_ARRAY
+        | glow::TEXTURE_2D_ARRAY
+        | glow::TEXTURE_2D_MULTISAMPLE_ARRAY
+        | glow::TEXTURE_3D
+        | glow::TEXTURE_CUBE_MAP_ARRAY => (target, true),
+        _ => (target, false),
+    }
+}
+
+/// View of a single slice of an image, to be attached to a framebuffer.
+fn transfer_view(
+    image: &n::Image,
+    aspects: Aspects,
+    level: image::Level,
+    slice: image::Layer,
+) -> n::ImageView {
+    match image.object_type {
+        n::ImageType::Renderbuffer { raw, .. } => n::ImageView::Renderbuffer {
+            raw,
+            aspects,
+            samples: image.kind.num_samples(),
+        },
+        n::ImageType::Texture {
+            target,
+            raw,
+            format,
+            ..
+        } => {
+            let (target, is_3d) = attachment_target(target, slice);
+            n::ImageView::Texture {
+                target,
+                raw,
+                format,
+                is_3d,
+                sub: image::SubresourceRange {
+                    aspects,
+                    level_start: level,
+                    level_count: Some(1),
+                    layer_start: slice,
+                    layer_count: Some(1),
+                },
+                samples: image.kind.num_samples(),
+            }
+        }
+    }
+}
+
+fn blit_mask(aspects: Aspects) -> u32 {
+    let mut mask = 0;
+    if aspects.contains(Aspects::COLOR) {
+        mask |= glow::COLOR_BUFFER_BIT;
+    }
+    if aspects.contains(Aspects::DEPTH) {
+        mask |= glow::DEPTH_BUFFER_BIT;
+    }
+    if aspects.contains(Aspects::STENCIL) {
+        mask |= glow::STENCIL_BUFFER_BIT;
+    }
+    mask
+}
+
+/// Set the stencil values of the given faces.
+fn set_faces(
+    values: &mut pso::Sided<pso::StencilValue>,
+    faces: pso::Face,
+    value: pso::StencilValue,
+) {
+    if faces.contains(pso::Face::FRONT) {
+        values.front = value;
+    }
+    if faces.contains(pso::Face::BACK) {
+        values.back = value;
+    }
+}
+
+/// Command binding the attachments of a sub-pass, or a single layer of them.
+fn bind_subpass_attachments(
+    state: &RenderPassCache,
+    subpass: &n::SubpassDesc,
+    layer: Option<image::Layer>,
+) -> Command {
+    let view = |index: usize| {
+        let view = &state.attachments[index].view;
+        match (view, layer) {
+            (
+                &n::ImageView::Texture {
+                    target,
+                    raw,
+                    format,
+                    ref sub,
+                    samples,
+                    ..
+                },
+                Some(layer),
+            ) => {
+                let layer_start = sub.layer_start + layer;
+                let (target, is_3d) = attachment_target(target, layer_start);
+                n::ImageView::Texture {
+                    target,
+                    raw,
+                    format,
+                    is_3d,
+                    sub: image::SubresourceRange {
+                        layer_start,
+                        layer_count: Some(1),
+                        ..sub.clone()
+                    },
+                    samples,
+                }
+            }
+            _ => view.clone(),
+        }
+    };
+    Command::BindFramebuffer {
+        target: glow::DRAW_FRAMEBUFFER,
+        framebuffer: state.framebuffer,
+        colors: subpass
+            .color_attachments
+            .iter()
+            .map(|&index| view(index))
+            .collect(),
+        depth_stencil: subpass.depth_stencil.map(view),
+    }
+}
+
+/// Command clearing a color draw buffer, interpreting the value by the channel type.
+fn clear_color_command(
+    draw_buffer: DrawBuffer,
+    channel: ChannelType,
+    value: command::ClearColor,
+) -> Command {
+    match channel {
+        ChannelType::Unorm
+        | ChannelType::Snorm
+        | ChannelType::Ufloat
+        | ChannelType::Sfloat
+        | ChannelType::Srgb
+        | ChannelType::Uscaled
+        | ChannelType::Sscaled => Command::ClearBufferColorF(draw_buffer, unsafe { value.float32 }),
+        ChannelType::Uint => Command::ClearBufferColorU(draw_buffer, unsafe { value.uint32 }),
+        ChannelType::Sint => Command::ClearBufferColorI(draw_buffer, unsafe { value.sint32 }),
+    }
+}
+
+/// Clear the bound depth-stencil attachment, lifting the write masks for the clear.
+fn push_clear_depth_stencil(
+    data: &mut CommandStorage,
+    cache: &Cache,
+    depth: Option<pso::DepthValue>,
+    stencil: Option<pso::StencilValue>,
+) {
+    // Only reset depth mask if it was non writable
+    let depth_mask = cache.depth_mask.filter(|mask| !mask);
+    let stencil_mask = cache
+        .stencil_mask
+        .filter(|mask| mask.front != !0 || mask.back != !0);
+
+    // Temporarily reset masks as they may prevent buffer clear in gl
+    if depth_mask.is_some() || cache.depth_mask.is_none() {
+        data.push_cmd(Command::SetDepthMask(true));
+    }
+    if stencil_mask.is_some() || cache.stencil_mask.is_none() {
+        data.push_cmd(Command::SetStencilMask(!0));
+    }
+
+    if depth.is_some() || stencil.is_some() {
+        data.push_cmd(Command::ClearBufferDepthStencil(depth, stencil));
+    }
+
+    // Restore masks if they were reset
+    if let Some(mask) = depth_mask {
+        data.push_cmd(Command::SetDepthMask(mask));
+    }
+    if let Some(mask) = stencil_mask {
+        data.push_cmd(Command::SetStencilMaskSeparate(mask));
+    }
+}
+
+/// Command applying the sampler state to a texture unit.
+fn sampler_command(
+    texture_index: u32,
+    tex_target: n::TextureTarget,
+    sampler: &n::FatSampler,
+) -> Command {
+    match *sampler {
+        n::FatSampler::Sampler(object) => Command::BindSampler(texture_index, object),
+        n::FatSampler::Info(ref info) => {
+            Command::SetTextureSamplerSettings(texture_index, tex_target, info.clone())
+        }
+    }
+}
+
+/// Barrier bits covering the given accesses to the data written by a buffer barrier.
+fn buffer_barrier_bits(access: buffer::Access) -> u32 {
+    use buffer::Access as A;
+
+    if access.contains(A::MEMORY_READ) || access.contains(A::MEMORY_WRITE) {
+        return glow::ALL_BARRIER_BITS;
+    }
+    let mut bits = 0;
+    if access.contains(A::INDIRECT_COMMAND_READ) {
+        bits |= glow::COMMAND_BARRIER_BIT;
+    }
+    if access.contains(A::INDEX_BUFFER_READ) {
+        bits |= glow::ELEMENT_ARRAY_BARRIER_BIT;
+    }
+    if access.contains(A::VERTEX_BUFFER_READ) {
+        bits |= glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT;
+    }
+    if access.contains(A::UNIFORM_READ) {
+        bits |= glow::UNIFORM_BARRIER_BIT;
+    }
+    if access.intersects(A::SHADER_READ | A::SHADER_WRITE) {
+        // storage buffers, uniform and storage texel buffers
+        bits |= glow::SHADER_STORAGE_BARRIER_BIT
+            | glow::TEXTURE_FETCH_BARRIER_BIT
+            | glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
+    }
+    if access.intersects(A::TRANSFER_READ | A::TRANSFER_WRITE) {
+        bits |= glow::BUFFER_UPDATE_BARRIER_BIT | glow::PIXEL_BUFFER_BARRIER_BIT;
+    }
+    if access.intersects(A::HOST_READ | A::HOST_WRITE) {
+        // `CLIENT_MAPPED_BUFFER_BARRIER_BIT` is not accepted by GLES 3.1
+        bits |= glow::BUFFER_UPDATE_BARRIER_BIT;
+    }
+    bits
+}
+
+/// Barrier bits covering the given accesses to the data written by an image barrier.
+fn image_barrier_bits(access: image::Access) -> u32 {
+    use image::Access as A;
+
+    if access.contains(A::MEMORY_READ) || access.contains(A::MEMORY_WRITE) {
+        return glow::ALL_BARRIER_BITS;
+    }
+    let mut bits = 0;
+    if access.intersects(A::SHADER_READ | A::INPUT_ATTACHMENT_READ) {
+        bits |= glow::TEXTURE_FETCH_BARRIER_BIT | glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
+    }
+    if access.contains(A::SHADER_WRITE) {
+        bits |= glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
+    }
+    if access.intersects(
+        A::COLOR_ATTACHMENT_READ
+            | A::COLOR_ATTACHMENT_WRITE
+            | A::DEPTH_STENCIL_ATTACHMENT_READ
+            | A::DEPTH_STENCIL_ATTACHMENT_WRITE,
+    ) {
+        bits |= glow::FRAMEBUFFER_BARRIER_BIT;
+    }
+    if access.intersects(A::TRANSFER_READ | A::TRANSFER_WRITE) {
+        bits |= glow::TEXTURE_UPDATE_BARRIER_BIT | glow::PIXEL_BUFFER_BARRIER_BIT;
+    }
+    if access.intersects(A::HOST_READ | A::HOST_WRITE) {
+        bits |= glow::TEXTURE_UPDATE_BARRIER_BIT;
+    }
+    bits
+}
+
+/// Collect the `glMemoryBarrier` bits required by the given barriers.
+///
+/// GL only needs explicit barriers for the incoherent writes done by shaders
+/// through storage buffers and images. The bits are picked from the accesses
+/// that consume these writes.
+fn barrier_mask<'a, T>(barriers: T) -> u32
+where
+    T: Iterator<Item = memory::Barrier<'a, Backend>>,
+{
+    let buffer_writes = buffer::Access::SHADER_WRITE | buffer::Access::MEMORY_WRITE;
+    let image_writes = image::Access::SHADER_WRITE | image::Access::MEMORY_WRITE;
+    let mut mask = 0;
+
+    for barrier in barriers {
+        match barrier {
+            memory::Barrier::AllBuffers(access)
+            | memory::Barrier::Buffer { states: access, .. } => {
+                if access.start.intersects(buffer_writes) {
+                    mask |= buffer_barrier_bits(access.end);
+                }
+            }
+            memory::Barrier::AllImages(access) => {
+                if access.start.intersects(image_writes) {
+                    mask |= image_barrier_bits(access.end);
+                }
+            }
+            memory::Barrier::Image { states, .. } => {
+                if states.start.0.intersects(image_writes) {
+                    mask |= image_barrier_bits(states.end.0);
+                }
+            }
+        }
+    }
+
+    mask
 }
 
 impl command::CommandBuffer<Backend> for CommandBuffer {
@@ -756,37 +1569,9 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = memory::Barrier<'a, Backend>>,
     {
-        //TODO: this needs to be much more detailed. Problem is that the affected
-        // resources by a barrier have to be bound to specific slots, so, for example,
-        // doing a `set_graphics_pipeline` followed by `pipeline_barrier` may need
-        // the vertex bindings to be reinstated.
-        let mut mask = 0;
-
-        for barrier in barriers {
-            match barrier {
-                memory::Barrier::AllBuffers(access) => {
-                    if access.start.contains(buffer::Access::SHADER_WRITE) {
-                        mask |= glow::SHADER_STORAGE_BARRIER_BIT;
-                    }
-                }
-                memory::Barrier::Buffer { states, .. } => {
-                    if states.start.contains(buffer::Access::SHADER_WRITE) {
-                        mask |= glow::SHADER_STORAGE_BARRIER_BIT;
-                    }
-                }
-                memory::Barrier::AllImages(access) => {
-                    if access.start.contains(image::Access::SHADER_WRITE) {
-                        mask |= glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
-                    }
-                }
-                memory::Barrier::Image { states, .. } => {
-                    if states.start.0.contains(image::Access::SHADER_WRITE) {
-                        mask |= glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
-                    }
-                }
-            }
-        }
-
+        // Execution dependencies are implicit in GL, only the visibility
+        // of shader writes has to be ensured.
+        let mask = barrier_mask(barriers);
         if mask != 0 {
             self.data.push_cmd(Command::MemoryBarrier(mask));
         }
@@ -807,59 +1592,27 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         &mut self,
         render_pass: &n::RenderPass,
         framebuffer: &n::Framebuffer,
-        _render_area: pso::Rect,
+        render_area: pso::Rect,
         attachment_infos: T,
         _first_subpass: command::SubpassContents,
     ) where
         T: Iterator<Item = command::RenderAttachmentInfo<'a, Backend>>,
     {
-        // TODO: load ops: clearing strategy
-        //  1.  < GL 3.0 / GL ES 2.0: glClear, only single color attachment?
-        //  2.  = GL ES 2.0: glBindFramebuffer + glClear (no single draw buffer supported)
-        //  3. >= GL 3.0 / GL ES 3.0: glBindFramerbuffer + glClearBuffer
-        //
-        // Clearing when entering a subpass:
-        //    * Acquire channel information from renderpass description to
-        //      select correct ClearBuffer variant.
-        //    * Check for attachment loading clearing strategy
-
-        // TODO: store ops:
-        //   < GL 4.5: Ignore
-        //  >= GL 4.5: Invalidate framebuffer attachment when store op is `DONT_CARE`.
-
-        // 2./3.
-        let mut attachments = Vec::new();
-        let mut colors = ArrayVec::new();
-        let mut depth_stencil = None;
-
-        for info in attachment_infos {
-            let view = info.image_view.clone();
-            let aspects = view.aspects();
-            let color_index = if aspects.contains(Aspects::COLOR) {
-                colors.push(view);
-                Some(colors.len() as u8 - 1)
-            } else {
-                depth_stencil = Some(view);
-                None
-            };
-            attachments.push(AttachmentInfo {
-                color_index,
+        // Clearing strategy: >= GL 3.0 / GL ES 3.0: glBindFramebuffer + glClearBuffer.
+        // Attachments are cleared when entering the first sub-pass that uses them,
+        // and invalidated after the last one when their contents are not needed.
+        let attachments = attachment_infos
+            .map(|info| AttachmentInfo {
+                view: info.image_view.clone(),
                 clear_value: info.clear_value,
             })
-        }
+            .collect();
 
         self.pass_cache = Some(RenderPassCache {
             render_pass: render_pass.clone(),
-            attachments,
-        });
-
-        //Note: we currently only expect one sub-pass.
-
-        self.data.push_cmd(Command::BindFramebuffer {
-            target: glow::DRAW_FRAMEBUFFER,
             framebuffer: framebuffer.raw,
-            colors,
-            depth_stencil,
+            attachments,
+            render_area,
         });
 
         // Enter first subpass
@@ -868,11 +1621,108 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn next_subpass(&mut self, _contents: command::SubpassContents) {
-        unimplemented!()
+        self.end_subpass();
+        self.cur_subpass += 1;
+        self.begin_subpass();
     }
 
     unsafe fn end_render_pass(&mut self) {
-        // TODO
+        self.end_subpass();
+        self.pass_cache = None;
+        self.cur_subpass = !0;
+    }
+
+    unsafe fn begin_rendering(&mut self, info: &command::RenderingInfo<Backend>) {
+        let framebuffer = match self.render_fbo {
+            Some(fbo) => fbo,
+            None => {
+                error!("Can't render without framebuffer objects");
+                return;
+            }
+        };
+
+        // Array views are attached one layer at a time, see `Device::bind_target`.
+        if info.layers != 1 {
+            error!(
+                "Dynamic rendering to {} layers is not supported",
+                info.layers
+            );
+            return;
+        }
+
+        // Dynamic rendering goes through the render pass path, with a render pass
+        // of a single sub-pass using all the attachments.
+        let color_count = info.colors.len();
+        let mut attachments = Vec::new();
+        let mut attachment_infos = Vec::new();
+        for at in info.colors.iter().chain(info.depth_stencil) {
+            attachments.push(pass::Attachment {
+                format: Some(at.format),
+                samples: at.image_view.samples(),
+                ops: at.ops,
+                stencil_ops: at.stencil_ops,
+                layouts: at.layout..at.layout,
+            });
+            attachment_infos.push(AttachmentInfo {
+                view: at.image_view.clone(),
+                clear_value: at.clear_value,
+            });
+        }
+
+        let mut resolve_attachments = Vec::new();
+        if info.colors.iter().any(|at| at.resolve.is_some()) {
+            for at in info.colors {
+                let index = match at.resolve {
+                    Some((view, layout)) => {
+                        // The format of the resolve view isn't known, but resolve
+                        // attachments are never cleared nor invalidated.
+                        attachments.push(pass::Attachment {
+                            format: None,
+                            samples: view.samples(),
+                            ops: pass::AttachmentOps::new(
+                                pass::AttachmentLoadOp::DontCare,
+                                pass::AttachmentStoreOp::Store,
+                            ),
+                            stencil_ops: pass::AttachmentOps::DONT_CARE,
+                            layouts: layout..layout,
+                        });
+                        attachment_infos.push(AttachmentInfo {
+                            view: view.clone(),
+                            clear_value: at.clear_value,
+                        });
+                        attachments.len() - 1
+                    }
+                    None => pass::ATTACHMENT_UNUSED,
+                };
+                resolve_attachments.push(index);
+            }
+        }
+
+        let render_pass = n::RenderPass {
+            attachments,
+            subpasses: vec![n::SubpassDesc {
+                color_attachments: (0..color_count).collect(),
+                depth_stencil: info.depth_stencil.map(|_| color_count),
+                input_attachments: Vec::new(),
+                resolve_attachments,
+            }],
+        };
+
+        self.pass_cache = Some(RenderPassCache {
+            render_pass,
+            framebuffer,
+            attachments: attachment_infos,
+            render_area: info.render_area,
+        });
+
+        self.cur_subpass = 0;
+        self.begin_subpass();
+    }
+
+    unsafe fn end_rendering(&mut self) {
+        if self.pass_cache.is_some() {
+            self.end_render_pass();
+        }
     }
 
     unsafe fn clear_image<T>(
@@ -898,19 +1748,22 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                     n::ImageType::Renderbuffer { raw, .. } => n::ImageView::Renderbuffer {
                         raw,
                         aspects: image.format_desc.aspects,
+                        samples: image.kind.num_samples(),
                     },
                     n::ImageType::Texture {
                         target,
                         raw,
                         level_count,
                         layer_count,
+                        format,
                         ..
                     } => {
-                        let is_3d = layer_count == 1; //TODO?
+                        // Attach all the layers, a clear of a layered framebuffer clears them all.
                         n::ImageView::Texture {
                             target,
                             raw,
-                            is_3d,
+                            format,
+                            is_3d: false,
                             sub: image::SubresourceRange {
                                 aspects: Aspects::COLOR,
                                 layer_start: 0,
@@ -918,6 +1771,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                                 level_start: 0,
                                 level_count: Some(level_count),
                             },
+                            samples: image.kind.num_samples(),
                         }
                     }
                 };
@@ -932,17 +1786,8 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                 self.data
                     .push_cmd(Command::SetColorMask(None, pso::ColorMask::ALL));
 
-                self.data.push_cmd(match image.channel {
-                    ChannelType::Unorm
-                    | ChannelType::Snorm
-                    | ChannelType::Ufloat
-                    | ChannelType::Sfloat
-                    | ChannelType::Srgb
-                    | ChannelType::Uscaled
-                    | ChannelType::Sscaled => Command::ClearBufferColorF(0, color.float32),
-                    ChannelType::Uint => Command::ClearBufferColorU(0, color.uint32),
-                    ChannelType::Sint => Command::ClearBufferColorI(0, color.sint32),
-                });
+                self.data
+                    .push_cmd(clear_color_command(0, image.channel, color));
 
                 //Note: color mask is not restored: we are outside of a render pass,
                 // and whatever needs to have the mask, including the pass, should set it.
@@ -960,39 +1805,141 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         }
     }
 
-    unsafe fn clear_attachments<T, U>(&mut self, _: T, _: U)
+    unsafe fn clear_attachments<T, U>(&mut self, clears: T, rects: U)
     where
         T: Iterator<Item = command::AttachmentClear>,
         U: Iterator<Item = pso::ClearRect>,
     {
-        unimplemented!()
+        let state = match self.pass_cache {
+            Some(ref state) => state,
+            None => {
+                error!("Attachments can only be cleared inside of a render pass");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+        let subpass = &state.render_pass.subpasses[self.cur_subpass as usize];
+        let clears = clears.collect::<Vec<_>>();
+
+        // The clears are restricted to the rectangles by the scissor test,
+        // and aren't affected by the color masks of the pipeline.
+        self.data.push_cmd(Command::SetScissorTest(true));
+        self.data
+            .push_cmd(Command::SetColorMask(None, pso::ColorMask::ALL));
+
+        for rect in rects {
+            let scissor = self.data.add::<i32>(&[
+                rect.rect.x as i32,
+                rect.rect.y as i32,
+                rect.rect.w as i32,
+                rect.rect.h as i32,
+            ]);
+            self.data.push_cmd(Command::SetScissors(0, scissor));
+
+            // The attachments are bound with all their layers, so the other layers
+            // are cleared by binding them one at a time.
+            let is_layered = rect.layers != (0..1);
+            for layer in rect.layers.clone() {
+                if is_layered {
+                    self.data
+                        .push_cmd(bind_subpass_attachments(state, subpass, Some(layer)));
+                }
+                for clear in clears.iter() {
+                    match *clear {
+                        command::AttachmentClear::Color { index, value } => {
+                            let rat =
+                                &state.render_pass.attachments[subpass.color_attachments[index]];
+                            self.data.push_cmd(clear_color_command(
+                                index as DrawBuffer,
+                                rat.format.unwrap().base_format().1,
+                                value,
+                            ));
+                        }
+                        command::AttachmentClear::DepthStencil { depth, stencil } => {
+                            push_clear_depth_stencil(&mut self.data, &self.cache, depth, stencil);
+                        }
+                    }
+                }
+            }
+            if is_layered {
+                self.data
+                    .push_cmd(bind_subpass_attachments(state, subpass, None));
+            }
+        }
+
+        // Restore the scissors and the color masks.
+        self.data.push_cmd(Command::SetScissorTest(false));
+        if let Some((first_scissor, scissors)) = self.cache.scissors {
+            self.data
+                .push_cmd(Command::SetScissors(first_scissor, scissors));
+        }
+        let blend_targets = &self.cache.blend_targets;
+        match blend_targets.first() {
+            Some(&Some(first)) if blend_targets.iter().all(|target| *target == Some(first)) => {
+                self.data.push_cmd(Command::SetColorMask(None, first.mask));
+            }
+            _ => {
+                for (slot, target) in blend_targets.iter().enumerate() {
+                    if let Some(ref target) = *target {
+                        self.data
+                            .push_cmd(Command::SetColorMask(Some(slot as _), target.mask));
+                    }
+                }
+            }
+        }
     }
 
     unsafe fn resolve_image<T>(
         &mut self,
-        _src: &n::Image,
+        src: &n::Image,
         _src_layout: image::Layout,
-        _dst: &n::Image,
+        dst: &n::Image,
         _dst_layout: image::Layout,
-        _regions: T,
+        regions: T,
     ) where
         T: Iterator<Item = command::ImageResolve>,
     {
-        unimplemented!()
+        // Blitting from a multisampled framebuffer resolves it.
+        for r in regions {
+            let region = TransferRegion::unscaled(
+                src,
+                r.src_subresource,
+                r.src_offset,
+                dst,
+                r.dst_subresource,
+                r.dst_offset,
+                r.extent,
+            );
+            self.blit_region(src, dst, region, glow::NEAREST);
+        }
     }
 
     unsafe fn blit_image<T>(
         &mut self,
-        _src: &n::Image,
+        src: &n::Image,
         _src_layout: image::Layout,
-        _dst: &n::Image,
+        dst: &n::Image,
         _dst_layout: image::Layout,
-        _filter: image::Filter,
-        _regions: T,
+        filter: image::Filter,
+        regions: T,
     ) where
         T: Iterator<Item = command::ImageBlit>,
     {
-        error!("Blit is not implemented");
+        let filter = match filter {
+            image::Filter::Nearest => glow::NEAREST,
+            image::Filter::Linear => glow::LINEAR,
+        };
+        for r in regions {
+            let region = TransferRegion::new(
+                src,
+                r.src_subresource,
+                r.src_bounds,
+                dst,
+                r.dst_subresource,
+                r.dst_bounds,
+            );
+            self.blit_region(src, dst, region, filter);
+        }
     }
 
     unsafe fn bind_index_buffer(
@@ -1091,6 +2038,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                 self.cache.error_state = true;
             }
             n if n + first_scissor as usize <= self.limits.max_viewports => {
+                self.cache.scissors = Some((first_scissor, scissors_ptr));
                 self.data
                     .push_cmd(Command::SetScissors(first_scissor, scissors_ptr));
             }
@@ -1120,19 +2068,36 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
             back = value;
         }
 
-        // Only cache the stencil references values until
-        // we assembled all the pieces to set the stencil state
-        // from the pipeline.
+        // The references values are set together with the rest
+        // of the stencil state of the pipeline.
         self.cache.stencil_ref = Some((front, back));
+        if let Some(pso::StencilTest {
+            reference_values: pso::State::Dynamic,
+            ..
+        }) = self.cache.stencil
+        {
+            self.update_stencil();
+        }
     }
 
-    unsafe fn set_stencil_read_mask(&mut self, _faces: pso::Face, _value: pso::StencilValue) {
-        unimplemented!();
+    unsafe fn set_stencil_read_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
+        let mut masks = self.cache.stencil_read_mask.unwrap_or(pso::Sided::new(!0));
+        set_faces(&mut masks, faces, value);
+        self.cache.stencil_read_mask = Some(masks);
+        if let Some(pso::StencilTest {
+            read_masks: pso::State::Dynamic,
+            ..
+        }) = self.cache.stencil
+        {
+            self.update_stencil();
+        }
     }
 
-    unsafe fn set_stencil_write_mask(&mut self, _faces: pso::Face, _value: pso::StencilValue) {
-        // set self.cache.stencil_mask once implemented
-        unimplemented!();
+    unsafe fn set_stencil_write_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
+        let mut masks = self.cache.stencil_write_mask.unwrap_or(pso::Sided::new(!0));
+        set_faces(&mut masks, faces, value);
+        self.cache.stencil_write_mask = Some(masks);
+        self.update_stencil_write_mask();
     }
 
     unsafe fn set_blend_constants(&mut self, cv: pso::ColorValue) {
@@ -1142,12 +2107,12 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         }
     }
 
-    unsafe fn set_depth_bounds(&mut self, _: Range<f32>) {
-        warn!("Depth bounds test is not supported");
+    unsafe fn set_depth_bounds(&mut self, bounds: Range<f32>) {
+        self.data.push_cmd(Command::SetDepthBounds(bounds));
     }
 
-    unsafe fn set_line_width(&mut self, _width: f32) {
-        unimplemented!()
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.data.push_cmd(Command::SetLineWidth(width));
     }
 
     unsafe fn set_depth_bias(&mut self, _depth_bias: pso::DepthBias) {
@@ -1188,6 +2153,16 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         ));
         self.cache.depth_mask = pipeline.depth.map(|d| d.write);
 
+        self.cache.stencil = pipeline.stencil;
+        self.update_stencil();
+        self.update_stencil_write_mask();
+
+        if self.cache.depth_bounds_test != Some(pipeline.depth_bounds) {
+            self.cache.depth_bounds_test = Some(pipeline.depth_bounds);
+            self.data
+                .push_cmd(Command::SetDepthBoundsTest(pipeline.depth_bounds));
+        }
+
         if let Some(ref vp) = pipeline.baked_states.viewport {
             self.set_viewports(0, iter::once(vp.clone()));
         }
@@ -1232,6 +2207,19 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         self.bind_descriptor_sets(layout, first_set, sets, offsets)
     }
 
+    unsafe fn push_graphics_descriptor_set<'a, I>(
+        &mut self,
+        layout: &n::PipelineLayout,
+        set_index: usize,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        self.push_descriptor_set(layout, set_index, binding, array_offset, descriptors)
+    }
+
     unsafe fn bind_compute_pipeline(&mut self, pipeline: &n::ComputePipeline) {
         if self.cache.program != Some(pipeline.program) {
             self.cache.program = Some(pipeline.program);
@@ -1252,6 +2240,19 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         self.bind_descriptor_sets(layout, first_set, sets, offsets)
     }
 
+    unsafe fn push_compute_descriptor_set<'a, I>(
+        &mut self,
+        layout: &n::PipelineLayout,
+        set_index: usize,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        self.push_descriptor_set(layout, set_index, binding, array_offset, descriptors)
+    }
+
     unsafe fn dispatch(&mut self, count: hal::WorkGroupCount) {
         self.data.push_cmd(Command::Dispatch(count));
     }
@@ -1294,19 +2295,18 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
     {
         let old_size = self.data.buf.size;
 
+        //TODO: use `glCopyImageSubData` when available, blits convert between formats
         for r in regions {
-            let cmd = match dst.object_type {
-                n::ImageType::Renderbuffer { raw, format } => Command::CopyImageToRenderbuffer {
-                    src_image: src.object_type,
-                    dst_renderbuffer: raw,
-                    dst_format: format,
-                    data: r,
-                },
-                n::ImageType::Texture { raw, target, .. } => {
-                    Command::CopyImageToTexture(src.object_type, raw, target, r)
-                }
-            };
-            self.data.push_cmd(cmd);
+            let region = TransferRegion::unscaled(
+                src,
+                r.src_subresource,
+                r.src_offset,
+                dst,
+                r.dst_subresource,
+                r.dst_offset,
+                r.extent,
+            );
+            self.blit_region(src, dst, region, glow::NEAREST);
         }
 
         if self.data.buf.size == old_size {
@@ -1369,9 +2369,15 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
         for mut r in regions {
             r.buffer_offset += dst_range.start;
+            if let Some(fbo) = self.read_fbo {
+                self.read_image_region(src, fbo, dst_raw, &r);
+                continue;
+            }
             let cmd = match src.object_type {
-                n::ImageType::Renderbuffer { raw, .. } => {
-                    Command::CopyRenderbufferToBuffer(raw, dst_raw, r)
+                n::ImageType::Renderbuffer { .. } => {
+                    error!("Renderbuffers can only be read through framebuffer objects");
+                    self.cache.error_state = true;
+                    continue;
                 }
                 n::ImageType::Texture {
                     raw,
@@ -1551,20 +2557,34 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
     ) {
         unimplemented!()
     }
-    unsafe fn set_event(&mut self, _: &(), _: pso::PipelineStage) {
-        unimplemented!()
+    unsafe fn set_event(&mut self, event: &n::Event, _: pso::PipelineStage) {
+        // GL executes the commands in order, so the event gets signaled
+        // once all the previously submitted work is complete.
+        self.data.push_cmd(Command::SetEvent(Arc::clone(&event.0)));
     }
 
-    unsafe fn reset_event(&mut self, _: &(), _: pso::PipelineStage) {
-        unimplemented!()
+    unsafe fn reset_event(&mut self, event: &n::Event, _: pso::PipelineStage) {
+        self.data
+            .push_cmd(Command::ResetEvent(Arc::clone(&event.0)));
     }
 
-    unsafe fn wait_events<'a, I, J>(&mut self, _: I, _: Range<pso::PipelineStage>, _: J)
-    where
-        I: Iterator<Item = &'a ()>,
+    unsafe fn wait_events<'a, I, J>(
+        &mut self,
+        events: I,
+        _stages: Range<pso::PipelineStage>,
+        barriers: J,
+    ) where
+        I: Iterator<Item = &'a n::Event>,
         J: Iterator<Item = memory::Barrier<'a, Backend>>,
     {
-        unimplemented!()
+        for event in events {
+            self.data.push_cmd(Command::WaitEvent(Arc::clone(&event.0)));
+        }
+
+        let mask = barrier_mask(barriers);
+        if mask != 0 {
+            self.data.push_cmd(Command::MemoryBarrier(mask));
+        }
     }
 
     unsafe fn begin_query(&mut self, _query: query::Query<Backend>, _flags: query::ControlFlags) {
@@ -1640,13 +2660,15 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!()
     }
 
-    unsafe fn insert_debug_marker(&mut self, _name: &str, _color: u32) {
-        //TODO
+    unsafe fn insert_debug_marker(&mut self, name: &str, _color: u32) {
+        let name = self.data.add_raw(name.as_bytes());
+        self.data.push_cmd(Command::InsertDebugMarker(name));
     }
-    unsafe fn begin_debug_marker(&mut self, _name: &str, _color: u32) {
-        //TODO
+    unsafe fn begin_debug_marker(&mut self, name: &str, _color: u32) {
+        let name = self.data.add_raw(name.as_bytes());
+        self.data.push_cmd(Command::PushDebugGroup(name));
     }
     unsafe fn end_debug_marker(&mut self) {
-        //TODO
+        self.data.push_cmd(Command::PopDebugGroup);
     }
 }
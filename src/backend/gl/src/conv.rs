@@ -257,6 +257,53 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
     })
 }
 
+/// Check if the format can back a buffer texture.
+pub fn is_texel_buffer_format(desc: &FormatDescription) -> bool {
+    match desc.tex_external {
+        glow::DEPTH_COMPONENT | glow::DEPTH_STENCIL | glow::BGRA => false,
+        // only the 32-bit channels are allowed for 3-component formats
+        _ if desc.num_components == 3 => match desc.tex_internal {
+            glow::RGB32F | glow::RGB32UI | glow::RGB32I => true,
+            _ => false,
+        },
+        _ => desc.tex_internal != glow::SRGB8_ALPHA8,
+    }
+}
+
+/// Check if the format can be used with image load/store.
+pub fn is_image_format(format: Format) -> bool {
+    use hal::format::Format::*;
+    match format {
+        Rgba32Sfloat | Rgba16Sfloat | R32Sfloat | Rgba8Unorm | Rgba32Uint | Rgba16Uint
+        | Rgba8Uint | R32Uint | Rgba32Sint | Rgba16Sint | Rgba8Sint | R32Sint => true,
+        _ => false,
+    }
+}
+
+/// Map the GL shader stage bits, as used by the program pipelines, to hal stages.
+pub fn map_gl_stage_bits(bits: u32) -> pso::ShaderStageFlags {
+    let mut stages = pso::ShaderStageFlags::empty();
+    if bits & glow::VERTEX_SHADER_BIT != 0 {
+        stages |= pso::ShaderStageFlags::VERTEX;
+    }
+    if bits & glow::TESS_CONTROL_SHADER_BIT != 0 {
+        stages |= pso::ShaderStageFlags::HULL;
+    }
+    if bits & glow::TESS_EVALUATION_SHADER_BIT != 0 {
+        stages |= pso::ShaderStageFlags::DOMAIN;
+    }
+    if bits & glow::GEOMETRY_SHADER_BIT != 0 {
+        stages |= pso::ShaderStageFlags::GEOMETRY;
+    }
+    if bits & glow::FRAGMENT_SHADER_BIT != 0 {
+        stages |= pso::ShaderStageFlags::FRAGMENT;
+    }
+    if bits & glow::COMPUTE_SHADER_BIT != 0 {
+        stages |= pso::ShaderStageFlags::COMPUTE;
+    }
+    stages
+}
+
 #[cfg(feature = "cross")]
 pub fn map_naga_stage_to_cross(stage: naga::ShaderStage) -> spirv_cross::spirv::ExecutionModel {
     use spirv_cross::spirv::ExecutionModel as Em;
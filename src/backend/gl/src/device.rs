@@ -1,5 +1,5 @@
 use crate::{
-    command as cmd, conv,
+    command as cmd, conv, ext, fence,
     info::LegacyFeatures,
     native as n,
     pool::{BufferMemory, CommandPool, OwnedBuffer},
@@ -18,7 +18,11 @@ use hal::{
 use glow::HasContext;
 use parking_lot::Mutex;
 
-use std::{ops::Range, slice, sync::Arc};
+use std::{
+    ops::Range,
+    slice,
+    sync::{atomic::Ordering, Arc},
+};
 
 #[cfg(feature = "cross")]
 type CrossAst = spirv_cross::spirv::Ast<spirv_cross::glsl::Target>;
@@ -116,7 +120,14 @@ impl Device {
             naga::ShaderStage::Fragment => glow::FRAGMENT_SHADER,
             naga::ShaderStage::Compute => glow::COMPUTE_SHADER,
         };
+        Self::create_shader_raw(gl, shader, target)
+    }
 
+    fn create_shader_raw(
+        gl: &GlContainer,
+        shader: &str,
+        target: u32,
+    ) -> Result<n::Shader, d::ShaderError> {
         let name = unsafe { gl.create_shader(target) }.unwrap();
         unsafe {
             gl.shader_source(name, shader);
@@ -140,16 +151,252 @@ impl Device {
         }
     }
 
+    /// Compute the pipeline cache key of a program.
+    ///
+    /// The key holds everything the program depends on, rather than a digest of it,
+    /// so that the lookups can't return the program of a colliding key.
+    ///
+    /// Returns `None` if any of the shader modules can't be identified.
+    fn program_cache_key(
+        &self,
+        shaders: &[(naga::ShaderStage, Option<&pso::EntryPoint<B>>)],
+        layout: &n::PipelineLayout,
+    ) -> Option<Vec<u8>> {
+        let mut key = n::CacheWriter(Vec::new());
+        for &(stage, point_maybe) in shaders {
+            if let Some(point) = point_maybe {
+                key.u32(stage as u32);
+                key.words(point.module.spv.as_ref()?);
+                key.0.push(point.module.prefer_naga as u8);
+                key.bytes(point.entry.as_bytes());
+                key.u32(point.specialization.constants.len() as u32);
+                for constant in point.specialization.constants.iter() {
+                    key.u32(constant.id);
+                    key.u32(constant.range.start as u32);
+                    key.u32(constant.range.end as u32);
+                }
+                key.bytes(&point.specialization.data);
+            }
+        }
+        key.u32(layout.sets.len() as u32);
+        for set in layout.sets.iter() {
+            key.bytes(&set.bindings);
+            key.u32(set.layout.bindings.len() as u32);
+            for binding in set.layout.bindings.iter() {
+                key.u32(binding.binding);
+                key.descriptor_type(binding.ty);
+                key.u32(binding.count as u32);
+            }
+        }
+        key.0.extend_from_slice(&self.features.bits().to_le_bytes());
+        Some(key.0)
+    }
+
+    fn link_program(&self, program: glow::Program) -> Result<(), pso::CreationError> {
+        let gl = &self.share.context;
+        unsafe {
+            gl.link_program(program);
+        }
+        info!("\tLinked program {:?}", program);
+        if let Err(err) = self.share.check() {
+            panic!("Error linking program: {:?}", err);
+        }
+
+        let linked_ok = unsafe { gl.get_program_link_status(program) };
+        let log = unsafe { gl.get_program_info_log(program) };
+        if !linked_ok {
+            error!("\tLog: {}", log);
+            return Err(pso::CreationError::Other);
+        }
+        if !log.is_empty() {
+            warn!("\tLog: {}", log);
+        }
+        Ok(())
+    }
+
+    /// Assign the binding slots to the named resources of a linked program,
+    /// unless the shaders have explicit layouts.
+    fn assign_bindings(
+        &self,
+        program: glow::Program,
+        name_bindings: &[(String, n::BindingRegister, u8)],
+    ) {
+        if self
+            .share
+            .legacy_features
+            .contains(LegacyFeatures::EXPLICIT_LAYOUTS_IN_SHADER)
+        {
+            return;
+        }
+
+        let gl = &self.share.context;
+        unsafe {
+            gl.use_program(Some(program));
+        }
+        for &(ref name, register, slot) in name_bindings.iter() {
+            match register {
+                n::BindingRegister::Textures | n::BindingRegister::Images => unsafe {
+                    let loc = gl.get_uniform_location(program, name);
+                    gl.uniform_1_i32(loc.as_ref(), slot as _);
+                    // the elements of arrays take the consecutive slots
+                    for (element, name) in array_element_names(name) {
+                        match gl.get_uniform_location(program, &name) {
+                            Some(loc) => gl.uniform_1_i32(Some(&loc), slot as i32 + element),
+                            None => break,
+                        }
+                    }
+                },
+                n::BindingRegister::UniformBuffers => unsafe {
+                    // arrays of blocks are only known by the names of the elements
+                    let index = gl
+                        .get_uniform_block_index(program, name)
+                        .or_else(|| gl.get_uniform_block_index(program, &format!("{}[0]", name)))
+                        .unwrap();
+                    gl.uniform_block_binding(program, index, slot as _);
+                    for (element, name) in array_element_names(name) {
+                        match gl.get_uniform_block_index(program, &name) {
+                            Some(index) => gl.uniform_block_binding(
+                                program,
+                                index,
+                                slot as u32 + element as u32,
+                            ),
+                            None => break,
+                        }
+                    }
+                },
+                n::BindingRegister::StorageBuffers => unsafe {
+                    let index = gl
+                        .get_shader_storage_block_index(program, name)
+                        .or_else(|| {
+                            gl.get_shader_storage_block_index(program, &format!("{}[0]", name))
+                        })
+                        .unwrap();
+                    gl.shader_storage_block_binding(program, index, slot as _);
+                    for (element, name) in array_element_names(name) {
+                        match gl.get_shader_storage_block_index(program, &name) {
+                            Some(index) => gl.shader_storage_block_binding(
+                                program,
+                                index,
+                                slot as u32 + element as u32,
+                            ),
+                            None => break,
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Re-create a program from the pipeline cache, preferring the driver binary
+    /// and falling back to the cached sources.
+    fn create_cached_program(&self, cached: &n::CachedProgram) -> Option<glow::Program> {
+        let gl = &self.share.context;
+
+        if let Some((format, ref binary)) = cached.binary {
+            if self.share.private_caps.program_binary {
+                let program = unsafe { gl.create_program() }.unwrap();
+                let linked_ok = unsafe {
+                    gl.ext.program_binary(
+                        program,
+                        format,
+                        binary.as_ptr() as *const _,
+                        binary.len() as i32,
+                    );
+                    gl.get_program_link_status(program)
+                };
+                if linked_ok {
+                    self.assign_bindings(program, &cached.name_bindings);
+                    return Some(program);
+                }
+                // Drivers reject the binaries of other driver versions.
+                info!("\tCached program binary was rejected");
+                unsafe {
+                    gl.delete_program(program);
+                }
+            }
+        }
+
+        let program = unsafe { gl.create_program() }.unwrap();
+        for &(target, ref source) in cached.sources.iter() {
+            match Self::create_shader_raw(gl, source, target) {
+                Ok(shader) => unsafe {
+                    gl.attach_shader(program, shader);
+                    gl.delete_shader(shader);
+                },
+                Err(err) => {
+                    warn!("Cached shader compilation failed: {:?}", err);
+                    unsafe {
+                        gl.delete_program(program);
+                    }
+                    return None;
+                }
+            }
+        }
+        if self.link_program(program).is_err() {
+            unsafe {
+                gl.delete_program(program);
+            }
+            return None;
+        }
+        self.assign_bindings(program, &cached.name_bindings);
+        Some(program)
+    }
+
+    /// Retrieve the driver binary of a linked program, if supported.
+    fn get_program_binary(&self, program: glow::Program) -> Option<(u32, Vec<u8>)> {
+        if !self.share.private_caps.program_binary {
+            return None;
+        }
+        let gl = &self.share.context;
+        let mut length = 0;
+        unsafe {
+            gl.ext
+                .get_program_iv(program, glow::PROGRAM_BINARY_LENGTH, &mut length);
+        }
+        if length <= 0 {
+            return None;
+        }
+        let mut binary = vec![0u8; length as usize];
+        let mut format = 0;
+        unsafe {
+            gl.ext.get_program_binary(
+                program,
+                length,
+                &mut length,
+                &mut format,
+                binary.as_mut_ptr() as *mut _,
+            );
+        }
+        binary.truncate(length.max(0) as usize);
+        Some((format, binary))
+    }
+
     fn create_shader_program(
         &self,
         shaders: &[(naga::ShaderStage, Option<&pso::EntryPoint<B>>)],
         layout: &n::PipelineLayout,
+        cache: Option<&n::PipelineCache>,
     ) -> Result<(glow::Program, n::SamplerBindMap), pso::CreationError> {
+        let cache_key = match cache {
+            Some(_) => self.program_cache_key(shaders, layout),
+            None => None,
+        };
+        if let (Some(cache), Some(key)) = (cache, cache_key.as_ref()) {
+            let cached = cache.programs.lock().get(key).cloned();
+            if let Some(cached) = cached {
+                if let Some(program) = self.create_cached_program(&cached) {
+                    return Ok((program, cached.sampler_map));
+                }
+                warn!("Failed to restore a cached program, compiling from scratch");
+            }
+        }
+
         let gl = &self.share.context;
         let program = unsafe { gl.create_program().unwrap() };
 
         let mut name_binding_map = FastHashMap::<String, (n::BindingRegister, u8)>::default();
         let mut sampler_map = [None; MAX_TEXTURE_SLOTS];
+        let mut sources = Vec::new();
 
         let mut has_vertex_stage = false;
         let mut has_fragment_stage = false;
@@ -161,13 +408,19 @@ impl Device {
 
         for &(stage, point_maybe) in shaders {
             if let Some(point) = point_maybe {
-                match stage {
-                    naga::ShaderStage::Vertex => has_vertex_stage = true,
-                    naga::ShaderStage::Fragment => has_fragment_stage = true,
-                    naga::ShaderStage::Compute => (),
-                }
+                let target = match stage {
+                    naga::ShaderStage::Vertex => {
+                        has_vertex_stage = true;
+                        glow::VERTEX_SHADER
+                    }
+                    naga::ShaderStage::Fragment => {
+                        has_fragment_stage = true;
+                        glow::FRAGMENT_SHADER
+                    }
+                    naga::ShaderStage::Compute => glow::COMPUTE_SHADER,
+                };
 
-                let shader = self
+                let (shader, source) = self
                     .compile_shader(point, stage, context.reborrow())
                     .map_err(|err| {
                         error!("Compilation failed: {:?}", err);
@@ -177,6 +430,7 @@ impl Device {
                     gl.attach_shader(program, shader);
                     gl.delete_shader(shader);
                 }
+                sources.push((target, source));
             }
         }
 
@@ -204,55 +458,60 @@ impl Device {
                 gl.attach_shader(program, shader);
                 gl.delete_shader(shader);
             }
+            sources.push((glow::FRAGMENT_SHADER, shader_src));
         }
 
-        unsafe {
-            gl.link_program(program);
+        if cache_key.is_some() && self.share.private_caps.program_binary {
+            unsafe {
+                gl.ext
+                    .program_parameter_i32(program, ext::PROGRAM_BINARY_RETRIEVABLE_HINT, 1);
+            }
         }
-        info!("\tLinked program {:?}", program);
-        if let Err(err) = self.share.check() {
-            panic!("Error linking program: {:?}", err);
+        self.link_program(program)?;
+
+        let name_bindings = name_binding_map
+            .into_iter()
+            .map(|(name, (register, slot))| (name, register, slot))
+            .collect::<Vec<_>>();
+        self.assign_bindings(program, &name_bindings);
+
+        if let (Some(cache), Some(key)) = (cache, cache_key) {
+            let cached = n::CachedProgram {
+                sources,
+                binary: self.get_program_binary(program),
+                sampler_map,
+                name_bindings,
+            };
+            cache.programs.lock().insert(key, cached);
         }
 
-        let linked_ok = unsafe { gl.get_program_link_status(program) };
-        let log = unsafe { gl.get_program_info_log(program) };
-        if !linked_ok {
-            error!("\tLog: {}", log);
-            return Err(pso::CreationError::Other);
-        }
-        if !log.is_empty() {
-            warn!("\tLog: {}", log);
-        }
+        Ok((program, sampler_map))
+    }
 
-        if !self
-            .share
-            .legacy_features
-            .contains(LegacyFeatures::EXPLICIT_LAYOUTS_IN_SHADER)
-        {
-            unsafe {
-                gl.use_program(Some(program));
-            }
-            for (name, &(register, slot)) in name_binding_map.iter() {
-                match register {
-                    n::BindingRegister::Textures => unsafe {
-                        let loc = gl.get_uniform_location(program, name);
-                        gl.uniform_1_i32(loc.as_ref(), slot as _);
-                    },
-                    n::BindingRegister::UniformBuffers => unsafe {
-                        let index = gl.get_uniform_block_index(program, name).unwrap();
-                        gl.uniform_block_binding(program, index, slot as _);
-                    },
-                    n::BindingRegister::StorageBuffers => unsafe {
-                        let index = gl.get_shader_storage_block_index(program, name).unwrap();
-                        gl.shader_storage_block_binding(program, index, slot as _);
-                    },
-                }
-            }
+    /// Attach a debug label to a GL object, visible in the graphics debuggers
+    /// and in the command buffer dumps.
+    #[cfg(not(target_arch = "wasm32"))]
+    unsafe fn set_object_label(&self, identifier: u32, object: u32, name: &str) {
+        self.share.labels.set(identifier, object, name);
+        if self.share.private_caps.debug_labels {
+            self.share
+                .context
+                .object_label(identifier, object, Some(name));
         }
+    }
 
-        Ok((program, sampler_map))
+    #[cfg(target_arch = "wasm32")]
+    unsafe fn set_object_label<T>(&self, _identifier: u32, _object: T, _name: &str) {}
+
+    /// Forget the label of a GL object that is deleted.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn remove_object_label(&self, identifier: u32, object: u32) {
+        self.share.labels.remove(identifier, object);
     }
 
+    #[cfg(target_arch = "wasm32")]
+    fn remove_object_label<T>(&self, _identifier: u32, _object: T) {}
+
     fn _bind_target_compat(gl: &GlContainer, point: u32, attachment: u32, view: &n::ImageView) {
         match *view {
             n::ImageView::Renderbuffer { raw: rb, .. } => unsafe {
@@ -263,6 +522,7 @@ impl Device {
                 raw,
                 ref sub,
                 is_3d: false,
+                ..
             } => unsafe {
                 gl.bind_texture(target, Some(raw));
                 gl.framebuffer_texture_2d(
@@ -278,6 +538,7 @@ impl Device {
                 raw,
                 ref sub,
                 is_3d: true,
+                ..
             } => unsafe {
                 gl.bind_texture(target, Some(raw));
                 gl.framebuffer_texture_3d(
@@ -297,11 +558,30 @@ impl Device {
             n::ImageView::Renderbuffer { raw: rb, .. } => unsafe {
                 gl.framebuffer_renderbuffer(point, attachment, glow::RENDERBUFFER, Some(rb));
             },
+            // A single face of a cube map, see `command::attachment_target`.
+            n::ImageView::Texture {
+                target,
+                raw,
+                ref sub,
+                is_3d: false,
+                ..
+            } if target >= glow::TEXTURE_CUBE_MAP_POSITIVE_X
+                && target <= glow::TEXTURE_CUBE_MAP_NEGATIVE_Z =>
+            unsafe {
+                gl.framebuffer_texture_2d(
+                    point,
+                    attachment,
+                    target,
+                    Some(raw),
+                    sub.level_start as _,
+                );
+            },
             n::ImageView::Texture {
                 target: _,
                 raw,
                 ref sub,
                 is_3d: false,
+                ..
             } => unsafe {
                 gl.framebuffer_texture(point, attachment, Some(raw), sub.level_start as _);
             },
@@ -310,6 +590,7 @@ impl Device {
                 raw,
                 ref sub,
                 is_3d: true,
+                ..
             } => unsafe {
                 gl.framebuffer_texture_layer(
                     point,
@@ -418,6 +699,13 @@ impl Device {
             layout,
             nb_map,
         );
+        self.remap_binding(
+            ast,
+            &res.storage_images,
+            n::BindingRegister::Images,
+            layout,
+            nb_map,
+        );
         self.remap_binding(
             ast,
             &res.uniform_buffers,
@@ -591,7 +879,7 @@ impl Device {
         shader: &d::NagaShader,
         options: &naga::back::glsl::Options,
         context: CompilationContext,
-    ) -> Result<n::Shader, d::ShaderError> {
+    ) -> Result<(n::Shader, String), d::ShaderError> {
         let mut output = Vec::new();
         let mut writer =
             naga::back::glsl::Writer::new(&mut output, &shader.module, &shader.analysis, options)
@@ -613,6 +901,7 @@ impl Device {
                 let source = String::from_utf8(output).unwrap();
                 debug!("Naga generated shader:\n{}", source);
                 Self::create_shader_module_raw(gl, &source, options.shader_stage)
+                    .map(|shader| (shader, source))
             }
             Err(e) => {
                 warn!("Naga GLSL write: {}", e);
@@ -626,7 +915,7 @@ impl Device {
         ep: &pso::EntryPoint<B>,
         stage: naga::ShaderStage,
         mut context: CompilationContext,
-    ) -> Result<n::Shader, d::ShaderError> {
+    ) -> Result<(n::Shader, String), d::ShaderError> {
         let naga_options = naga::back::glsl::Options {
             version: {
                 use naga::back::glsl::Version;
@@ -655,7 +944,8 @@ impl Device {
         }
         #[cfg(feature = "cross")]
         if result.is_err() {
-            let mut ast = self.parse_spirv_cross(&ep.module.spv).unwrap();
+            let spv = ep.module.spv.as_ref().unwrap();
+            let mut ast = self.parse_spirv_cross(spv).unwrap();
             auxil::spirv_cross_specialize_ast(&mut ast, &ep.specialization).unwrap();
             self.remap_bindings(&mut ast, context.layout, context.name_binding_map);
             self.combine_separate_images_and_samplers(&mut ast, context.reborrow());
@@ -665,7 +955,8 @@ impl Device {
                 .translate_spirv_cross(&mut ast, stage, ep.entry)
                 .unwrap();
             debug!("SPIRV-Cross generated shader:\n{}", glsl);
-            result = Self::create_shader_module_raw(&self.share.context, &glsl, stage);
+            result = Self::create_shader_module_raw(&self.share.context, &glsl, stage)
+                .map(|shader| (shader, glsl));
         }
         if result.is_err() && !ep.module.prefer_naga {
             if let Some(ref shader) = ep.module.naga {
@@ -681,6 +972,133 @@ impl Device {
     }
 }
 
+/// Names of the elements of a resource array, past the first one.
+fn array_element_names(name: &str) -> impl Iterator<Item = (i32, String)> + '_ {
+    (1..).map(move |element| (element, format!("{}[{}]", name, element)))
+}
+
+/// Access of a storage image descriptor, for `glBindImageTexture`.
+fn image_access(read_only: bool) -> u32 {
+    if read_only {
+        glow::READ_ONLY
+    } else {
+        glow::READ_WRITE
+    }
+}
+
+/// Write the descriptors into the set, starting at the given binding and array offset.
+pub(crate) fn write_descriptors<'a, I>(
+    set: &mut n::DescriptorSet,
+    binding: pso::DescriptorBinding,
+    mut array_offset: pso::DescriptorArrayIndex,
+    descriptors: I,
+) where
+    I: Iterator<Item = pso::Descriptor<'a, B>>,
+{
+    let mut layout_index = set
+        .layout
+        .bindings
+        .binary_search_by_key(&binding, |b| b.binding)
+        .unwrap();
+
+    for descriptor in descriptors {
+        let binding_layout = &set.layout.bindings[layout_index];
+        let binding = match descriptor {
+            pso::Descriptor::Buffer(buffer, ref sub) => {
+                let (raw_buffer, buffer_range) = buffer.as_bound();
+                let range = crate::resolve_sub_range(sub, buffer_range);
+
+                let register = match binding_layout.ty {
+                    pso::DescriptorType::Buffer { ty, .. } => match ty {
+                        pso::BufferDescriptorType::Uniform => n::BindingRegister::UniformBuffers,
+                        pso::BufferDescriptorType::Storage { .. } => {
+                            n::BindingRegister::StorageBuffers
+                        }
+                    },
+                    other => {
+                        panic!("Can't write buffer into descriptor of type {:?}", other)
+                    }
+                };
+
+                n::DescSetBindings::Buffer {
+                    register,
+                    buffer: raw_buffer,
+                    offset: range.start as i32,
+                    size: (range.end - range.start) as i32,
+                }
+            }
+            pso::Descriptor::CombinedImageSampler(view, _layout, sampler) => match *view {
+                n::ImageView::Texture { target, raw, .. } => {
+                    n::DescSetBindings::CombinedImageSampler(raw, target, sampler.clone())
+                }
+                n::ImageView::Renderbuffer { .. } => {
+                    panic!("Texture doesn't support shader binding")
+                }
+            },
+            pso::Descriptor::Image(view, _layout) => match (view, binding_layout.ty) {
+                (
+                    &n::ImageView::Texture {
+                        raw,
+                        format,
+                        is_3d,
+                        ref sub,
+                        ..
+                    },
+                    pso::DescriptorType::Image {
+                        ty: pso::ImageDescriptorType::Storage { read_only },
+                    },
+                ) => n::DescSetBindings::Image(n::ImageBinding {
+                    texture: raw,
+                    level: sub.level_start as i32,
+                    layer: if is_3d {
+                        None
+                    } else {
+                        Some(sub.layer_start as i32)
+                    },
+                    access: image_access(read_only),
+                    format,
+                }),
+                (&n::ImageView::Texture { target, raw, .. }, _) => {
+                    n::DescSetBindings::Texture(raw, target)
+                }
+                (&n::ImageView::Renderbuffer { .. }, _) => {
+                    panic!("Texture doesn't support shader binding")
+                }
+            },
+            pso::Descriptor::Sampler(sampler) => n::DescSetBindings::Sampler(sampler.clone()),
+            pso::Descriptor::TexelBuffer(view) => match binding_layout.ty {
+                pso::DescriptorType::Buffer {
+                    ty: pso::BufferDescriptorType::Uniform,
+                    format: pso::BufferDescriptorFormat::Texel,
+                } => n::DescSetBindings::Texture(view.raw, glow::TEXTURE_BUFFER),
+                pso::DescriptorType::Buffer {
+                    ty: pso::BufferDescriptorType::Storage { read_only },
+                    format: pso::BufferDescriptorFormat::Texel,
+                } => n::DescSetBindings::Image(n::ImageBinding {
+                    texture: view.raw,
+                    level: 0,
+                    layer: Some(0),
+                    access: image_access(read_only),
+                    format: view.format,
+                }),
+                other => panic!(
+                    "Can't write texel buffer into descriptor of type {:?}",
+                    other
+                ),
+            },
+        };
+
+        let index = set.layout.offsets[layout_index] + array_offset;
+        set.bindings[index] = Some(binding);
+
+        array_offset += 1;
+        if array_offset == binding_layout.count {
+            array_offset = 0;
+            layout_index += 1;
+        }
+    }
+}
+
 pub(crate) unsafe fn set_sampler_info<SetParamFloat, SetParamFloatVec, SetParamInt>(
     info: &i::SamplerDesc,
     features: &hal::Features,
@@ -819,10 +1237,13 @@ impl d::Device<B> for Device {
                     panic!("Error allocating memory buffer {:?}", err);
                 }
 
+                self.share.memory_usage[memory_type.heap_index].fetch_add(size, Ordering::Relaxed);
+
                 Ok(n::Memory {
                     properties: memory_type.properties,
                     buffer: Some((raw, target)),
                     size,
+                    heap_index: memory_type.heap_index,
                     map_flags,
                     emulate_map_allocation: None,
                 })
@@ -830,10 +1251,13 @@ impl d::Device<B> for Device {
 
             MemoryUsage::Image => {
                 assert!(is_device_local_memory);
+                self.share.memory_usage[memory_type.heap_index].fetch_add(size, Ordering::Relaxed);
+
                 Ok(n::Memory {
                     properties: memory::Properties::DEVICE_LOCAL,
                     buffer: None,
                     size,
+                    heap_index: memory_type.heap_index,
                     map_flags: 0,
                     emulate_map_allocation: None,
                 })
@@ -847,6 +1271,8 @@ impl d::Device<B> for Device {
         flags: CommandPoolCreateFlags,
     ) -> Result<CommandPool, d::OutOfMemory> {
         let fbo = create_fbo_internal(&self.share);
+        let read_fbo = create_fbo_internal(&self.share);
+        let render_fbo = create_fbo_internal(&self.share);
         let limits = self.share.public_caps.limits.into();
         let memory = if flags.contains(CommandPoolCreateFlags::RESET_INDIVIDUAL) {
             BufferMemory::Individual {
@@ -861,15 +1287,23 @@ impl d::Device<B> for Device {
 
         Ok(CommandPool {
             fbo,
+            read_fbo,
+            render_fbo,
             limits,
             memory: Arc::new(Mutex::new(memory)),
             legacy_features: self.share.legacy_features,
+            labels: Arc::clone(&self.share.labels),
         })
     }
 
     unsafe fn destroy_command_pool(&self, pool: CommandPool) {
-        if let Some(fbo) = pool.fbo {
-            let gl = &self.share.context;
+        let gl = &self.share.context;
+        for fbo in pool
+            .fbo
+            .into_iter()
+            .chain(pool.read_fbo)
+            .chain(pool.render_fbo)
+        {
             gl.delete_framebuffer(fbo);
         }
     }
@@ -893,10 +1327,15 @@ impl d::Device<B> for Device {
                 let color_attachments = subpass.colors.iter().map(|&(index, _)| index).collect();
 
                 let depth_stencil = subpass.depth_stencil.map(|ds| ds.0);
+                let input_attachments = subpass.inputs.iter().map(|&(index, _)| index).collect();
+                let resolve_attachments =
+                    subpass.resolves.iter().map(|&(index, _)| index).collect();
 
                 n::SubpassDesc {
                     color_attachments,
                     depth_stencil,
+                    input_attachments,
+                    resolve_attachments,
                 }
             })
             .collect();
@@ -919,16 +1358,22 @@ impl d::Device<B> for Device {
         let mut sets = Vec::new();
         let mut num_samplers = 0usize;
         let mut num_textures = 0usize;
+        let mut num_images = 0usize;
         let mut num_uniform_buffers = 0usize;
         let mut num_storage_buffers = 0usize;
 
         for layout_bindings in layouts {
             // create a vector with the size enough to hold all the bindings, filled with `!0`
-            let mut bindings =
-                vec![!0; layout_bindings.last().map_or(0, |b| b.binding as usize + 1)];
-
-            for binding in layout_bindings.iter() {
-                assert!(!binding.immutable_samplers); //TODO
+            let mut bindings = vec![
+                !0;
+                layout_bindings
+                    .bindings
+                    .last()
+                    .map_or(0, |b| b.binding as usize + 1)
+            ];
+
+            // Arrays take consecutive slots, starting at the slot of the binding.
+            for binding in layout_bindings.bindings.iter() {
                 let counter = match binding.ty {
                     pso::DescriptorType::Sampler => &mut num_samplers,
                     pso::DescriptorType::InputAttachment | pso::DescriptorType::Image { .. } => {
@@ -936,7 +1381,16 @@ impl d::Device<B> for Device {
                     }
                     pso::DescriptorType::Buffer {
                         ty,
-                        format: _, //TODO
+                        format: pso::BufferDescriptorFormat::Texel,
+                    } => match ty {
+                        pso::BufferDescriptorType::Uniform => &mut num_textures,
+                        pso::BufferDescriptorType::Storage { .. } => &mut num_images,
+                    },
+                    // Dynamic offsets only shift the bound range, they are applied
+                    // when binding the descriptor sets.
+                    pso::DescriptorType::Buffer {
+                        ty,
+                        format: pso::BufferDescriptorFormat::Structured { .. },
                     } => match ty {
                         pso::BufferDescriptorType::Uniform => &mut num_uniform_buffers,
                         pso::BufferDescriptorType::Storage { .. } => &mut num_storage_buffers,
@@ -956,31 +1410,59 @@ impl d::Device<B> for Device {
         Ok(n::PipelineLayout { sets })
     }
 
-    unsafe fn create_pipeline_cache(&self, _data: Option<&[u8]>) -> Result<(), d::OutOfMemory> {
-        Ok(())
+    unsafe fn create_pipeline_cache(
+        &self,
+        data: Option<&[u8]>,
+    ) -> Result<n::PipelineCache, d::OutOfMemory> {
+        let data = match data {
+            Some(data) if !data.is_empty() => data,
+            _ => return Ok(n::PipelineCache::default()),
+        };
+        let header = self.share.pipeline_cache_header();
+        match n::PipelineCache::deserialize(data, &header) {
+            Some(cache) => Ok(cache),
+            None => {
+                warn!("Pipeline cache data is invalid or from another driver, ignoring");
+                Ok(n::PipelineCache::default())
+            }
+        }
     }
 
-    unsafe fn get_pipeline_cache_data(&self, _cache: &()) -> Result<Vec<u8>, d::OutOfMemory> {
-        //empty
-        Ok(Vec::new())
+    unsafe fn get_pipeline_cache_data(
+        &self,
+        cache: &n::PipelineCache,
+    ) -> Result<Vec<u8>, d::OutOfMemory> {
+        Ok(cache.serialize(&self.share.pipeline_cache_header()))
     }
 
-    unsafe fn destroy_pipeline_cache(&self, _: ()) {
+    unsafe fn destroy_pipeline_cache(&self, _: n::PipelineCache) {
         //empty
     }
 
-    unsafe fn merge_pipeline_caches<'a, I>(&self, _: &mut (), _: I) -> Result<(), d::OutOfMemory>
+    unsafe fn merge_pipeline_caches<'a, I>(
+        &self,
+        target: &mut n::PipelineCache,
+        sources: I,
+    ) -> Result<(), d::OutOfMemory>
     where
-        I: Iterator<Item = &'a ()>,
+        I: Iterator<Item = &'a n::PipelineCache>,
     {
-        //empty
+        let programs = target.programs.get_mut();
+        for source in sources {
+            let source_programs = source.programs.lock();
+            programs.extend(
+                source_programs
+                    .iter()
+                    .map(|(key, program)| (key.clone(), program.clone())),
+            );
+        }
         Ok(())
     }
 
     unsafe fn create_graphics_pipeline<'a>(
         &self,
         desc: &pso::GraphicsPipelineDesc<'a, B>,
-        _cache: Option<&()>,
+        cache: Option<&n::PipelineCache>,
     ) -> Result<n::GraphicsPipeline, pso::CreationError> {
         let (vertex_buffers, desc_attributes, input_assembler, vs) = match desc.primitive_assembler
         {
@@ -1015,7 +1497,8 @@ impl d::Device<B> for Device {
             (naga::ShaderStage::Vertex, Some(vs)),
             (naga::ShaderStage::Fragment, desc.fragment.as_ref()),
         ];
-        let (program, sampler_map) = self.create_shader_program(&shaders[..], &desc.layout)?;
+        let (program, sampler_map) =
+            self.create_shader_program(&shaders[..], &desc.layout, cache)?;
 
         let patch_size = match input_assembler.primitive {
             pso::Primitive::PatchList(size) => Some(size as _),
@@ -1070,6 +1553,8 @@ impl d::Device<B> for Device {
             uniforms,
             rasterizer: desc.rasterizer,
             depth: desc.depth_stencil.depth,
+            depth_bounds: desc.depth_stencil.depth_bounds,
+            stencil: desc.depth_stencil.stencil,
             baked_states: desc.baked_states.clone(),
             sampler_map,
         })
@@ -1078,13 +1563,13 @@ impl d::Device<B> for Device {
     unsafe fn create_compute_pipeline<'a>(
         &self,
         desc: &pso::ComputePipelineDesc<'a, B>,
-        _cache: Option<&()>,
+        cache: Option<&n::PipelineCache>,
     ) -> Result<n::ComputePipeline, pso::CreationError> {
         if self.share.public_caps.limits.max_compute_work_group_count[0] == 0 {
             return Err(pso::CreationError::UnsupportedPipeline);
         }
         let shader = (naga::ShaderStage::Compute, Some(&desc.shader));
-        let (program, sampler_map) = self.create_shader_program(&[shader], &desc.layout)?;
+        let (program, sampler_map) = self.create_shader_program(&[shader], &desc.layout, cache)?;
         Ok(n::ComputePipeline {
             program,
             sampler_map,
@@ -1182,8 +1667,7 @@ impl d::Device<B> for Device {
     ) -> Result<n::ShaderModule, d::ShaderError> {
         Ok(n::ShaderModule {
             prefer_naga: self.always_prefer_naga,
-            #[cfg(feature = "cross")]
-            spv: raw_data.to_vec(),
+            spv: Some(raw_data.to_vec()),
             naga: {
                 let parser =
                     naga::front::spv::Parser::new(raw_data.iter().cloned(), &Default::default());
@@ -1211,19 +1695,21 @@ impl d::Device<B> for Device {
         &self,
         shader: d::NagaShader,
     ) -> Result<n::ShaderModule, (d::ShaderError, d::NagaShader)> {
-        Ok(n::ShaderModule {
-            prefer_naga: true,
-            #[cfg(feature = "cross")]
-            spv: match naga::back::spv::write_vec(
-                &shader.module,
-                &shader.analysis,
-                &self.spv_options,
-            ) {
+        #[cfg(feature = "cross")]
+        let spv =
+            match naga::back::spv::write_vec(&shader.module, &shader.analysis, &self.spv_options) {
                 Ok(spv) => spv,
                 Err(e) => {
                     return Err((d::ShaderError::CompilationFailed(format!("{}", e)), shader))
                 }
-            },
+            };
+        Ok(n::ShaderModule {
+            prefer_naga: true,
+            // Without SPIR-V there is nothing stable to identify the module by.
+            #[cfg(feature = "cross")]
+            spv: Some(spv),
+            #[cfg(not(feature = "cross"))]
+            spv: None,
             naga: Some(shader),
         })
     }
@@ -1299,8 +1785,8 @@ impl d::Device<B> for Device {
         offset: u64,
         buffer: &mut n::Buffer,
     ) -> Result<(), d::BindError> {
-        let size = match *buffer {
-            n::Buffer::Unbound { size, .. } => size,
+        let (size, usage) = match *buffer {
+            n::Buffer::Unbound { size, usage } => (size, usage),
             n::Buffer::Bound { .. } => panic!("Unexpected Buffer::Bound"),
         };
 
@@ -1310,11 +1796,16 @@ impl d::Device<B> for Device {
                 .expect("Improper memory type used for buffer memory")
                 .0,
             range: offset..offset + size,
+            usage,
         };
 
         Ok(())
     }
 
+    unsafe fn get_buffer_device_address(&self, _buffer: &n::Buffer) -> buffer::DeviceAddress {
+        unreachable!("Feature BUFFER_DEVICE_ADDRESS is not supported")
+    }
+
     unsafe fn map_memory(
         &self,
         memory: &mut n::Memory,
@@ -1439,11 +1930,49 @@ impl d::Device<B> for Device {
 
     unsafe fn create_buffer_view(
         &self,
-        _: &n::Buffer,
-        _: Option<Format>,
-        _: buffer::SubRange,
+        buffer: &n::Buffer,
+        format: Option<Format>,
+        sub: buffer::SubRange,
     ) -> Result<n::BufferView, buffer::ViewCreationError> {
-        unimplemented!()
+        if !self.share.private_caps.texture_buffer {
+            return Err(buffer::ViewCreationError::UnsupportedFormat(format));
+        }
+        let desc = match format.and_then(conv::describe_format) {
+            Some(desc) if conv::is_texel_buffer_format(&desc) => desc,
+            _ => return Err(buffer::ViewCreationError::UnsupportedFormat(format)),
+        };
+        // Storage texel buffers are bound as images, which only support a subset of the formats.
+        if let n::Buffer::Bound { usage, .. } = *buffer {
+            if usage.contains(buffer::Usage::STORAGE_TEXEL)
+                && !format.map_or(false, conv::is_image_format)
+            {
+                return Err(buffer::ViewCreationError::UnsupportedFormat(format));
+            }
+        }
+
+        let gl = &self.share.context;
+        let (raw_buffer, buffer_range) = buffer.as_bound();
+        let range = crate::resolve_sub_range(&sub, buffer_range);
+
+        let raw = gl.create_texture().unwrap();
+        gl.bind_texture(glow::TEXTURE_BUFFER, Some(raw));
+        gl.ext.tex_buffer_range(
+            glow::TEXTURE_BUFFER,
+            desc.tex_internal,
+            raw_buffer,
+            range.start as isize,
+            (range.end - range.start) as isize,
+        );
+        gl.bind_texture(glow::TEXTURE_BUFFER, None);
+
+        if let Err(err) = self.share.check() {
+            panic!("Error creating buffer view: {:?}", err);
+        }
+
+        Ok(n::BufferView {
+            raw,
+            format: desc.tex_internal,
+        })
     }
 
     unsafe fn create_image(
@@ -1576,6 +2105,7 @@ impl d::Device<B> for Device {
             n::ImageType::Renderbuffer {
                 raw: name,
                 format: desc.tex_external,
+                pixel_type: desc.data_type,
             }
         };
 
@@ -1655,6 +2185,7 @@ impl d::Device<B> for Device {
                     Ok(n::ImageView::Renderbuffer {
                         raw,
                         aspects: image.format_desc.aspects,
+                        samples: image.kind.num_samples(),
                     })
                 } else if level != 0 {
                     Err(i::ViewCreationError::Level(level)) //TODO
@@ -1672,7 +2203,7 @@ impl d::Device<B> for Device {
                     i::ViewKind::D1 | i::ViewKind::D2 => false,
                     _ => true,
                 };
-                match conv::describe_format(view_format) {
+                let view_format = match conv::describe_format(view_format) {
                     Some(description) => {
                         let raw_view_format = description.tex_internal;
                         if format != raw_view_format {
@@ -1681,16 +2212,20 @@ impl d::Device<B> for Device {
                                 raw_view_format, format
                             );
                         }
+                        raw_view_format
                     }
                     None => {
                         warn!("View format {:?} is not supported", view_format);
+                        format
                     }
-                }
+                };
                 Ok(n::ImageView::Texture {
                     target,
                     raw,
+                    format: view_format,
                     is_3d,
                     sub: range,
+                    samples: image.kind.num_samples(),
                 })
             }
         }
@@ -1711,97 +2246,97 @@ impl d::Device<B> for Device {
     unsafe fn create_descriptor_set_layout<'a, I, J>(
         &self,
         layout: I,
-        _immutable_samplers: J,
+        immutable_samplers: J,
     ) -> Result<n::DescriptorSetLayout, d::OutOfMemory>
     where
         I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
         J: Iterator<Item = &'a n::FatSampler>,
     {
-        let mut bindings = layout.collect::<Vec<_>>();
+        // immutable samplers are provided in the order of the bindings
+        let mut immutable_samplers = immutable_samplers;
+        let mut bindings = layout
+            .map(|binding| {
+                let samplers = if binding.immutable_samplers {
+                    immutable_samplers
+                        .by_ref()
+                        .take(binding.count)
+                        .cloned()
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                (binding, samplers)
+            })
+            .collect::<Vec<_>>();
         // all operations rely on the ascending bindings order
-        bindings.sort_by_key(|b| b.binding);
-        Ok(Arc::new(bindings))
+        bindings.sort_by_key(|&(ref b, _)| b.binding);
+
+        let mut offsets = Vec::with_capacity(bindings.len());
+        let mut samplers = Vec::new();
+        for &(ref binding, ref binding_samplers) in bindings.iter() {
+            offsets.push(samplers.len());
+            if binding_samplers.is_empty() {
+                samplers.extend((0..binding.count).map(|_| None));
+            } else {
+                assert_eq!(binding_samplers.len(), binding.count);
+                samplers.extend(binding_samplers.iter().cloned().map(Some));
+            }
+        }
+
+        Ok(Arc::new(n::SetLayout {
+            bindings: bindings.into_iter().map(|(binding, _)| binding).collect(),
+            offsets,
+            immutable_samplers: samplers,
+        }))
+    }
+
+    unsafe fn create_push_descriptor_set_layout<'a, I, J>(
+        &self,
+        layout: I,
+        immutable_samplers: J,
+    ) -> Result<n::DescriptorSetLayout, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
+        J: Iterator<Item = &'a n::FatSampler>,
+    {
+        // Push descriptors are bound directly, so the layout is the same.
+        self.create_descriptor_set_layout(layout, immutable_samplers)
     }
 
     unsafe fn write_descriptor_set<'a, I>(&self, op: pso::DescriptorSetWrite<'a, B, I>)
     where
         I: Iterator<Item = pso::Descriptor<'a, B>>,
     {
-        let mut layout_index = op
-            .set
-            .layout
-            .binary_search_by_key(&op.binding, |b| b.binding)
-            .unwrap();
-        let mut array_offset = op.array_offset;
-
-        for descriptor in op.descriptors {
-            let binding_layout = &op.set.layout[layout_index];
-            let binding = match descriptor {
-                pso::Descriptor::Buffer(buffer, ref sub) => {
-                    let (raw_buffer, buffer_range) = buffer.as_bound();
-                    let range = crate::resolve_sub_range(sub, buffer_range);
-
-                    let register = match binding_layout.ty {
-                        pso::DescriptorType::Buffer { ty, .. } => match ty {
-                            pso::BufferDescriptorType::Uniform => {
-                                n::BindingRegister::UniformBuffers
-                            }
-                            pso::BufferDescriptorType::Storage { .. } => {
-                                n::BindingRegister::StorageBuffers
-                            }
-                        },
-                        other => {
-                            panic!("Can't write buffer into descriptor of type {:?}", other)
-                        }
-                    };
-
-                    n::DescSetBindings::Buffer {
-                        register,
-                        buffer: raw_buffer,
-                        offset: range.start as i32,
-                        size: (range.end - range.start) as i32,
-                    }
-                }
-                pso::Descriptor::CombinedImageSampler(view, _layout, sampler) => {
-                    match *view {
-                        n::ImageView::Texture { target, raw, .. } => op
-                            .set
-                            .bindings
-                            .push(n::DescSetBindings::Texture(raw, target)),
-                        n::ImageView::Renderbuffer { .. } => {
-                            panic!("Texture doesn't support shader binding")
-                        }
-                    }
-                    match *sampler {
-                        n::FatSampler::Sampler(sampler) => n::DescSetBindings::Sampler(sampler),
-                        n::FatSampler::Info(ref info) => {
-                            n::DescSetBindings::SamplerDesc(info.clone())
-                        }
-                    }
-                }
-                pso::Descriptor::Image(view, _layout) => match *view {
-                    n::ImageView::Texture { target, raw, .. } => {
-                        n::DescSetBindings::Texture(raw, target)
-                    }
-                    n::ImageView::Renderbuffer { .. } => {
-                        panic!("Texture doesn't support shader binding")
-                    }
-                },
-                pso::Descriptor::Sampler(sampler) => match *sampler {
-                    n::FatSampler::Sampler(sampler) => n::DescSetBindings::Sampler(sampler),
-                    n::FatSampler::Info(ref info) => n::DescSetBindings::SamplerDesc(info.clone()),
-                },
-                pso::Descriptor::TexelBuffer(_view) => unimplemented!(),
-            };
+        write_descriptors(op.set, op.binding, op.array_offset, op.descriptors);
+    }
 
-            //TODO: overwrite instead of pushing on top
-            op.set.bindings.push(binding);
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        _layout: &n::DescriptorSetLayout,
+        entries: I,
+    ) -> Result<n::DescriptorUpdateTemplate, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorUpdateTemplateEntry>,
+    {
+        Ok(entries.collect())
+    }
 
-            array_offset += 1;
-            if array_offset == binding_layout.count {
-                array_offset = 0;
-                layout_index += 1;
-            }
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        set: &mut n::DescriptorSet,
+        template: &n::DescriptorUpdateTemplate,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+        let mut descriptors = descriptors;
+        for entry in template {
+            write_descriptors(
+                set,
+                entry.binding,
+                entry.array_offset,
+                descriptors.by_ref().take(entry.count),
+            );
         }
     }
 
@@ -1810,14 +2345,14 @@ impl d::Device<B> for Device {
             panic!("copying within same descriptor set is not currently supported");
         }
 
-        // TODO: add support for array bindings when the OpenGL backend gets them
-        let src_start = op.src_binding as usize;
+        // Arrays are laid out consecutively, so copies spill onto the next bindings naturally.
+        let src_start = op.src_set.layout.descriptor_index(op.src_binding) + op.src_array_offset;
         let src_end = src_start + op.count;
         assert!(src_end <= op.src_set.bindings.len());
 
         let src_slice = &op.src_set.bindings[src_start..src_end];
 
-        let dst_start = op.dst_binding as usize;
+        let dst_start = op.dst_set.layout.descriptor_index(op.dst_binding) + op.dst_array_offset;
         let dst_end = dst_start + op.count;
         assert!(dst_end <= op.dst_set.bindings.len());
 
@@ -1825,7 +2360,103 @@ impl d::Device<B> for Device {
     }
 
     fn create_semaphore(&self) -> Result<n::Semaphore, d::OutOfMemory> {
-        Ok(n::Semaphore)
+        Ok(n::Semaphore::default())
+    }
+
+    fn create_timeline_semaphore(
+        &self,
+        initial_value: u64,
+    ) -> Result<n::Semaphore, d::OutOfMemory> {
+        Ok(n::Semaphore {
+            timeline: Mutex::new(fence::Timeline::new(initial_value)),
+        })
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        semaphore: &n::Semaphore,
+    ) -> Result<u64, d::WaitError> {
+        let mut fences = self.share.fences.lock();
+        Ok(semaphore
+            .timeline
+            .lock()
+            .update(&mut fences, &self.share.context))
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        semaphores: I,
+        wait: d::WaitFor,
+        timeout_ns: u64,
+    ) -> Result<bool, d::WaitError>
+    where
+        I: Iterator<Item = (&'a n::Semaphore, u64)>,
+    {
+        let gl = &self.share.context;
+        let mut fences = self.share.fences.lock();
+
+        // Like for the fences, reduce the wait to a single submission.
+        let mut target = None;
+        for (semaphore, value) in semaphores {
+            let mut timeline = semaphore.timeline.lock();
+            if timeline.update(&mut fences, gl) >= value {
+                if let d::WaitFor::Any = wait {
+                    return Ok(true);
+                }
+                continue;
+            }
+            match (timeline.submission_for(value), &wait) {
+                (Some(index), d::WaitFor::All) => target = target.max(Some(index)),
+                (Some(index), d::WaitFor::Any) => {
+                    target = Some(target.map_or(index, |t: u64| t.min(index)))
+                }
+                // Only the host could signal the value, which can't happen
+                // while we block the only thread using the context.
+                (None, d::WaitFor::All) => return Ok(false),
+                (None, d::WaitFor::Any) => {}
+            }
+        }
+
+        Ok(match target {
+            Some(index) => fences.wait(gl, index, timeout_ns),
+            None => match wait {
+                d::WaitFor::All => true,
+                d::WaitFor::Any => false,
+            },
+        })
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        semaphore: &n::Semaphore,
+        value: u64,
+    ) -> Result<(), d::OutOfMemory> {
+        semaphore.timeline.lock().signal(value);
+        Ok(())
+    }
+
+    fn create_exportable_semaphore(
+        &self,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<n::Semaphore, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        _semaphore: &n::Semaphore,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        _semaphore: &mut n::Semaphore,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
     }
 
     fn create_fence(&self, signaled: bool) -> Result<n::Fence, d::OutOfMemory> {
@@ -1842,39 +2473,19 @@ impl d::Device<B> for Device {
         fence: &n::Fence,
         timeout_ns: u64,
     ) -> Result<bool, d::WaitError> {
-        // TODO:
-        // This can be called by multiple objects wanting to ensure they have exclusive
-        // access to a resource. How much does this call costs ? The status of the fence
-        // could be cached to avoid calling this more than once (in core or in the backend ?).
-        let gl = &self.share.context;
-        match *fence {
-            n::Fence::Idle { signaled } => {
-                if !signaled {
-                    warn!("Fence ptr {:?} is not pending, waiting not possible", fence);
-                }
-                Ok(signaled)
-            }
-            n::Fence::Pending(sync) => {
-                // TODO: Could `wait_sync` be used here instead?
-                match gl.client_wait_sync(sync, glow::SYNC_FLUSH_COMMANDS_BIT, timeout_ns as i32) {
-                    glow::TIMEOUT_EXPIRED => Ok(false),
-                    glow::WAIT_FAILED => {
-                        if let Err(err) = self.share.check() {
-                            error!("Error when waiting on fence: {:?}", err);
-                        }
-                        Ok(false)
-                    }
-                    glow::CONDITION_SATISFIED | glow::ALREADY_SIGNALED => {
-                        //fence.0.set(n::Fence::Idle { signaled: true });
-                        Ok(true)
-                    }
-                    _ => unreachable!(),
-                }
+        Ok(match *fence {
+            // An unsignaled fence that hasn't been submitted can't be signaled
+            // while we block the only thread that can submit it.
+            n::Fence::Idle { signaled } => signaled,
+            n::Fence::Pending(index) => {
+                self.share
+                    .fences
+                    .lock()
+                    .wait(&self.share.context, index, timeout_ns)
             }
-        }
+        })
     }
 
-    #[cfg(target_arch = "wasm32")]
     unsafe fn wait_for_fences<'a, I>(
         &self,
         fences: I,
@@ -1884,70 +2495,140 @@ impl d::Device<B> for Device {
     where
         I: Iterator<Item = &'a n::Fence>,
     {
-        let performance = web_sys::window().unwrap().performance().unwrap();
-        let start = performance.now();
-        let get_elapsed = || ((performance.now() - start) * 1_000_000.0) as u64;
-
-        match wait {
-            d::WaitFor::All => {
-                for fence in fences {
-                    if !self.wait_for_fence(fence, 0)? {
-                        let elapsed_ns = get_elapsed();
-                        if elapsed_ns > timeout_ns {
-                            return Ok(false);
-                        }
-                        if !self.wait_for_fence(fence, timeout_ns - elapsed_ns)? {
-                            return Ok(false);
-                        }
+        // Submissions complete in order, so waiting for all the fences means
+        // waiting for the latest submission, and waiting for any of them
+        // means waiting for the earliest one.
+        let mut latest = None;
+        let mut earliest = None;
+        for fence in fences {
+            match *fence {
+                n::Fence::Idle { signaled: true } => {
+                    if let d::WaitFor::Any = wait {
+                        return Ok(true);
                     }
                 }
-                Ok(true)
-            }
-            d::WaitFor::Any => {
-                const FENCE_WAIT_NS: u64 = 100_000;
-
-                let fences: Vec<_> = fences.collect();
-                loop {
-                    for fence in &fences {
-                        if self.wait_for_fence(fence, FENCE_WAIT_NS)? {
-                            return Ok(true);
-                        }
-                    }
-                    if get_elapsed() >= timeout_ns {
+                n::Fence::Idle { signaled: false } => {
+                    if let d::WaitFor::All = wait {
                         return Ok(false);
                     }
                 }
+                n::Fence::Pending(index) => {
+                    latest = latest.max(Some(index));
+                    earliest = Some(earliest.map_or(index, |e: u64| e.min(index)));
+                }
             }
         }
+
+        let index = match wait {
+            d::WaitFor::All => latest,
+            d::WaitFor::Any => earliest,
+        };
+        Ok(match index {
+            Some(index) => self
+                .share
+                .fences
+                .lock()
+                .wait(&self.share.context, index, timeout_ns),
+            // Either all the fences are signaled, or none of them is pending.
+            None => match wait {
+                d::WaitFor::All => true,
+                d::WaitFor::Any => false,
+            },
+        })
     }
 
     unsafe fn get_fence_status(&self, fence: &n::Fence) -> Result<bool, d::DeviceLost> {
         Ok(match *fence {
             n::Fence::Idle { signaled } => signaled,
-            n::Fence::Pending(sync) => self.share.context.get_sync_status(sync) == glow::SIGNALED,
+            n::Fence::Pending(index) => self
+                .share
+                .fences
+                .lock()
+                .is_complete(&self.share.context, index),
         })
     }
 
-    fn create_event(&self) -> Result<(), d::OutOfMemory> {
-        unimplemented!()
+    fn create_event(&self) -> Result<n::Event, d::OutOfMemory> {
+        Ok(n::Event(Arc::new(Mutex::new(n::EventState::Reset))))
     }
 
-    unsafe fn get_event_status(&self, _event: &()) -> Result<bool, d::WaitError> {
-        unimplemented!()
+    unsafe fn get_event_status(&self, event: &n::Event) -> Result<bool, d::WaitError> {
+        let mut state = event.0.lock();
+        Ok(match *state {
+            n::EventState::Reset => false,
+            n::EventState::Set => true,
+            n::EventState::Pending(sync) => {
+                let gl = &self.share.context;
+                if gl.get_sync_status(sync) == glow::SIGNALED {
+                    gl.delete_sync(sync);
+                    *state = n::EventState::Set;
+                    true
+                } else {
+                    false
+                }
+            }
+        })
     }
 
-    unsafe fn set_event(&self, _event: &mut ()) -> Result<(), d::OutOfMemory> {
-        unimplemented!()
+    unsafe fn set_event(&self, event: &mut n::Event) -> Result<(), d::OutOfMemory> {
+        let mut state = event.0.lock();
+        if let n::EventState::Pending(sync) = *state {
+            self.share.context.delete_sync(sync);
+        }
+        *state = n::EventState::Set;
+        Ok(())
     }
 
-    unsafe fn reset_event(&self, _event: &mut ()) -> Result<(), d::OutOfMemory> {
-        unimplemented!()
+    unsafe fn reset_event(&self, event: &mut n::Event) -> Result<(), d::OutOfMemory> {
+        let mut state = event.0.lock();
+        if let n::EventState::Pending(sync) = *state {
+            self.share.context.delete_sync(sync);
+        }
+        *state = n::EventState::Reset;
+        Ok(())
+    }
+
+    unsafe fn allocate_exportable_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<n::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        _memory: &n::Memory,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<n::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        Ok(0)
     }
 
     unsafe fn free_memory(&self, memory: n::Memory) {
         if let Some((buffer, _)) = memory.buffer {
+            self.remove_object_label(glow::BUFFER, buffer);
             self.share.context.delete_buffer(buffer);
         }
+        self.share.memory_usage[memory.heap_index].fetch_sub(memory.size, Ordering::Relaxed);
     }
 
     unsafe fn create_query_pool(
@@ -1994,6 +2675,7 @@ impl d::Device<B> for Device {
     }
 
     unsafe fn destroy_framebuffer(&self, framebuffer: n::Framebuffer) {
+        self.remove_object_label(glow::FRAMEBUFFER, framebuffer.raw);
         self.share.context.delete_framebuffer(framebuffer.raw);
     }
 
@@ -2001,15 +2683,21 @@ impl d::Device<B> for Device {
         // Nothing to do
     }
 
-    unsafe fn destroy_buffer_view(&self, _: n::BufferView) {
-        // Nothing to do
+    unsafe fn destroy_buffer_view(&self, view: n::BufferView) {
+        self.share.context.delete_texture(view.raw);
     }
 
     unsafe fn destroy_image(&self, image: n::Image) {
         let gl = &self.share.context;
         match image.object_type {
-            n::ImageType::Renderbuffer { raw, .. } => gl.delete_renderbuffer(raw),
-            n::ImageType::Texture { raw, .. } => gl.delete_texture(raw),
+            n::ImageType::Renderbuffer { raw, .. } => {
+                self.remove_object_label(glow::RENDERBUFFER, raw);
+                gl.delete_renderbuffer(raw);
+            }
+            n::ImageType::Texture { raw, .. } => {
+                self.remove_object_label(glow::TEXTURE, raw);
+                gl.delete_texture(raw);
+            }
         }
     }
 
@@ -2033,64 +2721,71 @@ impl d::Device<B> for Device {
         // Nothing to do
     }
 
-    unsafe fn destroy_fence(&self, fence: n::Fence) {
-        match fence {
-            n::Fence::Idle { .. } => {}
-            n::Fence::Pending(sync) => {
-                self.share.context.delete_sync(sync);
-            }
-        }
+    unsafe fn destroy_descriptor_update_template(&self, _: n::DescriptorUpdateTemplate) {
+        // Nothing to do
+    }
+
+    unsafe fn destroy_fence(&self, _: n::Fence) {
+        // Nothing to do, the sync objects are owned by the fence pool
     }
 
     unsafe fn destroy_semaphore(&self, _: n::Semaphore) {
         // Nothing to do
     }
 
-    unsafe fn destroy_event(&self, _event: ()) {
-        unimplemented!()
+    unsafe fn destroy_event(&self, event: n::Event) {
+        if let n::EventState::Pending(sync) = *event.0.lock() {
+            self.share.context.delete_sync(sync);
+        }
     }
 
     fn wait_idle(&self) -> Result<(), d::OutOfMemory> {
         unsafe {
             self.share.context.finish();
+            self.share.fences.lock().complete_all(&self.share.context);
         }
         Ok(())
     }
 
-    unsafe fn set_image_name(&self, _image: &mut n::Image, _name: &str) {
-        // TODO
+    unsafe fn set_image_name(&self, image: &mut n::Image, name: &str) {
+        match image.object_type {
+            n::ImageType::Renderbuffer { raw, .. } => {
+                self.set_object_label(glow::RENDERBUFFER, raw, name)
+            }
+            n::ImageType::Texture { raw, .. } => self.set_object_label(glow::TEXTURE, raw, name),
+        }
     }
 
-    unsafe fn set_buffer_name(&self, _buffer: &mut n::Buffer, _name: &str) {
-        // TODO
+    unsafe fn set_buffer_name(&self, buffer: &mut n::Buffer, name: &str) {
+        match *buffer {
+            n::Buffer::Bound { buffer, .. } => self.set_object_label(glow::BUFFER, buffer, name),
+            // Buffers only get a GL object when bound to memory.
+            n::Buffer::Unbound { .. } => {}
+        }
     }
 
-    unsafe fn set_command_buffer_name(
-        &self,
-        _command_buffer: &mut cmd::CommandBuffer,
-        _name: &str,
-    ) {
-        // TODO
+    unsafe fn set_command_buffer_name(&self, command_buffer: &mut cmd::CommandBuffer, name: &str) {
+        command_buffer.name = Some(name.to_string());
     }
 
     unsafe fn set_semaphore_name(&self, _semaphore: &mut n::Semaphore, _name: &str) {
-        // TODO
+        // Semaphores are no-op on GL
     }
 
     unsafe fn set_fence_name(&self, _fence: &mut n::Fence, _name: &str) {
-        // TODO
+        // Sync objects are re-created on every submission
     }
 
-    unsafe fn set_framebuffer_name(&self, _framebuffer: &mut n::Framebuffer, _name: &str) {
-        // TODO
+    unsafe fn set_framebuffer_name(&self, framebuffer: &mut n::Framebuffer, name: &str) {
+        self.set_object_label(glow::FRAMEBUFFER, framebuffer.raw, name);
     }
 
     unsafe fn set_render_pass_name(&self, _render_pass: &mut n::RenderPass, _name: &str) {
-        // TODO
+        // No GL object to label
     }
 
     unsafe fn set_descriptor_set_name(&self, _descriptor_set: &mut n::DescriptorSet, _name: &str) {
-        // TODO
+        // No GL object to label
     }
 
     unsafe fn set_descriptor_set_layout_name(
@@ -2098,7 +2793,7 @@ impl d::Device<B> for Device {
         _descriptor_set_layout: &mut n::DescriptorSetLayout,
         _name: &str,
     ) {
-        // TODO
+        // No GL object to label
     }
 
     unsafe fn set_pipeline_layout_name(
@@ -2106,6 +2801,6 @@ impl d::Device<B> for Device {
         _pipeline_layout: &mut n::PipelineLayout,
         _name: &str,
     ) {
-        // TODO
+        // No GL object to label
     }
 }
@@ -0,0 +1,460 @@
+//! Human-readable dumps of the recorded command buffers.
+//!
+//! Commands are printed with their `Debug` representation, where resources
+//! show up with their GL names. The names given to these resources with the
+//! `set_*_name` calls are resolved next to them, together with the data they
+//! reference in the command buffer memory (viewports, scissors, uniform values,
+//! debug labels).
+
+use crate::{
+    command::{BufferSlice, Command, CommandBuffer},
+    pool::BufferMemory,
+    FastHashMap,
+};
+
+use parking_lot::Mutex;
+use std::{
+    fmt::{self, Write},
+    mem, ptr,
+};
+
+/// Environment variable enabling the logging of every submitted command buffer.
+pub(crate) const DUMP_ENV_VAR: &str = "GFX_GL_DUMP_COMMANDS";
+
+/// Number read from the command buffer memory, keeping the type it was stored with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Number {
+    Int(i64),
+    Float(f32),
+    Double(f64),
+}
+
+macro_rules! impl_number_from {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(value: $ty) -> Self {
+                    Number::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_number_from!(u8 => Int, i32 => Int, u32 => Int, f32 => Float, f64 => Double);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Number::Int(value) => write!(f, "{}", value),
+            Number::Float(value) => write!(f, "{}", value),
+            Number::Double(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Data referenced by a command, decoded from the command buffer memory.
+enum Payload {
+    Numbers(Vec<Vec<Number>>),
+    Text(String),
+}
+
+struct Entry {
+    name: String,
+    args: String,
+    payload: Vec<(&'static str, Payload)>,
+}
+
+/// Names given to the GL objects with the `set_*_name` calls,
+/// keyed by the object type identifier and the GL name.
+#[derive(Debug, Default)]
+pub(crate) struct ObjectLabels(Mutex<FastHashMap<(u32, u32), String>>);
+
+impl ObjectLabels {
+    pub(crate) fn set(&self, identifier: u32, object: u32, name: &str) {
+        self.0.lock().insert((identifier, object), name.to_string());
+    }
+
+    /// Forget the name of a deleted object, as GL reuses the names.
+    pub(crate) fn remove(&self, identifier: u32, object: u32) {
+        self.0.lock().remove(&(identifier, object));
+    }
+
+    /// Describe the named objects among the given ones.
+    fn resolve(&self, objects: &[(u32, u32)]) -> Option<Payload> {
+        let labels = self.0.lock();
+        let names = objects
+            .iter()
+            .filter_map(|key| {
+                let kind = match key.0 {
+                    glow::BUFFER => "buffer",
+                    glow::TEXTURE => "texture",
+                    glow::RENDERBUFFER => "renderbuffer",
+                    glow::FRAMEBUFFER => "framebuffer",
+                    _ => "object",
+                };
+                labels
+                    .get(key)
+                    .map(|label| format!("{} {} = {:?}", kind, key.1, label))
+            })
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            None
+        } else {
+            Some(Payload::Text(names.join(", ")))
+        }
+    }
+}
+
+/// List the GL objects referenced by a command, that can be named.
+#[cfg(not(target_arch = "wasm32"))]
+fn objects(command: &Command) -> Vec<(u32, u32)> {
+    use crate::native::ImageView;
+
+    let view_object = |view: &ImageView| match *view {
+        ImageView::Renderbuffer { raw, .. } => (glow::RENDERBUFFER, raw),
+        ImageView::Texture { raw, .. } => (glow::TEXTURE, raw),
+    };
+    match *command {
+        Command::DispatchIndirect(buffer, _)
+        | Command::BindIndexBuffer(buffer)
+        | Command::FillBuffer(buffer, _, _)
+        | Command::BindAttribute(_, buffer, _, _)
+        | Command::BindBufferRange(_, _, buffer, _, _)
+        | Command::ReadPixels {
+            dst_buffer: buffer, ..
+        } => vec![(glow::BUFFER, buffer)],
+        Command::CopyBufferToBuffer(src, dst, _) => {
+            vec![(glow::BUFFER, src), (glow::BUFFER, dst)]
+        }
+        Command::CopyBufferToTexture {
+            src_buffer,
+            dst_texture,
+            ..
+        } => vec![(glow::BUFFER, src_buffer), (glow::TEXTURE, dst_texture)],
+        Command::CopyBufferToRenderbuffer(buffer, renderbuffer, _) => {
+            vec![(glow::BUFFER, buffer), (glow::RENDERBUFFER, renderbuffer)]
+        }
+        Command::CopyTextureToBuffer {
+            src_texture,
+            dst_buffer,
+            ..
+        } => vec![(glow::TEXTURE, src_texture), (glow::BUFFER, dst_buffer)],
+        Command::BindTexture(_, texture, _) => vec![(glow::TEXTURE, texture)],
+        Command::BindImage(_, ref binding) => vec![(glow::TEXTURE, binding.texture)],
+        Command::BindFramebuffer {
+            framebuffer,
+            ref colors,
+            ref depth_stencil,
+            ..
+        } => Some((glow::FRAMEBUFFER, framebuffer))
+            .into_iter()
+            .chain(colors.iter().map(view_object))
+            .chain(depth_stencil.iter().map(view_object))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The GL objects are not plain names on the web, and can't be named.
+#[cfg(target_arch = "wasm32")]
+fn objects(_command: &Command) -> Vec<(u32, u32)> {
+    Vec::new()
+}
+
+fn read<T: Copy>(data: &[u8], slice: BufferSlice) -> Vec<T> {
+    let raw = &data[slice.offset as usize..(slice.offset + slice.size) as usize];
+    raw.chunks_exact(mem::size_of::<T>())
+        .map(|chunk| unsafe { ptr::read_unaligned(chunk.as_ptr() as *const T) })
+        .collect()
+}
+
+fn rows<T: Copy + Into<Number>>(data: &[u8], slice: BufferSlice, width: usize) -> Payload {
+    Payload::Numbers(
+        read::<T>(data, slice)
+            .chunks(width)
+            .map(|row| row.iter().map(|&value| value.into()).collect())
+            .collect(),
+    )
+}
+
+fn decode(command: &Command, data: &[u8], labels: &ObjectLabels) -> Entry {
+    let debug = format!("{:?}", command);
+    let split = debug
+        .find(|c: char| !c.is_alphanumeric())
+        .unwrap_or_else(|| debug.len());
+    let name = debug[..split].to_string();
+    let args = debug[split..].trim().to_string();
+
+    let mut payload = match *command {
+        Command::SetViewports {
+            viewport_ptr,
+            depth_range_ptr,
+            ..
+        } => vec![
+            ("viewports", rows::<f32>(data, viewport_ptr, 4)),
+            ("depth_ranges", rows::<f64>(data, depth_range_ptr, 2)),
+        ],
+        Command::SetScissors(_, scissors) => vec![("scissors", rows::<i32>(data, scissors, 4))],
+        Command::BindUniform {
+            ref uniform,
+            buffer,
+        } => {
+            // One row per vector, or per matrix column.
+            let values = match uniform.utype {
+                glow::INT => rows::<i32>(data, buffer, 1),
+                glow::INT_VEC2 => rows::<i32>(data, buffer, 2),
+                glow::INT_VEC3 => rows::<i32>(data, buffer, 3),
+                glow::INT_VEC4 => rows::<i32>(data, buffer, 4),
+                glow::UNSIGNED_INT => rows::<u32>(data, buffer, 1),
+                glow::UNSIGNED_INT_VEC2 => rows::<u32>(data, buffer, 2),
+                glow::UNSIGNED_INT_VEC3 => rows::<u32>(data, buffer, 3),
+                glow::UNSIGNED_INT_VEC4 => rows::<u32>(data, buffer, 4),
+                // Booleans are stored as 32-bit integers.
+                glow::BOOL => rows::<u32>(data, buffer, 1),
+                glow::BOOL_VEC2 => rows::<u32>(data, buffer, 2),
+                glow::BOOL_VEC3 => rows::<u32>(data, buffer, 3),
+                glow::BOOL_VEC4 => rows::<u32>(data, buffer, 4),
+                glow::FLOAT => rows::<f32>(data, buffer, 1),
+                glow::FLOAT_VEC2 | glow::FLOAT_MAT2 => rows::<f32>(data, buffer, 2),
+                glow::FLOAT_VEC3 | glow::FLOAT_MAT3 => rows::<f32>(data, buffer, 3),
+                glow::FLOAT_VEC4 | glow::FLOAT_MAT4 => rows::<f32>(data, buffer, 4),
+                _ => rows::<u8>(data, buffer, 16),
+            };
+            vec![("values", values)]
+        }
+        Command::InsertDebugMarker(label) | Command::PushDebugGroup(label) => {
+            let bytes = &data[label.offset as usize..(label.offset + label.size) as usize];
+            vec![(
+                "label",
+                Payload::Text(String::from_utf8_lossy(bytes).into_owned()),
+            )]
+        }
+        _ => Vec::new(),
+    };
+    if let Some(names) = labels.resolve(&objects(command)) {
+        payload.push(("names", names));
+    }
+
+    Entry {
+        name,
+        args,
+        payload,
+    }
+}
+
+fn decode_all(commands: &[Command], data: &[u8], labels: &ObjectLabels) -> Vec<Entry> {
+    commands
+        .iter()
+        .map(|cmd| decode(cmd, data, labels))
+        .collect()
+}
+
+/// Format the commands as text, one command per line followed by its payload.
+pub(crate) fn to_text(commands: &[Command], data: &[u8], labels: &ObjectLabels) -> String {
+    let mut out = String::new();
+    for (index, entry) in decode_all(commands, data, labels).into_iter().enumerate() {
+        let _ = writeln!(out, "{:4}: {} {}", index, entry.name, entry.args);
+        for (key, payload) in entry.payload {
+            match payload {
+                Payload::Numbers(rows) => {
+                    let _ = write!(out, "      {}:", key);
+                    for row in rows {
+                        let values = row.iter().map(Number::to_string).collect::<Vec<_>>();
+                        let _ = write!(out, " [{}]", values.join(", "));
+                    }
+                    out.push('\n');
+                }
+                Payload::Text(text) => {
+                    let _ = writeln!(out, "      {}: {:?}", key, text);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn json_number(out: &mut String, value: Number) {
+    // JSON has no representation for infinities and NaNs. The `Display` of the
+    // finite floats never uses an exponent, a sign prefix or a bare dot,
+    // so it's valid JSON as is.
+    let is_finite = match value {
+        Number::Int(_) => true,
+        Number::Float(value) => value.is_finite(),
+        Number::Double(value) => value.is_finite(),
+    };
+    if is_finite {
+        let _ = write!(out, "{}", value);
+    } else {
+        out.push_str("null");
+    }
+}
+
+/// Format the commands as a JSON array of objects.
+pub(crate) fn to_json(commands: &[Command], data: &[u8], labels: &ObjectLabels) -> String {
+    let mut out = String::from("[");
+    for (index, entry) in decode_all(commands, data, labels).into_iter().enumerate() {
+        if index != 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "\n  {{\"index\": {}, \"command\": ", index);
+        json_string(&mut out, &entry.name);
+        out.push_str(", \"args\": ");
+        json_string(&mut out, &entry.args);
+        for (key, payload) in entry.payload {
+            let _ = write!(out, ", \"{}\": ", key);
+            match payload {
+                Payload::Numbers(rows) => {
+                    out.push('[');
+                    for (i, row) in rows.iter().enumerate() {
+                        if i != 0 {
+                            out.push_str(", ");
+                        }
+                        out.push('[');
+                        for (j, &value) in row.iter().enumerate() {
+                            if j != 0 {
+                                out.push_str(", ");
+                            }
+                            json_number(&mut out, value);
+                        }
+                        out.push(']');
+                    }
+                    out.push(']');
+                }
+                Payload::Text(text) => json_string(&mut out, &text),
+            }
+        }
+        out.push('}');
+    }
+    out.push_str("\n]\n");
+    out
+}
+
+impl CommandBuffer {
+    fn with_commands<T>(&self, fun: impl FnOnce(&[Command], &[u8], &ObjectLabels) -> T) -> T {
+        let storage = &self.data;
+        let memory = storage
+            .memory
+            .try_lock()
+            .expect("Trying to dump a command buffer, while memory is in-use.");
+        let buffer = match *memory {
+            BufferMemory::Linear(ref buffer) => buffer,
+            BufferMemory::Individual { ref storage, .. } => storage.get(&self.data.id).unwrap(),
+        };
+        let range = storage.buf.offset as usize..(storage.buf.offset + storage.buf.size) as usize;
+        fun(&buffer.commands[range], &buffer.data, &self.labels)
+    }
+
+    /// Pretty-print the recorded commands, together with the data they reference.
+    ///
+    /// Setting the `GFX_GL_DUMP_COMMANDS` environment variable logs this dump
+    /// for every submitted command buffer.
+    pub fn dump(&self) -> String {
+        self.with_commands(to_text)
+    }
+
+    /// Serialize the recorded commands to JSON, e.g. for diffing the command
+    /// streams of two runs.
+    pub fn dump_json(&self) -> String {
+        self.with_commands(to_json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_number, json_string, to_json, to_text, Number, ObjectLabels};
+    use crate::command::{BufferSlice, Command};
+
+    #[test]
+    fn test_json_string_escape() {
+        let mut out = String::new();
+        json_string(&mut out, "a \"b\" \\ c\n\r\t\u{1}é");
+        assert_eq!(out, r#""a \"b\" \\ c\n\r\t\u0001é""#);
+    }
+
+    #[test]
+    fn test_json_number() {
+        let format = |value| {
+            let mut out = String::new();
+            json_number(&mut out, value);
+            out
+        };
+        assert_eq!(format(Number::Int(-3)), "-3");
+        assert_eq!(format(Number::Int(u32::max_value().into())), "4294967295");
+        assert_eq!(format(Number::Float(1.0)), "1");
+        assert_eq!(format(Number::Float(0.1)), "0.1");
+        assert_eq!(format(Number::Float(-0.0)), "-0");
+        assert_eq!(format(Number::Float(1e-7)), "0.0000001");
+        assert_eq!(format(Number::Double(0.5)), "0.5");
+        assert_eq!(format(Number::Float(std::f32::NAN)), "null");
+        assert_eq!(format(Number::Float(std::f32::INFINITY)), "null");
+        assert_eq!(format(Number::Double(std::f64::NEG_INFINITY)), "null");
+    }
+
+    #[test]
+    fn test_dump() {
+        let mut data = Vec::new();
+        for value in &[0.0f32, 0.0, 64.0, 32.5] {
+            data.extend_from_slice(&value.to_ne_bytes());
+        }
+        for value in &[0.0f64, std::f64::INFINITY] {
+            data.extend_from_slice(&value.to_ne_bytes());
+        }
+        data.extend_from_slice(b"frame \"1\"\n");
+        let commands = [
+            Command::SetViewports {
+                first_viewport: 0,
+                viewport_ptr: BufferSlice {
+                    offset: 0,
+                    size: 16,
+                },
+                depth_range_ptr: BufferSlice {
+                    offset: 16,
+                    size: 16,
+                },
+            },
+            Command::PushDebugGroup(BufferSlice {
+                offset: 32,
+                size: 10,
+            }),
+            Command::BindIndexBuffer(3),
+            Command::PopDebugGroup,
+        ];
+        let labels = ObjectLabels::default();
+        labels.set(glow::BUFFER, 3, "indices");
+
+        let text = to_text(&commands, &data, &labels);
+        assert!(text.contains("   0: SetViewports {"));
+        assert!(text.contains("      viewports: [0, 0, 64, 32.5]\n"));
+        assert!(text.contains("      depth_ranges: [0, inf]\n"));
+        assert!(text.contains("      label: \"frame \\\"1\\\"\\n\"\n"));
+        assert!(text.contains("      names: \"buffer 3 = \\\"indices\\\"\"\n"));
+        assert!(text.contains("   3: PopDebugGroup \n"));
+
+        let json = to_json(&commands, &data, &labels);
+        assert!(json.starts_with("[\n  {\"index\": 0, \"command\": \"SetViewports\", "));
+        assert!(json
+            .ends_with("\n  {\"index\": 3, \"command\": \"PopDebugGroup\", \"args\": \"\"}\n]\n"));
+        assert!(json.contains(r#", "viewports": [[0, 0, 64, 32.5]], "depth_ranges": [[0, null]]}"#));
+        assert!(json.contains(r#", "label": "frame \"1\"\n"}"#));
+        assert!(json.contains(r#", "names": "buffer 3 = \"indices\""}"#));
+    }
+}
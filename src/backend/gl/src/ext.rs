@@ -0,0 +1,129 @@
+//! Entry points that are not exposed by `glow` yet.
+//!
+//! They are loaded with the same loader as the main context, and stay
+//! unloaded on the web, where none of them are available.
+
+use crate::GlContext;
+
+use std::os::raw::c_void;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{mem, ptr};
+
+/// `GL_PROGRAM_BINARY_RETRIEVABLE_HINT`, missing from the `glow` constants.
+pub(crate) const PROGRAM_BINARY_RETRIEVABLE_HINT: u32 = 0x8257;
+/// `GL_DEPTH_BOUNDS_TEST_EXT`, from `EXT_depth_bounds_test`.
+pub(crate) const DEPTH_BOUNDS_TEST: u32 = 0x8890;
+/// `GL_SUBGROUP_SIZE_KHR`, from `KHR_shader_subgroup`.
+pub(crate) const SUBGROUP_SIZE: u32 = 0x9532;
+/// `GL_SUBGROUP_SUPPORTED_STAGES_KHR`, from `KHR_shader_subgroup`.
+pub(crate) const SUBGROUP_SUPPORTED_STAGES: u32 = 0x9533;
+/// `GL_SUBGROUP_SUPPORTED_FEATURES_KHR`, from `KHR_shader_subgroup`.
+pub(crate) const SUBGROUP_SUPPORTED_FEATURES: u32 = 0x9534;
+/// `GL_SUBGROUP_QUAD_ALL_STAGES_KHR`, from `KHR_shader_subgroup`.
+pub(crate) const SUBGROUP_QUAD_ALL_STAGES: u32 = 0x9535;
+
+type Buffer = <GlContext as glow::HasContext>::Buffer;
+type Texture = <GlContext as glow::HasContext>::Texture;
+type Program = <GlContext as glow::HasContext>::Program;
+
+macro_rules! ext_fns {
+    ($( fn $name:ident = [$($sym:literal),+] ( $($arg:ident : $ty:ty),* ) $(-> $ret:ty)?; )*) => {
+        /// Table of the extra GL functions.
+        #[derive(Default)]
+        pub(crate) struct ExtFns {
+            $(
+                #[cfg(not(target_arch = "wasm32"))]
+                $name: Option<unsafe extern "system" fn($($ty),*) $(-> $ret)?>,
+            )*
+        }
+
+        impl ExtFns {
+            /// Load the functions, trying the symbol names in order.
+            #[cfg(not(target_arch = "wasm32"))]
+            pub(crate) fn load<F>(mut loader: F) -> Self
+            where
+                F: FnMut(&str) -> *const c_void,
+            {
+                ExtFns {
+                    $(
+                        $name: {
+                            let address = [$($sym),+]
+                                .iter()
+                                .map(|name| loader(name))
+                                .find(|address| !address.is_null())
+                                .unwrap_or(ptr::null());
+                            unsafe { mem::transmute(address) }
+                        },
+                    )*
+                }
+            }
+
+            $(
+                #[cfg(not(target_arch = "wasm32"))]
+                pub(crate) unsafe fn $name(&self, $($arg: $ty),*) $(-> $ret)? {
+                    let fun = self
+                        .$name
+                        .expect(concat!("GL function is not loaded: ", $($sym, " "),+));
+                    fun($($arg),*)
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                pub(crate) unsafe fn $name(&self, $(_: $ty),*) $(-> $ret)? {
+                    unreachable!(concat!("GL function is not available: ", $($sym, " "),+))
+                }
+            )*
+        }
+    };
+}
+
+ext_fns! {
+    fn tex_buffer_range = ["glTexBufferRange", "glTexBufferRangeEXT", "glTexBufferRangeOES"](
+        target: u32,
+        internal_format: u32,
+        buffer: Buffer,
+        offset: isize,
+        size: isize
+    );
+    fn get_internal_format_iv = ["glGetInternalformativ"](
+        target: u32,
+        internal_format: u32,
+        pname: u32,
+        count: i32,
+        params: *mut i32
+    );
+    fn bind_image_texture = ["glBindImageTexture"](
+        unit: u32,
+        texture: Texture,
+        level: i32,
+        layered: u8,
+        layer: i32,
+        access: u32,
+        format: u32
+    );
+    fn clear_buffer_sub_data = ["glClearBufferSubData"](
+        target: u32,
+        internal_format: u32,
+        offset: isize,
+        size: isize,
+        format: u32,
+        ty: u32,
+        data: *const c_void
+    );
+    fn get_program_iv = ["glGetProgramiv"](program: Program, pname: u32, params: *mut i32);
+    fn program_parameter_i32 = ["glProgramParameteri"](program: Program, pname: u32, value: i32);
+    fn get_program_binary = ["glGetProgramBinary", "glGetProgramBinaryOES"](
+        program: Program,
+        buffer_size: i32,
+        length: *mut i32,
+        binary_format: *mut u32,
+        binary: *mut c_void
+    );
+    fn program_binary = ["glProgramBinary", "glProgramBinaryOES"](
+        program: Program,
+        binary_format: u32,
+        binary: *const c_void,
+        length: i32
+    );
+    fn get_float_v = ["glGetFloatv"](pname: u32, params: *mut f32);
+    fn depth_bounds = ["glDepthBoundsEXT"](z_min: f64, z_max: f64);
+}
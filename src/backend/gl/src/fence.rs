@@ -0,0 +1,186 @@
+//! Tracking of the GPU progress.
+//!
+//! Every queue submission is assigned an increasing index and, when sync
+//! objects are supported, followed by a `glFenceSync`. Since a GL context
+//! executes the submissions in order, the indices form a timeline: once a
+//! submission is known to be complete, all the previous ones are complete too.
+//! Fences only store the index of the submission they wait for, and the sync
+//! objects are owned by the `FencePool`, which releases them as soon as the
+//! submissions complete.
+
+use crate::GlContext;
+
+use glow::HasContext;
+
+use std::collections::VecDeque;
+
+type GlSync = <GlContext as HasContext>::Fence;
+
+/// `glClientWaitSync` takes the timeout as a signed 32-bit integer in `glow`.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_CLIENT_WAIT_NS: u64 = i32::MAX as u64;
+/// WebGL doesn't allow blocking waits (`MAX_CLIENT_WAIT_TIMEOUT_WEBGL` is 0).
+#[cfg(target_arch = "wasm32")]
+const MAX_CLIENT_WAIT_NS: u64 = 0;
+
+#[derive(Debug, Default)]
+pub(crate) struct FencePool {
+    /// Index of the last submission.
+    last_submitted: u64,
+    /// Index of the last submission known to be complete.
+    last_completed: u64,
+    /// Sync objects of the submissions in flight, in submission order.
+    active: VecDeque<(u64, GlSync)>,
+}
+
+// The pool is only reachable through `Share`, which lives in a `Starc`:
+// dereferencing it asserts that the caller is on the thread that created
+// the context, so the sync objects are never used from another thread,
+// as required by the GL threading contract of the `Share`.
+unsafe impl Send for FencePool {}
+
+impl FencePool {
+    /// Register a new submission, returning its index.
+    ///
+    /// Without sync objects there is no way to observe the GPU progress,
+    /// so the submission is considered complete right away.
+    pub(crate) unsafe fn signal(&mut self, gl: &GlContext, sync_supported: bool) -> u64 {
+        self.maintain(gl);
+        self.last_submitted += 1;
+        if sync_supported {
+            let sync = gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0).unwrap();
+            self.active.push_back((self.last_submitted, sync));
+        } else {
+            self.last_completed = self.last_submitted;
+        }
+        self.last_submitted
+    }
+
+    /// Release the sync objects of the completed submissions.
+    pub(crate) unsafe fn maintain(&mut self, gl: &GlContext) {
+        while let Some(&(index, sync)) = self.active.front() {
+            if gl.get_sync_status(sync) != glow::SIGNALED {
+                break;
+            }
+            self.complete(gl, index);
+        }
+    }
+
+    /// Check if the submission with the given index is complete.
+    pub(crate) unsafe fn is_complete(&mut self, gl: &GlContext, index: u64) -> bool {
+        if index > self.last_completed {
+            self.maintain(gl);
+        }
+        index <= self.last_completed
+    }
+
+    /// Block until the submission with the given index is complete,
+    /// or the timeout expires. Returns true if the submission is complete.
+    ///
+    /// On WebGL this never blocks and only polls the current status.
+    pub(crate) unsafe fn wait(&mut self, gl: &GlContext, index: u64, timeout_ns: u64) -> bool {
+        if self.is_complete(gl, index) {
+            return true;
+        }
+        let (target, sync) = match self.active.iter().find(|&&(i, _)| i >= index) {
+            Some(&pair) => pair,
+            None => {
+                error!("Waiting on submission {} that was never made", index);
+                return false;
+            }
+        };
+
+        let mut remaining = timeout_ns;
+        loop {
+            let chunk = remaining.min(MAX_CLIENT_WAIT_NS);
+            match gl.client_wait_sync(sync, glow::SYNC_FLUSH_COMMANDS_BIT, chunk as i32) {
+                glow::CONDITION_SATISFIED | glow::ALREADY_SIGNALED => {
+                    self.complete(gl, target);
+                    return true;
+                }
+                glow::TIMEOUT_EXPIRED if chunk != 0 && remaining > chunk => {
+                    remaining -= chunk;
+                }
+                glow::TIMEOUT_EXPIRED => return false,
+                _ => {
+                    error!("Error when waiting on submission {}", index);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Mark every submission as complete, after the context has been finished.
+    pub(crate) unsafe fn complete_all(&mut self, gl: &GlContext) {
+        self.complete(gl, self.last_submitted);
+    }
+
+    fn complete(&mut self, gl: &GlContext, index: u64) {
+        while let Some(&(i, sync)) = self.active.front() {
+            if i > index {
+                break;
+            }
+            unsafe { gl.delete_sync(sync) };
+            self.active.pop_front();
+        }
+        self.last_completed = self.last_completed.max(index);
+    }
+}
+
+/// Counter of a timeline semaphore.
+///
+/// The values signaled by the queue are reached once their submissions complete.
+#[derive(Debug, Default)]
+pub(crate) struct Timeline {
+    /// Value known to be reached.
+    value: u64,
+    /// Values to be reached, with the index of the submission signaling them.
+    pending: Vec<(u64, u64)>,
+}
+
+impl Timeline {
+    pub(crate) fn new(value: u64) -> Self {
+        Timeline {
+            value,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Set the value from the host.
+    pub(crate) fn signal(&mut self, value: u64) {
+        self.value = self.value.max(value);
+    }
+
+    /// Set the value once the submission with the given index completes.
+    pub(crate) fn push(&mut self, value: u64, index: u64) {
+        self.pending.push((value, index));
+    }
+
+    /// Check if the value is reached, or will be reached by a submission.
+    pub(crate) fn will_reach(&self, value: u64) -> bool {
+        value <= self.value || self.pending.iter().any(|&(v, _)| v >= value)
+    }
+
+    /// Get the current value, accounting for the completed submissions.
+    pub(crate) unsafe fn update(&mut self, pool: &mut FencePool, gl: &GlContext) -> u64 {
+        let mut value = self.value;
+        self.pending.retain(|&(v, index)| {
+            let complete = pool.is_complete(gl, index);
+            if complete {
+                value = value.max(v);
+            }
+            !complete
+        });
+        self.value = value;
+        value
+    }
+
+    /// Get the index of the earliest submission reaching the value.
+    pub(crate) fn submission_for(&self, value: u64) -> Option<u64> {
+        self.pending
+            .iter()
+            .filter(|&&(v, _)| v >= value)
+            .map(|&(_, index)| index)
+            .min()
+    }
+}
@@ -1,6 +1,10 @@
-use crate::{Error, GlContainer, MAX_COLOR_ATTACHMENTS};
+use crate::{conv, ext, Error, GlContainer, MAX_COLOR_ATTACHMENTS};
 use glow::HasContext;
-use hal::{DynamicStates, Features, Limits, PerformanceCaveats, PhysicalDeviceProperties};
+use hal::{
+    format::{BufferFeature, ChannelType, Format, ImageFeature, Properties},
+    pso, DynamicStates, Features, Limits, PerformanceCaveats, PhysicalDeviceProperties,
+    SubgroupOperations, SubgroupProperties,
+};
 use std::{collections::HashSet, fmt, str};
 
 /// A version number for a specific component of an OpenGL implementation
@@ -210,6 +214,22 @@ pub struct PrivateCaps {
     pub get_tex_image: bool,
     /// Inserting memory barriers.
     pub memory_barrier: bool,
+    /// Texel buffers with `GL_TEXTURE_BUFFER` and `glTexBufferRange`.
+    pub texture_buffer: bool,
+    /// Binding textures as images for shader load/store.
+    pub image_load_store: bool,
+    /// Querying the per-format limits with `glGetInternalformativ`.
+    pub internal_format_query: bool,
+    /// Discarding framebuffer contents with `glInvalidateFramebuffer`.
+    pub invalidate_framebuffer: bool,
+    /// Object labels and debug groups from `KHR_debug`.
+    pub debug_labels: bool,
+    /// Filling buffers with `glClearBufferSubData`.
+    pub clear_buffer_data: bool,
+    /// Retrieving and loading linked programs with `glGetProgramBinary`/`glProgramBinary`.
+    pub program_binary: bool,
+    /// Depth bounds test from `EXT_depth_bounds_test`.
+    pub depth_bounds: bool,
 }
 
 /// OpenGL implementation information
@@ -387,6 +407,8 @@ pub(crate) fn query_all(
             .unwrap_or(1024),
         min_storage_buffer_offset_alignment,
         framebuffer_color_sample_counts: max_samples_mask,
+        framebuffer_depth_sample_counts: max_samples_mask,
+        framebuffer_stencil_sample_counts: max_samples_mask,
         non_coherent_atom_size: 1,
         max_color_attachments: get_usize(gl, glow::MAX_COLOR_ATTACHMENTS)
             .unwrap_or(1)
@@ -421,9 +443,30 @@ pub(crate) fn query_all(
         }
     }
 
-    let mut features = Features::NDC_Y_UP | Features::MUTABLE_COMPARISON_SAMPLER;
+    // Push descriptors are emulated by binding the descriptors directly.
+    let mut features =
+        Features::NDC_Y_UP | Features::MUTABLE_COMPARISON_SAMPLER | Features::PUSH_DESCRIPTOR;
     let mut legacy = LegacyFeatures::empty();
 
+    // Wide lines are optional, and removed from the forward-compatible contexts.
+    let forward_compatible = !info.version.is_embedded
+        && info.is_supported(&[Core(3, 0)])
+        && get_usize(gl, glow::CONTEXT_FLAGS).unwrap_or(0) as u32
+            & glow::CONTEXT_FLAG_FORWARD_COMPATIBLE_BIT
+            != 0;
+    if !IS_WEBGL && !forward_compatible {
+        let mut line_width_range = [1.0f32; 2];
+        unsafe {
+            gl.ext.get_float_v(
+                glow::ALIASED_LINE_WIDTH_RANGE,
+                line_width_range.as_mut_ptr(),
+            )
+        };
+        if line_width_range[1] > 1.0 {
+            features |= Features::LINE_WIDTH;
+        }
+    }
+
     if info.is_supported(&[
         Core(4, 6),
         Ext("GL_ARB_texture_filter_anisotropic"),
@@ -444,6 +487,10 @@ pub(crate) fn query_all(
     if info.is_supported(&[Core(2, 1)]) {
         features |= Features::SAMPLER_BORDER_COLOR;
     }
+    if info.is_supported(&[Core(3, 0), Es(3, 0)]) {
+        // arrays of samplers, indexed with constant expressions
+        features |= Features::TEXTURE_DESCRIPTOR_ARRAY;
+    }
     if info.is_supported(&[Core(4, 4), Ext("ARB_texture_mirror_clamp_to_edge")]) {
         features |= Features::SAMPLER_MIRROR_CLAMP_EDGE;
     }
@@ -511,8 +558,28 @@ pub(crate) fn query_all(
     if !info.is_supported(&[Core(4, 2)]) {
         performance_caveats |= PerformanceCaveats::BASE_VERTEX_INSTANCE_DRAWING;
     }
+    let mut subgroup = SubgroupProperties::default();
+    if !IS_WEBGL && info.is_supported(&[Ext("GL_KHR_shader_subgroup")]) {
+        let size = get_usize(gl, ext::SUBGROUP_SIZE).unwrap_or(0) as u32;
+        let stages = get_usize(gl, ext::SUBGROUP_SUPPORTED_STAGES).unwrap_or(0) as u32;
+        let operations = get_usize(gl, ext::SUBGROUP_SUPPORTED_FEATURES).unwrap_or(0) as u32;
+        subgroup = SubgroupProperties {
+            size,
+            min_size: size,
+            max_size: size,
+            supported_stages: conv::map_gl_stage_bits(stages),
+            // The feature bits of the extension match the Vulkan ones.
+            supported_operations: SubgroupOperations::from_bits_truncate(operations),
+            quad_operations_in_all_stages: get_usize(gl, ext::SUBGROUP_QUAD_ALL_STAGES)
+                .unwrap_or(0)
+                != 0,
+            required_size_stages: pso::ShaderStageFlags::empty(),
+        };
+    }
+
     let properties = PhysicalDeviceProperties {
         limits,
+        subgroup,
         performance_caveats,
         dynamic_pipeline_states: DynamicStates::all(),
         ..PhysicalDeviceProperties::default()
@@ -545,11 +612,167 @@ pub(crate) fn query_all(
         per_slot_color_mask: info.is_supported(&[Core(3, 0)]),
         get_tex_image: !info.version.is_embedded,
         memory_barrier: info.is_supported(&[Core(4, 2), Es(3, 1)]),
+        texture_buffer: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 2),
+            Ext("GL_ARB_texture_buffer_range"),
+            Ext("GL_EXT_texture_buffer"),
+            Ext("GL_OES_texture_buffer"),
+        ]),
+        image_load_store: info.is_supported(&[
+            Core(4, 2),
+            Es(3, 1),
+            Ext("GL_ARB_shader_image_load_store"),
+        ]),
+        internal_format_query: !info.is_webgl()
+            && info.is_supported(&[Core(4, 2), Es(3, 0), Ext("GL_ARB_internalformat_query")]),
+        invalidate_framebuffer: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 0),
+            Ext("GL_ARB_invalidate_subdata"),
+        ]),
+        debug_labels: !info.is_webgl()
+            && info.is_supported(&[Core(4, 3), Es(3, 2), Ext("GL_KHR_debug")]),
+        clear_buffer_data: !info.is_webgl()
+            && info.is_supported(&[Core(4, 3), Ext("GL_ARB_clear_buffer_object")]),
+        program_binary: !info.is_webgl()
+            && info.is_supported(&[
+                Core(4, 1),
+                Es(3, 0),
+                Ext("GL_ARB_get_program_binary"),
+                Ext("GL_OES_get_program_binary"),
+            ])
+            && get_usize(gl, glow::NUM_PROGRAM_BINARY_FORMATS).unwrap_or(0) != 0,
+        depth_bounds: !info.is_webgl() && info.is_supported(&[Ext("GL_EXT_depth_bounds_test")]),
     };
 
+    if private.depth_bounds {
+        features |= Features::DEPTH_BOUNDS;
+    }
+    // Timeline semaphores are emulated on top of the submission fences.
+    if private.sync {
+        features |= Features::TIMELINE_SEMAPHORE;
+    }
+    // Dynamic rendering uses a framebuffer object of the command pool.
+    if private.framebuffer {
+        features |= Features::DYNAMIC_RENDERING;
+    }
+
     (info, features, legacy, properties, private)
 }
 
+/// Derive the features of a format from the version and the extensions
+/// of the implementation.
+pub(crate) fn query_format_properties(
+    info: &Info,
+    private: &PrivateCaps,
+    format: Format,
+) -> Properties {
+    use self::Requirement::*;
+
+    let desc = match conv::describe_format(format) {
+        Some(desc) => desc,
+        None => return Properties::default(),
+    };
+    let channel = format.base_format().1;
+    let is_integer = match channel {
+        ChannelType::Uint | ChannelType::Sint => true,
+        _ => false,
+    };
+    let is_32bit_float = match desc.data_type {
+        glow::FLOAT => format.is_color(),
+        _ => false,
+    };
+    let is_half_float = desc.data_type == glow::HALF_FLOAT;
+    let is_norm16 = desc.data_type == glow::UNSIGNED_SHORT && !is_integer;
+
+    let mut image = ImageFeature::TRANSFER_SRC | ImageFeature::TRANSFER_DST;
+    let mut buffer = BufferFeature::empty();
+
+    // sampling
+    image |= ImageFeature::SAMPLED | ImageFeature::BLIT_SRC;
+    let filterable = if is_integer || format.is_stencil() && !format.is_depth() {
+        false
+    } else if is_32bit_float {
+        info.is_supported(&[Core(3, 0), Ext("GL_OES_texture_float_linear")])
+    } else {
+        true
+    };
+    if filterable {
+        image |= ImageFeature::SAMPLED_LINEAR;
+    }
+
+    // rendering
+    if format.is_depth() || format.is_stencil() {
+        image |= ImageFeature::DEPTH_STENCIL_ATTACHMENT | ImageFeature::BLIT_DST;
+    } else {
+        let renderable = if desc.num_components == 3 {
+            // RGB formats are only guaranteed renderable on desktop,
+            // and never for the 32-bit channels.
+            !info.version.is_embedded && !is_32bit_float && !is_integer
+        } else if is_32bit_float {
+            info.is_supported(&[Core(3, 0), Ext("GL_EXT_color_buffer_float")])
+        } else if is_half_float {
+            info.is_supported(&[
+                Core(3, 0),
+                Ext("GL_EXT_color_buffer_float"),
+                Ext("GL_EXT_color_buffer_half_float"),
+            ])
+        } else if is_norm16 {
+            info.is_supported(&[Core(3, 0), Ext("GL_EXT_texture_norm16")])
+        } else {
+            true
+        };
+        if renderable {
+            image |= ImageFeature::COLOR_ATTACHMENT | ImageFeature::BLIT_DST;
+            let blendable = if is_integer {
+                false
+            } else if is_32bit_float {
+                info.is_supported(&[Core(3, 0), Ext("GL_EXT_float_blend")])
+            } else {
+                true
+            };
+            if blendable {
+                image |= ImageFeature::COLOR_ATTACHMENT_BLEND;
+            }
+        }
+    }
+
+    // shader load/store
+    if private.image_load_store && conv::is_image_format(format) {
+        image |= ImageFeature::STORAGE;
+        match format {
+            Format::R32Uint | Format::R32Sint => {
+                image |= ImageFeature::STORAGE_ATOMIC;
+            }
+            _ => {}
+        }
+    }
+
+    // buffers
+    if format.is_color() && channel != ChannelType::Srgb && desc.tex_external != glow::BGRA {
+        buffer |= BufferFeature::VERTEX;
+    }
+    if private.texture_buffer && conv::is_texel_buffer_format(&desc) {
+        buffer |= BufferFeature::UNIFORM_TEXEL;
+        if image.contains(ImageFeature::STORAGE) {
+            buffer |= BufferFeature::STORAGE_TEXEL;
+        }
+    }
+
+    Properties {
+        // Linear images are backed by the same textures, but we don't
+        // want them to be rendered to.
+        linear_tiling: image
+            & (ImageFeature::TRANSFER_SRC
+                | ImageFeature::TRANSFER_DST
+                | ImageFeature::SAMPLED
+                | ImageFeature::SAMPLED_LINEAR),
+        optimal_tiling: image,
+        buffer_features: buffer,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Version;
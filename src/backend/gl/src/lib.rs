@@ -31,18 +31,26 @@ use std::{
     fmt,
     hash::BuildHasherDefault,
     ops::{Deref, Range},
-    sync::{Arc, Weak},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
     thread,
 };
 
 use hal::{adapter, buffer, image, memory, queue as q};
+use parking_lot::Mutex;
 
 pub use self::device::Device;
 pub use self::info::{Info, PlatformName, Version};
+pub use self::queue::EliminatedCalls;
 
 mod command;
 mod conv;
 mod device;
+mod dump;
+mod ext;
+mod fence;
 mod info;
 mod native;
 mod pool;
@@ -72,6 +80,8 @@ const MAX_COLOR_ATTACHMENTS: usize = 16;
 
 struct GlContainer {
     context: GlContext,
+    /// Functions missing in `glow`.
+    ext: ext::ExtFns,
 }
 
 impl Deref for GlContainer {
@@ -111,14 +121,15 @@ impl hal::Backend for Backend {
     type ComputePipeline = native::ComputePipeline;
     type GraphicsPipeline = native::GraphicsPipeline;
     type PipelineLayout = native::PipelineLayout;
-    type PipelineCache = ();
+    type PipelineCache = native::PipelineCache;
     type DescriptorSetLayout = native::DescriptorSetLayout;
     type DescriptorPool = native::DescriptorPool;
     type DescriptorSet = native::DescriptorSet;
+    type DescriptorUpdateTemplate = native::DescriptorUpdateTemplate;
 
     type Fence = native::Fence;
     type Semaphore = native::Semaphore;
-    type Event = ();
+    type Event = native::Event;
     type QueryPool = ();
 }
 
@@ -211,6 +222,12 @@ struct Share {
     // Indicates if there is an active logical device.
     open: Cell<bool>,
     memory_types: Vec<(adapter::MemoryType, MemoryUsage)>,
+    /// Progress of the queue submissions.
+    fences: Mutex<fence::FencePool>,
+    /// Size of the memory allocations, per heap.
+    memory_usage: [AtomicU64; 2],
+    /// Names of the objects, resolved in the command buffer dumps.
+    labels: Arc<dump::ObjectLabels>,
 }
 
 impl Share {
@@ -247,6 +264,23 @@ impl Share {
         type_mask
     }
 
+    /// Identification of the driver, stored at the start of the pipeline cache data,
+    /// since program binaries can't be shared between drivers.
+    fn pipeline_cache_header(&self) -> Vec<u8> {
+        let mut header = b"GFXGLPC2".to_vec();
+        let strings = [
+            &env!("CARGO_PKG_VERSION").to_string(),
+            &self.info.platform_name.vendor,
+            &self.info.platform_name.renderer,
+            &format!("{:?}", self.info.version),
+        ];
+        for string in strings.iter() {
+            header.extend_from_slice(&(string.len() as u32).to_le_bytes());
+            header.extend_from_slice(string.as_bytes());
+        }
+        header
+    }
+
     fn image_memory_type_mask(&self) -> u32 {
         let mut type_mask = 0;
         for (type_index, &(_, kind)) in self.memory_types.iter().enumerate() {
@@ -353,8 +387,8 @@ unsafe impl<T: ?Sized> Sync for Wstarc<T> {}
 pub struct PhysicalDevice(Starc<Share>);
 
 impl PhysicalDevice {
-    fn new_adapter(context: GlContext) -> adapter::Adapter<Backend> {
-        let gl = GlContainer { context };
+    fn new_adapter(context: GlContext, ext: ext::ExtFns) -> adapter::Adapter<Backend> {
+        let gl = GlContainer { context, ext };
         // query information
         let (info, supported_features, legacy_features, public_caps, private_caps) =
             info::query_all(&gl);
@@ -438,6 +472,9 @@ impl PhysicalDevice {
             private_caps,
             open: Cell::new(false),
             memory_types,
+            fences: Mutex::new(fence::FencePool::default()),
+            memory_usage: Default::default(),
+            labels: Arc::default(),
         };
         if let Err(err) = share.check() {
             panic!("Error querying info: {:?}", err);
@@ -586,39 +623,131 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         })
     }
 
-    fn format_properties(&self, _: Option<hal::format::Format>) -> hal::format::Properties {
-        use hal::format::{BufferFeature as Bf, ImageFeature as If};
-
-        // TODO: These are for show
-        hal::format::Properties {
-            linear_tiling: If::TRANSFER_SRC | If::TRANSFER_DST | If::empty(),
-            optimal_tiling: If::TRANSFER_SRC | If::TRANSFER_DST | If::SAMPLED,
-            buffer_features: Bf::VERTEX,
+    fn format_properties(&self, format: Option<hal::format::Format>) -> hal::format::Properties {
+        match format {
+            Some(format) => {
+                info::query_format_properties(&self.0.info, &self.0.private_caps, format)
+            }
+            None => hal::format::Properties::default(),
         }
     }
 
     fn image_format_properties(
         &self,
         format: hal::format::Format,
-        _dimensions: u8,
-        _tiling: image::Tiling,
-        _usage: image::Usage,
-        _view_caps: image::ViewCapabilities,
+        dimensions: u8,
+        tiling: image::Tiling,
+        usage: image::Usage,
+        view_caps: image::ViewCapabilities,
     ) -> Option<image::FormatProperties> {
-        conv::describe_format(format)?;
-        Some(image::FormatProperties {
-            max_extent: image::Extent {
-                width: !0,
-                height: !0,
-                depth: !0,
+        use hal::format::ImageFeature as If;
+
+        let desc = conv::describe_format(format)?;
+        let properties = self.format_properties(Some(format));
+        let features = match tiling {
+            image::Tiling::Optimal => properties.optimal_tiling,
+            image::Tiling::Linear => properties.linear_tiling,
+        };
+        let required = [
+            (image::Usage::TRANSFER_SRC, If::TRANSFER_SRC),
+            (image::Usage::TRANSFER_DST, If::TRANSFER_DST),
+            (image::Usage::SAMPLED, If::SAMPLED),
+            (image::Usage::STORAGE, If::STORAGE),
+            (image::Usage::COLOR_ATTACHMENT, If::COLOR_ATTACHMENT),
+            (
+                image::Usage::DEPTH_STENCIL_ATTACHMENT,
+                If::DEPTH_STENCIL_ATTACHMENT,
+            ),
+        ];
+        if required
+            .iter()
+            .any(|&(u, f)| usage.contains(u) && !features.contains(f))
+        {
+            return None;
+        }
+
+        let limits = &self.0.public_caps.limits;
+        let max_extent = match dimensions {
+            1 => image::Extent {
+                width: limits.max_image_1d_size,
+                height: 1,
+                depth: 1,
+            },
+            2 if view_caps.contains(image::ViewCapabilities::KIND_CUBE) => image::Extent {
+                width: limits.max_image_cube_size,
+                height: limits.max_image_cube_size,
+                depth: 1,
+            },
+            2 => image::Extent {
+                width: limits.max_image_2d_size,
+                height: limits.max_image_2d_size,
+                depth: 1,
             },
-            max_levels: !0,
-            max_layers: !0,
-            sample_count_mask: 127,
+            3 => image::Extent {
+                width: limits.max_image_3d_size,
+                height: limits.max_image_3d_size,
+                depth: limits.max_image_3d_size,
+            },
+            _ => return None,
+        };
+        let max_dimension = max_extent
+            .width
+            .max(max_extent.height)
+            .max(max_extent.depth);
+        let max_layers = if dimensions == 3 {
+            1
+        } else {
+            limits.max_image_array_layers
+        };
+
+        let is_attachment =
+            features.intersects(If::COLOR_ATTACHMENT | If::DEPTH_STENCIL_ATTACHMENT);
+        let sample_count_mask =
+            if dimensions != 2 || tiling != image::Tiling::Optimal || !is_attachment {
+                1
+            } else if self.0.private_caps.internal_format_query {
+                let gl = &self.0.context;
+                let mut num_sample_counts = 0;
+                let mut sample_counts = Vec::new();
+                unsafe {
+                    gl.ext.get_internal_format_iv(
+                        glow::RENDERBUFFER,
+                        desc.tex_internal,
+                        glow::NUM_SAMPLE_COUNTS,
+                        1,
+                        &mut num_sample_counts,
+                    );
+                    sample_counts.resize(num_sample_counts as usize, 0);
+                    gl.ext.get_internal_format_iv(
+                        glow::RENDERBUFFER,
+                        desc.tex_internal,
+                        glow::SAMPLES,
+                        num_sample_counts,
+                        sample_counts.as_mut_ptr(),
+                    );
+                }
+                sample_counts
+                    .into_iter()
+                    .fold(1, |mask, count| mask | count as image::NumSamples)
+            } else if format.is_color() {
+                limits.framebuffer_color_sample_counts
+            } else {
+                limits.framebuffer_depth_sample_counts
+            };
+
+        Some(image::FormatProperties {
+            max_extent,
+            max_levels: 32 - max_dimension.leading_zeros() as image::Level,
+            max_layers,
+            sample_count_mask,
             max_resource_size: !0,
         })
     }
 
+    fn is_valid_cache(&self, cache: &[u8]) -> bool {
+        cache.starts_with(&self.0.pipeline_cache_header())
+    }
+
     fn memory_properties(&self) -> adapter::MemoryProperties {
         adapter::MemoryProperties {
             memory_types: self
@@ -640,6 +769,21 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         }
     }
 
+    fn memory_budget(&self) -> Option<Vec<adapter::MemoryHeapBudget>> {
+        // GL doesn't expose the available memory, so the budget is the whole heap.
+        let heaps = self.memory_properties().memory_heaps;
+        Some(
+            heaps
+                .iter()
+                .zip(self.0.memory_usage.iter())
+                .map(|(heap, usage)| adapter::MemoryHeapBudget {
+                    budget: heap.size,
+                    usage: usage.load(Ordering::Relaxed),
+                })
+                .collect(),
+        )
+    }
+
     fn features(&self) -> hal::Features {
         self.0.supported_features
     }
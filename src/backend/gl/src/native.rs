@@ -1,4 +1,4 @@
-use crate::{Backend, GlContext, MAX_TEXTURE_SLOTS};
+use crate::{fence, Backend, FastHashMap, GlContext, MAX_TEXTURE_SLOTS};
 
 use hal::{
     buffer, format, image as i,
@@ -6,6 +6,7 @@ use hal::{
     pass, pso, window as w,
 };
 
+use parking_lot::Mutex;
 use std::{borrow::Borrow, fmt, ops::Range, sync::Arc};
 
 pub type TextureTarget = u32;
@@ -24,7 +25,10 @@ pub type Sampler = <GlContext as glow::HasContext>::Sampler;
 // TODO: UniformLocation was copy in glow 0.3, but in 0.4 it isn't. Wrap it in a Starc for now
 // to make it `Sync + Send` instead.
 pub type UniformLocation = crate::Starc<<GlContext as glow::HasContext>::UniformLocation>;
-pub type DescriptorSetLayout = Arc<Vec<pso::DescriptorSetLayoutBinding>>;
+pub type DescriptorSetLayout = Arc<SetLayout>;
+/// The descriptors are written directly into the set storage, so the
+/// templates only keep the entries.
+pub type DescriptorUpdateTemplate = Vec<pso::DescriptorUpdateTemplateEntry>;
 
 #[derive(Clone, Debug)]
 pub struct Framebuffer {
@@ -40,6 +44,7 @@ pub enum Buffer {
     Bound {
         buffer: RawBuffer,
         range: Range<buffer::Offset>,
+        usage: buffer::Usage,
     },
 }
 
@@ -48,28 +53,52 @@ impl Buffer {
     pub(crate) fn as_bound(&self) -> (RawBuffer, Range<u64>) {
         match *self {
             Buffer::Unbound { .. } => panic!("Expected bound buffer!"),
-            Buffer::Bound { buffer, ref range } => (buffer, range.clone()),
+            Buffer::Bound {
+                buffer, ref range, ..
+            } => (buffer, range.clone()),
         }
     }
 }
 
 #[derive(Debug)]
-pub struct BufferView;
+pub struct BufferView {
+    /// Buffer texture covering the viewed range.
+    pub(crate) raw: Texture,
+    pub(crate) format: TextureFormat,
+}
 
 #[derive(Debug)]
 pub enum Fence {
-    Idle { signaled: bool },
+    Idle {
+        signaled: bool,
+    },
+    /// Signaled once the queue submission with this index completes.
+    Pending(u64),
+}
+
+#[derive(Debug)]
+pub(crate) enum EventState {
+    Reset,
+    Set,
+    /// Set by a command buffer, signaled once the preceding GPU work is done.
     Pending(<GlContext as glow::HasContext>::Fence),
 }
 
-unsafe impl Send for Fence {}
-unsafe impl Sync for Fence {}
+/// Events are shared between the host and the command stream,
+/// so that the queue can update the state when processing commands.
+#[derive(Debug)]
+pub struct Event(pub(crate) Arc<Mutex<EventState>>);
+
+unsafe impl Send for Event {}
+unsafe impl Sync for Event {}
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
 pub enum BindingRegister {
-    Textures,
-    UniformBuffers,
-    StorageBuffers,
+    Textures = 0,
+    Images = 1,
+    UniformBuffers = 2,
+    StorageBuffers = 3,
 }
 
 /// For each texture in the pipeline layout, store the index of the only
@@ -87,6 +116,8 @@ pub struct GraphicsPipeline {
     pub(crate) uniforms: Vec<UniformDesc>,
     pub(crate) rasterizer: pso::Rasterizer,
     pub(crate) depth: Option<pso::DepthTest>,
+    pub(crate) depth_bounds: bool,
+    pub(crate) stencil: Option<pso::StencilTest>,
     pub(crate) baked_states: pso::BakedStates,
     pub(crate) sampler_map: SamplerBindMap,
 }
@@ -130,6 +161,7 @@ pub enum ImageType {
     Renderbuffer {
         raw: Renderbuffer,
         format: TextureFormat,
+        pixel_type: DataType,
     },
     Texture {
         target: TextureTarget,
@@ -154,12 +186,16 @@ pub enum ImageView {
     Renderbuffer {
         raw: Renderbuffer,
         aspects: format::Aspects,
+        samples: i::NumSamples,
     },
     Texture {
         target: TextureTarget,
         raw: Texture,
+        /// Internal format of the view, used for image load/store.
+        format: TextureFormat,
         is_3d: bool,
         sub: i::SubresourceRange,
+        samples: i::NumSamples,
     },
 }
 
@@ -170,6 +206,24 @@ impl ImageView {
             ImageView::Texture { ref sub, .. } => sub.aspects,
         }
     }
+
+    pub(crate) fn samples(&self) -> i::NumSamples {
+        match *self {
+            ImageView::Renderbuffer { samples, .. } | ImageView::Texture { samples, .. } => samples,
+        }
+    }
+
+    /// Framebuffer attachment point of a depth-stencil view.
+    pub(crate) fn depth_stencil_point(&self) -> u32 {
+        let aspects = self.aspects();
+        if aspects == format::Aspects::DEPTH {
+            glow::DEPTH_ATTACHMENT
+        } else if aspects == format::Aspects::STENCIL {
+            glow::STENCIL_ATTACHMENT
+        } else {
+            glow::DEPTH_STENCIL_ATTACHMENT
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -195,6 +249,7 @@ impl SwapchainImage {
     pub(crate) fn new(
         renderbuffer: Renderbuffer,
         format: TextureFormat,
+        pixel_type: DataType,
         extent: w::Extent2D,
         channel: format::ChannelType,
     ) -> Self {
@@ -203,6 +258,7 @@ impl SwapchainImage {
                 object_type: ImageType::Renderbuffer {
                     raw: renderbuffer,
                     format,
+                    pixel_type,
                 },
                 channel,
                 kind: i::Kind::D2(extent.width as u32, extent.height as u32, 1, 1),
@@ -223,6 +279,7 @@ impl SwapchainImage {
             view: ImageView::Renderbuffer {
                 raw: renderbuffer,
                 aspects: format::Aspects::COLOR,
+                samples: 1,
             },
         }
     }
@@ -237,15 +294,50 @@ pub(crate) enum DescSetBindings {
         size: i32,
     },
     Texture(Texture, TextureTarget),
-    Sampler(Sampler),
-    SamplerDesc(i::SamplerDesc),
+    /// Texture with the sampler bound to the same texture unit.
+    CombinedImageSampler(Texture, TextureTarget, FatSampler),
+    Image(ImageBinding),
+    Sampler(FatSampler),
+}
+
+/// Arguments of `glBindImageTexture` for a storage image descriptor.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ImageBinding {
+    pub(crate) texture: Texture,
+    pub(crate) level: i32,
+    /// Bind all the layers of the texture, or only the given one.
+    pub(crate) layer: Option<i32>,
+    pub(crate) access: u32,
+    pub(crate) format: TextureFormat,
+}
+
+#[derive(Debug)]
+pub struct SetLayout {
+    /// Bindings, sorted by the binding index.
+    pub(crate) bindings: Vec<pso::DescriptorSetLayoutBinding>,
+    /// Index of the first descriptor of each binding in the set storage.
+    pub(crate) offsets: Vec<usize>,
+    /// Samplers baked into the layout, per descriptor of the set.
+    pub(crate) immutable_samplers: Vec<Option<FatSampler>>,
+}
+
+impl SetLayout {
+    /// Index of the first descriptor of the given binding in the set storage.
+    pub(crate) fn descriptor_index(&self, binding: pso::DescriptorBinding) -> usize {
+        let position = self
+            .bindings
+            .binary_search_by_key(&binding, |b| b.binding)
+            .unwrap();
+        self.offsets[position]
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct DescriptorSet {
     pub(crate) layout: DescriptorSetLayout,
+    /// Descriptors of all the bindings, with the arrays laid out consecutively.
     //TODO: use `UnsafeCell` instead
-    pub(crate) bindings: Vec<DescSetBindings>,
+    pub(crate) bindings: Vec<Option<DescSetBindings>>,
 }
 
 #[derive(Debug)]
@@ -258,7 +350,7 @@ impl pso::DescriptorPool<Backend> for DescriptorPool {
     ) -> Result<DescriptorSet, pso::AllocationError> {
         Ok(DescriptorSet {
             layout: Arc::clone(layout),
-            bindings: Vec::new(),
+            bindings: vec![None; layout.immutable_samplers.len()],
         })
     }
 
@@ -278,8 +370,9 @@ impl pso::DescriptorPool<Backend> for DescriptorPool {
 
 pub struct ShaderModule {
     pub(crate) prefer_naga: bool,
-    #[cfg(feature = "cross")]
-    pub(crate) spv: Vec<u32>,
+    /// SPIR-V code, translated by SPIRV-Cross and used for the pipeline cache lookups.
+    /// Only missing for naga modules without the `cross` feature.
+    pub(crate) spv: Option<Vec<u32>>,
     pub(crate) naga: Option<hal::device::NagaShader>,
 }
 
@@ -289,6 +382,187 @@ impl fmt::Debug for ShaderModule {
     }
 }
 
+/// Linked program stored in the pipeline cache.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedProgram {
+    /// Generated GLSL per shader type, used when the driver rejects the binary.
+    pub(crate) sources: Vec<(u32, String)>,
+    /// Binary format and data of the linked program.
+    pub(crate) binary: Option<(u32, Vec<u8>)>,
+    pub(crate) sampler_map: SamplerBindMap,
+    pub(crate) name_bindings: Vec<(String, BindingRegister, u8)>,
+}
+
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    /// Programs keyed by the shaders, specialization and layout they are built from.
+    pub(crate) programs: Mutex<FastHashMap<Vec<u8>, CachedProgram>>,
+}
+
+impl PipelineCache {
+    /// Serialize the programs, prepending the driver identification header.
+    pub(crate) fn serialize(&self, header: &[u8]) -> Vec<u8> {
+        let programs = self.programs.lock();
+        let mut writer = CacheWriter(header.to_vec());
+        writer.u32(programs.len() as u32);
+        for (key, program) in programs.iter() {
+            writer.bytes(key);
+            writer.u32(program.sources.len() as u32);
+            for &(shader_type, ref source) in program.sources.iter() {
+                writer.u32(shader_type);
+                writer.bytes(source.as_bytes());
+            }
+            match program.binary {
+                Some((format, ref data)) => {
+                    writer.u32(format);
+                    writer.bytes(data);
+                }
+                None => writer.u32(NO_PROGRAM_BINARY),
+            }
+            for slot in program.sampler_map.iter() {
+                writer.0.push(slot.unwrap_or(!0));
+            }
+            writer.u32(program.name_bindings.len() as u32);
+            for &(ref name, register, slot) in program.name_bindings.iter() {
+                writer.bytes(name.as_bytes());
+                writer.0.push(register as u8);
+                writer.0.push(slot);
+            }
+        }
+        writer.0
+    }
+
+    /// Deserialize the programs, checking the driver identification header.
+    pub(crate) fn deserialize(data: &[u8], header: &[u8]) -> Option<Self> {
+        if !data.starts_with(header) {
+            return None;
+        }
+        let mut reader = CacheReader(&data[header.len()..]);
+        let mut programs = FastHashMap::default();
+        for _ in 0..reader.u32()? {
+            let key = reader.bytes()?.to_vec();
+            let mut sources = Vec::new();
+            for _ in 0..reader.u32()? {
+                let shader_type = reader.u32()?;
+                sources.push((shader_type, reader.string()?));
+            }
+            let binary = match reader.u32()? {
+                NO_PROGRAM_BINARY => None,
+                format => Some((format, reader.bytes()?.to_vec())),
+            };
+            let mut sampler_map = [None; MAX_TEXTURE_SLOTS];
+            for slot in sampler_map.iter_mut() {
+                *slot = Some(reader.u8()?).filter(|&index| index != !0);
+            }
+            let mut name_bindings = Vec::new();
+            for _ in 0..reader.u32()? {
+                let name = reader.string()?;
+                let register = match reader.u8()? {
+                    0 => BindingRegister::Textures,
+                    1 => BindingRegister::Images,
+                    2 => BindingRegister::UniformBuffers,
+                    3 => BindingRegister::StorageBuffers,
+                    _ => return None,
+                };
+                name_bindings.push((name, register, reader.u8()?));
+            }
+            programs.insert(
+                key,
+                CachedProgram {
+                    sources,
+                    binary,
+                    sampler_map,
+                    name_bindings,
+                },
+            );
+        }
+        Some(PipelineCache {
+            programs: Mutex::new(programs),
+        })
+    }
+}
+
+/// Binary format marker for cached programs that only have sources.
+const NO_PROGRAM_BINARY: u32 = !0;
+
+pub(crate) struct CacheWriter(pub(crate) Vec<u8>);
+
+impl CacheWriter {
+    pub(crate) fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+    pub(crate) fn bytes(&mut self, data: &[u8]) {
+        self.u32(data.len() as u32);
+        self.0.extend_from_slice(data);
+    }
+    pub(crate) fn words(&mut self, data: &[u32]) {
+        self.u32(data.len() as u32);
+        for &word in data {
+            self.u32(word);
+        }
+    }
+    pub(crate) fn descriptor_type(&mut self, ty: pso::DescriptorType) {
+        use hal::pso::{BufferDescriptorFormat as Bdf, BufferDescriptorType as Bdt};
+        use hal::pso::{DescriptorType as Dt, ImageDescriptorType as Idt};
+        let (tag, flag) = match ty {
+            Dt::Sampler => (0, false),
+            Dt::Image {
+                ty: Idt::Sampled { with_sampler },
+            } => (1, with_sampler),
+            Dt::Image {
+                ty: Idt::Storage { read_only },
+            } => (2, read_only),
+            Dt::Buffer {
+                ty: Bdt::Uniform,
+                format: Bdf::Structured { dynamic_offset },
+            } => (3, dynamic_offset),
+            Dt::Buffer {
+                ty: Bdt::Uniform,
+                format: Bdf::Texel,
+            } => (4, false),
+            Dt::Buffer {
+                ty: Bdt::Storage { read_only },
+                format: Bdf::Structured { dynamic_offset },
+            } => (5 + dynamic_offset as u8, read_only),
+            Dt::Buffer {
+                ty: Bdt::Storage { read_only },
+                format: Bdf::Texel,
+            } => (7, read_only),
+            Dt::InputAttachment => (8, false),
+        };
+        self.0.push(tag);
+        self.0.push(flag as u8);
+    }
+}
+
+struct CacheReader<'a>(&'a [u8]);
+
+impl<'a> CacheReader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if self.0.len() < count {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(count);
+        self.0 = tail;
+        Some(head)
+    }
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|data| data[0])
+    }
+    fn u32(&mut self) -> Option<u32> {
+        let mut raw = [0; 4];
+        raw.copy_from_slice(self.take(4)?);
+        Some(u32::from_le_bytes(raw))
+    }
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let count = self.u32()? as usize;
+        self.take(count)
+    }
+    fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.bytes()?.to_vec()).ok()
+    }
+}
+
 #[derive(Debug)]
 pub struct Memory {
     pub(crate) properties: Properties,
@@ -297,6 +571,8 @@ pub struct Memory {
     pub(crate) buffer: Option<(RawBuffer, u32)>,
     /// Allocation size
     pub(crate) size: u64,
+    /// Heap the memory is allocated from.
+    pub(crate) heap_index: usize,
     pub(crate) map_flags: u32,
     pub(crate) emulate_map_allocation: Option<*mut u8>,
 }
@@ -310,13 +586,41 @@ pub struct RenderPass {
     pub(crate) subpasses: Vec<SubpassDesc>,
 }
 
+impl RenderPass {
+    /// Check if the attachment is used for the first time in the given sub-pass.
+    pub(crate) fn is_first_use(&self, at_id: pass::AttachmentId, subpass: pass::SubpassId) -> bool {
+        !self.subpasses[..subpass as usize]
+            .iter()
+            .any(|sp| sp.uses(at_id))
+    }
+
+    /// Check if the attachment is used for the last time in the given sub-pass.
+    pub(crate) fn is_last_use(&self, at_id: pass::AttachmentId, subpass: pass::SubpassId) -> bool {
+        !self.subpasses[subpass as usize + 1..]
+            .iter()
+            .any(|sp| sp.uses(at_id))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SubpassDesc {
     pub(crate) color_attachments: Vec<usize>,
     pub(crate) depth_stencil: Option<usize>,
+    /// Attachments read as textures by this sub-pass.
+    pub(crate) input_attachments: Vec<usize>,
+    /// Resolve targets, either empty or matching `color_attachments`.
+    pub(crate) resolve_attachments: Vec<usize>,
 }
 
 impl SubpassDesc {
+    /// Check if an attachment is referenced in any way by this sub-pass.
+    pub(crate) fn uses(&self, at_id: pass::AttachmentId) -> bool {
+        self.depth_stencil == Some(at_id)
+            || self.color_attachments.contains(&at_id)
+            || self.input_attachments.contains(&at_id)
+            || self.resolve_attachments.contains(&at_id)
+    }
+
     /// Check if an attachment is used by this sub-pass.
     pub(crate) fn _attachment_using(&self, at_id: pass::AttachmentId) -> Option<u32> {
         if self.depth_stencil == Some(at_id) {
@@ -335,6 +639,7 @@ pub(crate) struct PipelineLayoutSet {
     pub(crate) layout: DescriptorSetLayout,
     /// Mapping of resources, indexed by `pso::DescriptorBinding`, into the whole layout space.
     /// For image resources, the value is the texture slot index.
+    /// For storage texel buffers, the value is the image unit index.
     /// For sampler resources, the value is the index of the sampler in the whole layout.
     /// For buffers, the value is the uniform or storage slot index.
     /// For unused bindings, the value is `!0`
@@ -347,11 +652,14 @@ pub struct PipelineLayout {
     pub(crate) sets: Vec<PipelineLayoutSet>,
 }
 
-#[derive(Debug)]
-// No inter-queue synchronization required for GL.
-pub struct Semaphore;
+/// No inter-queue synchronization is required for GL,
+/// so semaphores only track the counter of timeline semaphores.
+#[derive(Debug, Default)]
+pub struct Semaphore {
+    pub(crate) timeline: Mutex<fence::Timeline>,
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AttributeDesc {
     pub(crate) location: u32,
     pub(crate) offset: u32,
@@ -368,7 +676,7 @@ pub struct UniformDesc {
     pub(crate) utype: u32,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VertexAttribFunction {
     Float,   // glVertexAttribPointer
     Integer, // glVertexAttribIPointer
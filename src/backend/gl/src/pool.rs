@@ -1,5 +1,6 @@
 use crate::{
     command::{self, Command, CommandBuffer},
+    dump::ObjectLabels,
     info, native as n, Backend, FastHashMap,
 };
 
@@ -56,9 +57,14 @@ pub enum BufferMemory {
 #[derive(Debug)]
 pub struct CommandPool {
     pub(crate) fbo: Option<n::RawFramebuffer>,
+    /// Source framebuffer of the image transfers.
+    pub(crate) read_fbo: Option<n::RawFramebuffer>,
+    /// Framebuffer of the dynamic rendering scopes.
+    pub(crate) render_fbo: Option<n::RawFramebuffer>,
     pub(crate) limits: command::Limits,
     pub(crate) memory: Arc<Mutex<BufferMemory>>,
     pub(crate) legacy_features: info::LegacyFeatures,
+    pub(crate) labels: Arc<ObjectLabels>,
 }
 
 impl hal::pool::CommandPool<Backend> for CommandPool {
@@ -86,9 +92,12 @@ impl hal::pool::CommandPool<Backend> for CommandPool {
         // TODO: Implement secondary buffers
         CommandBuffer::new(
             self.fbo,
+            self.read_fbo,
+            self.render_fbo,
             self.limits,
             self.memory.clone(),
             self.legacy_features,
+            self.labels.clone(),
         )
     }
 
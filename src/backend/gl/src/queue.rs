@@ -1,6 +1,6 @@
 use crate::{
-    command as com, device, info::LegacyFeatures, native, state, Backend, Device, GlContext, Share,
-    Starc, Surface, MAX_COLOR_ATTACHMENTS,
+    command as com, device, ext, info::LegacyFeatures, native, state, Backend, Device, GlContext,
+    Share, Starc, Surface, MAX_COLOR_ATTACHMENTS,
 };
 
 use arrayvec::ArrayVec;
@@ -8,6 +8,12 @@ use glow::HasContext;
 
 use std::{mem, slice};
 
+type FramebufferBinding = (
+    native::RawFramebuffer,
+    ArrayVec<[native::ImageView; MAX_COLOR_ATTACHMENTS]>,
+    Option<native::ImageView>,
+);
+
 // State caching system for command queue.
 //
 // We track the current global state, which is based on
@@ -23,10 +29,22 @@ struct State {
     // Currently bound index/element buffer.
     // None denotes that we don't know what is currently bound.
     index_buffer: Option<native::RawBuffer>,
-    // Currently set viewports.
-    num_viewports: usize,
-    // Currently set scissor rects.
-    num_scissors: usize,
+    // Currently set viewports, as the first viewport and the raw viewport and depth range data.
+    viewports: Option<(u32, Vec<u8>)>,
+    // Currently set scissor rects, as the first scissor and the raw rectangle data.
+    scissors: Option<(u32, Vec<u8>)>,
+    // Currently used program.
+    program: Option<native::Program>,
+    // Blending state, when it's the same for all the color targets.
+    blend: Option<Option<hal::pso::BlendState>>,
+    // Currently set rasterizer state.
+    rasterizer: Option<hal::pso::Rasterizer>,
+    // Currently bound vertex attributes, indexed by the location.
+    attributes: Vec<Option<(native::AttributeDesc, native::RawBuffer, i32, u32)>>,
+    // Currently bound draw framebuffer and its attachments.
+    draw_framebuffer: Option<FramebufferBinding>,
+    // Currently bound read framebuffer and its attachments.
+    read_framebuffer: Option<FramebufferBinding>,
 }
 
 impl State {
@@ -36,8 +54,14 @@ impl State {
         State {
             vao: false,
             index_buffer: None,
-            num_viewports: 0,
-            num_scissors: 0,
+            viewports: None,
+            scissors: None,
+            program: None,
+            blend: None,
+            rasterizer: None,
+            attributes: Vec::new(),
+            draw_framebuffer: None,
+            read_framebuffer: None,
         }
     }
 
@@ -46,20 +70,55 @@ impl State {
     fn flush(&mut self) {
         self.vao = false;
         self.index_buffer = None;
+        self.invalidate();
+    }
 
-        // TOOD: reset viewports and scissors
-        //       do we need to clear everything from 0..MAX_VIEWPORTS?
+    // Forget the state that may be changed outside of the queue,
+    // e.g. by the device when creating pipelines.
+    fn invalidate(&mut self) {
+        self.viewports = None;
+        self.scissors = None;
+        self.program = None;
+        self.blend = None;
+        self.rasterizer = None;
+        self.attributes.clear();
+        self.draw_framebuffer = None;
+        self.read_framebuffer = None;
     }
 }
 
+/// Numbers of the redundant state changes skipped by the queue.
+///
+/// The counters are accumulated over all the submissions, and can be
+/// used to measure the effect of the state caching.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EliminatedCalls {
+    /// Program bindings.
+    pub programs: usize,
+    /// Blending state changes.
+    pub blends: usize,
+    /// Rasterizer state changes.
+    pub rasterizers: usize,
+    /// Vertex attribute bindings.
+    pub attributes: usize,
+    /// Framebuffer bindings.
+    pub framebuffers: usize,
+    /// Viewport changes, either redundant or overwritten by the next command.
+    pub viewports: usize,
+    /// Scissor changes, either redundant or overwritten by the next command.
+    pub scissors: usize,
+}
+
 #[derive(Debug)]
 pub struct Queue {
     pub(crate) share: Starc<Share>,
     features: hal::Features,
     vao: Option<native::VertexArray>,
     state: State,
+    eliminated: EliminatedCalls,
     fill_buffer: native::RawBuffer,
     fill_data: Box<[u32]>,
+    dump_commands: bool,
 }
 
 const FILL_DATA_WORDS: usize = 16 << 10;
@@ -88,8 +147,10 @@ impl Queue {
             features,
             vao,
             state: State::new(),
+            eliminated: EliminatedCalls::default(),
             fill_buffer,
             fill_data: vec![0; FILL_DATA_WORDS].into_boxed_slice(),
+            dump_commands: std::env::var_os(crate::dump::DUMP_ENV_VAR).is_some(),
         }
     }
 
@@ -106,6 +167,43 @@ impl Queue {
         self.state.flush();
     }
 
+    /// Numbers of the redundant GL calls eliminated so far by the state caching.
+    pub fn eliminated_calls(&self) -> EliminatedCalls {
+        self.eliminated
+    }
+
+    /// Check if the state set by a command is completely overwritten by the next one,
+    /// so that the command can be skipped.
+    fn is_overwritten(cmd: &com::Command, next: &com::Command, data_buf: &[u8]) -> bool {
+        match (cmd, next) {
+            (
+                &com::Command::SetViewports {
+                    first_viewport,
+                    viewport_ptr,
+                    ..
+                },
+                &com::Command::SetViewports {
+                    first_viewport: next_first,
+                    viewport_ptr: next_ptr,
+                    ..
+                },
+            ) => {
+                let count = Self::get::<[f32; 4]>(data_buf, viewport_ptr).len() as u32;
+                let next_count = Self::get::<[f32; 4]>(data_buf, next_ptr).len() as u32;
+                next_first <= first_viewport && first_viewport + count <= next_first + next_count
+            }
+            (
+                &com::Command::SetScissors(first_scissor, data_ptr),
+                &com::Command::SetScissors(next_first, next_ptr),
+            ) => {
+                let count = Self::get::<[i32; 4]>(data_buf, data_ptr).len() as u32;
+                let next_count = Self::get::<[i32; 4]>(data_buf, next_ptr).len() as u32;
+                next_first <= first_scissor && first_scissor + count <= next_first + next_count
+            }
+            _ => false,
+        }
+    }
+
     /*
     fn bind_attribute(&mut self, slot: hal::AttributeSlot, buffer: n::Buffer, bel: BufferElement) {
         use core::format::SurfaceType as S;
@@ -175,10 +273,10 @@ impl Queue {
         Device::bind_target(&self.share.context, point, attachment, view)
     }
 
-    fn _unbind_target(&mut self, point: u32, attachment: u32) {
+    fn unbind_target(&mut self, point: u32, attachment: u32) {
         let gl = &self.share.context;
-        // TODO: Find workaround or use explicit `textarget` with the other `framebuffer_texture`
-        unsafe { gl.framebuffer_texture(point, attachment, None, 0) };
+        // Attaching a zero renderbuffer detaches any kind of image.
+        unsafe { gl.framebuffer_renderbuffer(point, attachment, glow::RENDERBUFFER, None) };
     }
 
     /// Return a reference to a stored data object.
@@ -228,6 +326,9 @@ impl Queue {
             gl.depth_range_f32(0.0, 1.0);
             gl.scissor(0, 0, 0, 0);
         };
+
+        // The device shares the context, so the other bindings are not known either.
+        self.state.invalidate();
     }
 
     fn process(&mut self, cmd: &com::Command, data_buf: &[u8]) {
@@ -388,6 +489,15 @@ impl Queue {
                 viewport_ptr,
                 depth_range_ptr,
             } => {
+                let mut raw = Self::get_raw(data_buf, viewport_ptr).to_vec();
+                raw.extend_from_slice(Self::get_raw(data_buf, depth_range_ptr));
+                let state = Some((first_viewport, raw));
+                if self.state.viewports == state {
+                    self.eliminated.viewports += 1;
+                    return;
+                }
+                self.state.viewports = state;
+
                 let gl = &self.share.context;
                 let viewports = Self::get::<[f32; 4]>(data_buf, viewport_ptr);
                 let depth_ranges = Self::get::<[f64; 2]>(data_buf, depth_range_ptr);
@@ -430,6 +540,13 @@ impl Queue {
                 }
             }
             com::Command::SetScissors(first_scissor, data_ptr) => {
+                let state = Some((first_scissor, Self::get_raw(data_buf, data_ptr).to_vec()));
+                if self.state.scissors == state {
+                    self.eliminated.scissors += 1;
+                    return;
+                }
+                self.state.scissors = state;
+
                 let gl = &self.share.context;
                 let scissors = Self::get::<[i32; 4]>(data_buf, data_ptr);
                 let num_scissors = scissors.len();
@@ -446,6 +563,13 @@ impl Queue {
                     unsafe { gl.scissor_slice(first_scissor, num_scissors as i32, scissors) };
                 }
             }
+            com::Command::SetScissorTest(enable) => unsafe {
+                if enable {
+                    self.share.context.enable(glow::SCISSOR_TEST);
+                } else {
+                    self.share.context.disable(glow::SCISSOR_TEST);
+                }
+            },
             com::Command::SetBlendColor(color) => {
                 state::set_blend_color(&self.share.context, color);
             }
@@ -488,58 +612,131 @@ impl Queue {
                 ref colors,
                 ref depth_stencil,
             } => {
+                let binding = Some((framebuffer, colors.clone(), depth_stencil.clone()));
+                let is_bound = match target {
+                    glow::DRAW_FRAMEBUFFER => self.state.draw_framebuffer == binding,
+                    glow::READ_FRAMEBUFFER => self.state.read_framebuffer == binding,
+                    _ => {
+                        self.state.draw_framebuffer == binding
+                            && self.state.read_framebuffer == binding
+                    }
+                };
+                if is_bound {
+                    self.eliminated.framebuffers += 1;
+                    return;
+                }
+                if target != glow::READ_FRAMEBUFFER {
+                    self.state.draw_framebuffer = binding.clone();
+                }
+                if target != glow::DRAW_FRAMEBUFFER {
+                    self.state.read_framebuffer = binding;
+                }
+
                 let gl = &self.share.context;
                 unsafe { gl.bind_framebuffer(target, Some(framebuffer)) };
                 for (i, view) in colors.iter().enumerate() {
                     self.bind_target(target, glow::COLOR_ATTACHMENT0 + i as u32, view);
                 }
                 if let Some(ref view) = *depth_stencil {
-                    let aspects = view.aspects();
-                    let attachment = if aspects == hal::format::Aspects::DEPTH {
-                        glow::DEPTH_ATTACHMENT
-                    } else if aspects == hal::format::Aspects::STENCIL {
-                        glow::STENCIL_ATTACHMENT
-                    } else {
-                        glow::DEPTH_STENCIL_ATTACHMENT
-                    };
-                    self.bind_target(target, attachment, view);
+                    self.bind_target(target, view.depth_stencil_point(), view);
                 }
             }
-            com::Command::FillBuffer(buffer, ref range, value) => {
-                //Note: buffers with `DYNAMIC_STORAGE_BIT` can't be uploaded to directly.
-                // And we expect the target buffers to be on GPU, where we assign this flag.
-
-                let total_size = (range.end - range.start) as i32;
-                let temp_size = (total_size as usize / 4).min(FILL_DATA_WORDS);
-                let mut dst_offset = range.start as i32;
-                for v in self.fill_data[..temp_size].iter_mut() {
-                    *v = value;
+            com::Command::DetachAttachments(target, ref attachments) => {
+                self.state.draw_framebuffer = None;
+                self.state.read_framebuffer = None;
+                for &attachment in attachments {
+                    self.unbind_target(target, attachment);
                 }
-
+            }
+            com::Command::InvalidateAttachments(target, ref attachments) => {
+                if self.share.private_caps.invalidate_framebuffer {
+                    unsafe {
+                        self.share
+                            .context
+                            .invalidate_framebuffer(target, attachments);
+                    }
+                }
+            }
+            com::Command::BlitFramebuffer {
+                read_buffer,
+                src,
+                dst,
+                mask,
+                filter,
+            } => unsafe {
                 let gl = &self.share.context;
-                unsafe {
-                    gl.bind_buffer(glow::COPY_READ_BUFFER, Some(self.fill_buffer));
-                    gl.buffer_sub_data_u8_slice(
-                        glow::COPY_READ_BUFFER,
-                        0,
-                        slice::from_raw_parts(self.fill_data.as_ptr() as *const u8, temp_size * 4),
-                    );
-                    gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer));
+                gl.read_buffer(read_buffer);
+                gl.blit_framebuffer(
+                    src.x as i32,
+                    src.y as i32,
+                    src.x as i32 + src.w as i32,
+                    src.y as i32 + src.h as i32,
+                    dst.x as i32,
+                    dst.y as i32,
+                    dst.x as i32 + dst.w as i32,
+                    dst.y as i32 + dst.h as i32,
+                    mask,
+                    filter,
+                );
+            },
+            com::Command::FillBuffer(buffer, ref range, value) => {
+                if self.share.private_caps.clear_buffer_data {
+                    // The filled size is a multiple of 4, as for the `WHOLE_SIZE` in Vulkan.
+                    let size = (range.end - range.start) & !3;
+                    let gl = &self.share.context;
+                    unsafe {
+                        gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer));
+                        gl.ext.clear_buffer_sub_data(
+                            glow::COPY_WRITE_BUFFER,
+                            glow::R32UI,
+                            range.start as isize,
+                            size as isize,
+                            glow::RED_INTEGER,
+                            glow::UNSIGNED_INT,
+                            &value as *const u32 as *const _,
+                        );
+                        gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+                    }
+                } else {
+                    //Note: buffers with `DYNAMIC_STORAGE_BIT` can't be uploaded to directly.
+                    // And we expect the target buffers to be on GPU, where we assign this flag.
+
+                    let total_size = (range.end - range.start) as i32;
+                    let temp_size = (total_size as usize / 4).min(FILL_DATA_WORDS);
+                    let mut dst_offset = range.start as i32;
+                    for v in self.fill_data[..temp_size].iter_mut() {
+                        *v = value;
+                    }
 
-                    while dst_offset < range.end as i32 {
-                        let copy_size = (temp_size as i32 * 4).min(range.end as i32 - dst_offset);
-                        gl.copy_buffer_sub_data(
+                    let gl = &self.share.context;
+                    unsafe {
+                        gl.bind_buffer(glow::COPY_READ_BUFFER, Some(self.fill_buffer));
+                        gl.buffer_sub_data_u8_slice(
                             glow::COPY_READ_BUFFER,
-                            glow::COPY_WRITE_BUFFER,
                             0,
-                            dst_offset,
-                            copy_size,
+                            slice::from_raw_parts(
+                                self.fill_data.as_ptr() as *const u8,
+                                temp_size * 4,
+                            ),
                         );
-                        dst_offset += copy_size;
-                    }
+                        gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer));
+
+                        while dst_offset < range.end as i32 {
+                            let copy_size =
+                                (temp_size as i32 * 4).min(range.end as i32 - dst_offset);
+                            gl.copy_buffer_sub_data(
+                                glow::COPY_READ_BUFFER,
+                                glow::COPY_WRITE_BUFFER,
+                                0,
+                                dst_offset,
+                                copy_size,
+                            );
+                            dst_offset += copy_size;
+                        }
 
-                    gl.bind_buffer(glow::COPY_READ_BUFFER, None);
-                    gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+                        gl.bind_buffer(glow::COPY_READ_BUFFER, None);
+                        gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+                    }
                 }
             }
             com::Command::SetDrawColorBuffers(ref indices) => {
@@ -554,13 +751,24 @@ impl Queue {
                     .context
                     .patch_parameter_i32(glow::PATCH_VERTICES, num);
             },
-            com::Command::BindProgram(program) => unsafe {
-                self.share.context.use_program(Some(program));
-            },
+            com::Command::BindProgram(program) => {
+                if self.state.program == Some(program) {
+                    self.eliminated.programs += 1;
+                    return;
+                }
+                self.state.program = Some(program);
+                unsafe { self.share.context.use_program(Some(program)) };
+            }
             com::Command::SetBlend(ref blend) => {
+                if self.state.blend == Some(*blend) {
+                    self.eliminated.blends += 1;
+                    return;
+                }
+                self.state.blend = Some(*blend);
                 state::set_blend(&self.share.context, blend);
             }
             com::Command::SetBlendSlot(slot, ref blend) => {
+                self.state.blend = None;
                 if self.share.private_caps.draw_buffers {
                     state::set_blend_slot(&self.share.context, slot, blend, &self.features);
                 } else {
@@ -570,6 +778,17 @@ impl Queue {
             com::Command::BindAttribute(ref attribute, handle, stride, rate) => unsafe {
                 use crate::native::VertexAttribFunction::*;
 
+                let location = attribute.location as usize;
+                if self.state.attributes.len() <= location {
+                    self.state.attributes.resize(location + 1, None);
+                }
+                let binding = Some((attribute.clone(), handle, stride, rate));
+                if self.state.attributes[location] == binding {
+                    self.eliminated.attributes += 1;
+                    return;
+                }
+                self.state.attributes[location] = binding;
+
                 let &native::AttributeDesc {
                     location,
                     size,
@@ -722,78 +941,33 @@ impl Queue {
                     error!("CopyTextureToBuffer is not implemented on GLES");
                 }
             }
-            com::Command::CopyRenderbufferToBuffer(..) => {
-                //TODO: use FBO
-                error!("CopyRenderbufferToBuffer is not implemented");
-            }
-            com::Command::CopyImageToTexture(..) => {
-                //TODO: use FBO
-                error!("CopyImageToTexture is not implemented");
-            }
-            com::Command::CopyImageToRenderbuffer {
-                src_image,
-                dst_renderbuffer,
-                dst_format,
-                ref data,
-            } => {
+            com::Command::ReadPixels {
+                read_buffer,
+                rect,
+                format,
+                pixel_type,
+                dst_buffer,
+                buffer_offset,
+                row_length,
+            } => unsafe {
                 let gl = &self.share.context;
-
-                if data.src_subresource.aspects != hal::format::Aspects::COLOR
-                    || data.dst_subresource.aspects != hal::format::Aspects::COLOR
-                {
-                    unimplemented!()
-                }
-
-                match src_image {
-                    native::ImageType::Texture { .. } => unimplemented!(),
-                    native::ImageType::Renderbuffer {
-                        raw: src_renderbuffer,
-                        format: src_format,
-                    } => {
-                        if src_format != dst_format {
-                            unimplemented!()
-                        }
-
-                        unsafe {
-                            let src_fbo = gl.create_framebuffer().unwrap();
-                            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(src_fbo));
-                            gl.framebuffer_renderbuffer(
-                                glow::READ_FRAMEBUFFER,
-                                glow::COLOR_ATTACHMENT0,
-                                glow::RENDERBUFFER,
-                                Some(src_renderbuffer),
-                            );
-
-                            let dst_fbo = gl.create_framebuffer().unwrap();
-                            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(dst_fbo));
-                            gl.framebuffer_renderbuffer(
-                                glow::DRAW_FRAMEBUFFER,
-                                glow::COLOR_ATTACHMENT0,
-                                glow::RENDERBUFFER,
-                                Some(dst_renderbuffer),
-                            );
-
-                            gl.blit_framebuffer(
-                                data.src_offset.x,
-                                data.src_offset.y,
-                                data.src_offset.x + data.extent.width as i32,
-                                data.src_offset.y + data.extent.height as i32,
-                                data.dst_offset.x,
-                                data.dst_offset.y,
-                                data.dst_offset.x + data.extent.width as i32,
-                                data.dst_offset.y + data.extent.height as i32,
-                                glow::COLOR_BUFFER_BIT,
-                                glow::NEAREST,
-                            );
-
-                            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-
-                            gl.delete_framebuffer(src_fbo);
-                            gl.delete_framebuffer(dst_fbo);
-                        }
-                    }
-                }
-            }
+                gl.read_buffer(read_buffer);
+                gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(dst_buffer));
+                gl.pixel_store_i32(glow::PACK_ROW_LENGTH, row_length as i32);
+                gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+                gl.read_pixels(
+                    rect.x as i32,
+                    rect.y as i32,
+                    rect.w as i32,
+                    rect.h as i32,
+                    format,
+                    pixel_type,
+                    glow::PixelPackData::BufferOffset(buffer_offset as u32),
+                );
+                gl.pixel_store_i32(glow::PACK_ROW_LENGTH, 0);
+                gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+                gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+            },
             com::Command::BindBufferRange(target, index, buffer, offset, size) => unsafe {
                 let gl = &self.share.context;
                 gl.bind_buffer_range(target, index, Some(buffer), offset, size);
@@ -803,6 +977,23 @@ impl Queue {
                 gl.active_texture(glow::TEXTURE0 + index);
                 gl.bind_texture(textype, Some(texture));
             },
+            com::Command::BindImage(unit, ref image) => {
+                if self.share.private_caps.image_load_store {
+                    unsafe {
+                        self.share.context.ext.bind_image_texture(
+                            unit,
+                            image.texture,
+                            image.level,
+                            image.layer.is_none() as u8,
+                            image.layer.unwrap_or(0),
+                            image.access,
+                            image.format,
+                        );
+                    }
+                } else {
+                    warn!("Storage images are not supported");
+                }
+            }
             com::Command::BindSampler(index, sampler) => unsafe {
                 let gl = &self.share.context;
                 gl.bind_sampler(index, Some(sampler));
@@ -942,6 +1133,12 @@ impl Queue {
                 use hal::pso::FrontFace::*;
                 use hal::pso::PolygonMode::*;
 
+                if self.state.rasterizer == Some(rasterizer) {
+                    self.eliminated.rasterizers += 1;
+                    return;
+                }
+                self.state.rasterizer = Some(rasterizer);
+
                 let gl = &self.share.context;
 
                 unsafe {
@@ -1060,6 +1257,38 @@ impl Queue {
                     .context
                     .stencil_mask_separate(glow::BACK, values.back);
             },
+            com::Command::BindStencil(ref stencil) => {
+                state::bind_stencil(&self.share.context, stencil);
+            }
+            com::Command::SetLineWidth(width) => {
+                // The static line width of the next rasterizer has to be set again.
+                self.state.rasterizer = None;
+                unsafe { self.share.context.line_width(width) };
+            }
+            com::Command::SetDepthBoundsTest(enable) => {
+                if self.share.private_caps.depth_bounds {
+                    let gl = &self.share.context;
+                    unsafe {
+                        if enable {
+                            gl.enable(ext::DEPTH_BOUNDS_TEST);
+                        } else {
+                            gl.disable(ext::DEPTH_BOUNDS_TEST);
+                        }
+                    }
+                } else if enable {
+                    warn!("Depth bounds test is not supported");
+                }
+            }
+            com::Command::SetDepthBounds(ref bounds) => {
+                if self.share.private_caps.depth_bounds {
+                    unsafe {
+                        self.share
+                            .context
+                            .ext
+                            .depth_bounds(bounds.start as f64, bounds.end as f64)
+                    };
+                }
+            }
             com::Command::MemoryBarrier(mask) => {
                 if self.share.private_caps.memory_barrier {
                     unsafe {
@@ -1067,24 +1296,96 @@ impl Queue {
                     }
                 }
             }
+            com::Command::InsertDebugMarker(name) => {
+                if self.share.private_caps.debug_labels {
+                    let message = String::from_utf8_lossy(Self::get_raw(data_buf, name));
+                    unsafe {
+                        self.share.context.debug_message_insert(
+                            glow::DEBUG_SOURCE_APPLICATION,
+                            glow::DEBUG_TYPE_MARKER,
+                            0,
+                            glow::DEBUG_SEVERITY_NOTIFICATION,
+                            message,
+                        );
+                    }
+                }
+            }
+            com::Command::PushDebugGroup(name) => {
+                if self.share.private_caps.debug_labels {
+                    let message = String::from_utf8_lossy(Self::get_raw(data_buf, name));
+                    unsafe {
+                        self.share.context.push_debug_group(
+                            glow::DEBUG_SOURCE_APPLICATION,
+                            0,
+                            message,
+                        );
+                    }
+                }
+            }
+            com::Command::PopDebugGroup => {
+                if self.share.private_caps.debug_labels {
+                    unsafe { self.share.context.pop_debug_group() };
+                }
+            }
+            com::Command::SetEvent(ref event) => {
+                let gl = &self.share.context;
+                let mut state = event.lock();
+                if let native::EventState::Pending(sync) = *state {
+                    unsafe { gl.delete_sync(sync) };
+                }
+                let sync = if self.share.private_caps.sync {
+                    match unsafe { gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) } {
+                        Ok(sync) => Some(sync),
+                        Err(err) => {
+                            error!("Failed to create a sync object for the event: {}", err);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+                *state = match sync {
+                    Some(sync) => native::EventState::Pending(sync),
+                    None => {
+                        // No way to track the GPU progress, wait for it on the host.
+                        unsafe { gl.finish() };
+                        native::EventState::Set
+                    }
+                };
+            }
+            com::Command::ResetEvent(ref event) => {
+                let mut state = event.lock();
+                if let native::EventState::Pending(sync) = *state {
+                    unsafe { self.share.context.delete_sync(sync) };
+                }
+                *state = native::EventState::Reset;
+            }
+            com::Command::WaitEvent(ref event) => match *event.lock() {
+                native::EventState::Pending(sync) => unsafe {
+                    self.share.context.wait_sync(sync, 0, glow::TIMEOUT_IGNORED);
+                },
+                native::EventState::Set => {}
+                native::EventState::Reset => {
+                    // The GL queue is processed on submission, so an event
+                    // signaled by the host later on can't be waited for.
+                    warn!("Waiting on an unsignaled event is not supported");
+                }
+            },
         }
         if let Err(err) = self.share.check() {
             panic!("Error {:?} executing command: {:?}", err, cmd)
         }
     }
-}
 
-impl hal::queue::Queue<Backend> for Queue {
-    unsafe fn submit<'a, Ic, Iw, Is>(
+    /// Execute the command buffers of a submission and signal the fence,
+    /// returning the index of the submission.
+    unsafe fn execute<'a, Ic>(
         &mut self,
         command_buffers: Ic,
-        _wait_semaphores: Iw,
-        _signal_semaphores: Is,
         fence: Option<&mut native::Fence>,
-    ) where
+    ) -> u64
+    where
         Ic: Iterator<Item = &'a com::CommandBuffer>,
-        Iw: Iterator<Item = (&'a native::Semaphore, hal::pso::PipelineStage)>,
-        Is: Iterator<Item = &'a native::Semaphore>,
     {
         use crate::pool::BufferMemory;
         {
@@ -1103,26 +1404,96 @@ impl hal::queue::Queue<Backend> for Queue {
                 assert!(buffer.commands.len() >= (cb.buf.offset + cb.buf.size) as usize);
                 let commands = &buffer.commands
                     [cb.buf.offset as usize..(cb.buf.offset + cb.buf.size) as usize];
+                if self.dump_commands {
+                    info!(
+                        "Submitting command buffer {}:\n{}",
+                        cmd_buf.name.as_deref().unwrap_or("<unnamed>"),
+                        crate::dump::to_text(commands, &buffer.data, &self.share.labels)
+                    );
+                }
                 self.reset_state();
-                for com in commands {
-                    self.process(com, &buffer.data);
+                let debug_group = cmd_buf
+                    .name
+                    .as_ref()
+                    .filter(|_| self.share.private_caps.debug_labels);
+                if let Some(name) = debug_group {
+                    self.share
+                        .context
+                        .push_debug_group(glow::DEBUG_SOURCE_APPLICATION, 0, name);
+                }
+                let mut commands = commands.iter().peekable();
+                while let Some(com) = commands.next() {
+                    match commands.peek() {
+                        Some(next) if Self::is_overwritten(com, next, &buffer.data) => match *com {
+                            com::Command::SetViewports { .. } => self.eliminated.viewports += 1,
+                            _ => self.eliminated.scissors += 1,
+                        },
+                        _ => self.process(com, &buffer.data),
+                    }
+                }
+                if debug_group.is_some() {
+                    self.share.context.pop_debug_group();
                 }
             }
         }
 
+        // Every submission is tracked, so that the completion of the earlier
+        // ones can be deduced from the later fences.
+        let sync = self.share.private_caps.sync;
+        let index = self.share.fences.lock().signal(&self.share.context, sync);
         if let Some(fence) = fence {
-            *fence = if self.share.private_caps.sync {
-                native::Fence::Pending(
-                    self.share
-                        .context
-                        .fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0)
-                        .unwrap(),
-                )
+            *fence = if sync {
+                native::Fence::Pending(index)
             } else {
                 self.share.context.flush();
                 native::Fence::Idle { signaled: true }
             }
         }
+        index
+    }
+}
+
+impl hal::queue::Queue<Backend> for Queue {
+    unsafe fn submit<'a, Ic, Iw, Is>(
+        &mut self,
+        command_buffers: Ic,
+        _wait_semaphores: Iw,
+        _signal_semaphores: Is,
+        fence: Option<&mut native::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a com::CommandBuffer>,
+        Iw: Iterator<Item = (&'a native::Semaphore, hal::pso::PipelineStage)>,
+        Is: Iterator<Item = &'a native::Semaphore>,
+    {
+        self.execute(command_buffers, fence);
+    }
+
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        command_buffers: Ic,
+        wait_semaphores: Iw,
+        signal_semaphores: Is,
+        fence: Option<&mut native::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a com::CommandBuffer>,
+        Iw: Iterator<Item = (&'a native::Semaphore, u64, hal::pso::PipelineStage)>,
+        Is: Iterator<Item = (&'a native::Semaphore, u64)>,
+    {
+        // There is only one queue, executing the submissions in order, so the waits
+        // on values signaled by previous submissions are satisfied implicitly.
+        for (semaphore, value, _) in wait_semaphores {
+            let timeline = semaphore.timeline.lock();
+            if !timeline.will_reach(value) {
+                warn!(
+                    "Waiting on timeline value {} that is not signaled by any submission",
+                    value
+                );
+            }
+        }
+        let index = self.execute(command_buffers, fence);
+        for (semaphore, value) in signal_semaphores {
+            semaphore.timeline.lock().push(value, index);
+        }
     }
 
     unsafe fn present(
@@ -1137,6 +1508,7 @@ impl hal::queue::Queue<Backend> for Queue {
     fn wait_idle(&mut self) -> Result<(), hal::device::OutOfMemory> {
         unsafe {
             self.share.context.finish();
+            self.share.fences.lock().complete_all(&self.share.context);
         }
         Ok(())
     }
@@ -16,7 +16,6 @@ pub fn map_comparison(cmp: pso::Comparison) -> u32 {
     }
 }
 
-#[allow(dead_code)]
 fn map_operation(op: pso::StencilOp) -> u32 {
     use hal::pso::StencilOp::*;
     match op {
@@ -31,8 +30,8 @@ fn map_operation(op: pso::StencilOp) -> u32 {
     }
 }
 
-#[allow(dead_code)]
-pub(crate) fn bind_stencil(gl: &GlContainer, stencil: &Option<pso::StencilTest>, cull: pso::Face) {
+/// Set the stencil test. The write masks are set separately.
+pub(crate) fn bind_stencil(gl: &GlContainer, stencil: &Option<pso::StencilTest>) {
     fn bind_side(
         gl: &GlContainer,
         face: u32,
@@ -55,34 +54,20 @@ pub(crate) fn bind_stencil(gl: &GlContainer, stencil: &Option<pso::StencilTest>,
             unsafe { gl.enable(glow::STENCIL_TEST) };
             let read_masks = stencil.read_masks.static_or(pso::Sided::new(!0));
             let ref_values = stencil.reference_values.static_or(pso::Sided::new(0));
-            if !cull.contains(pso::Face::FRONT) {
-                bind_side(
-                    gl,
-                    glow::FRONT,
-                    &stencil.faces.front,
-                    read_masks.front,
-                    ref_values.front,
-                );
-                if let pso::State::Static(values) = stencil.write_masks {
-                    unsafe {
-                        gl.stencil_mask_separate(glow::FRONT, values.front);
-                    }
-                }
-            }
-            if !cull.contains(pso::Face::BACK) {
-                bind_side(
-                    gl,
-                    glow::BACK,
-                    &stencil.faces.back,
-                    read_masks.back,
-                    ref_values.back,
-                );
-                if let pso::State::Static(values) = stencil.write_masks {
-                    unsafe {
-                        gl.stencil_mask_separate(glow::BACK, values.back);
-                    }
-                }
-            }
+            bind_side(
+                gl,
+                glow::FRONT,
+                &stencil.faces.front,
+                read_masks.front,
+                ref_values.front,
+            );
+            bind_side(
+                gl,
+                glow::BACK,
+                &stencil.faces.back,
+                read_masks.back,
+                ref_values.back,
+            );
         }
         None => unsafe {
             gl.disable(glow::STENCIL_TEST);
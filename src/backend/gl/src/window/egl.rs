@@ -1,6 +1,6 @@
 //! EGL-based surface and swapchain.
 
-use crate::{conv, native, GlContainer, PhysicalDevice, Starc};
+use crate::{conv, ext::ExtFns, native, GlContainer, PhysicalDevice, Starc};
 use glow::HasContext;
 use hal::{image, window as w};
 use parking_lot::Mutex;
@@ -13,6 +13,7 @@ pub struct Swapchain {
     /// Extent because the window lies
     extent: w::Extent2D,
     format: native::TextureFormat,
+    pixel_type: native::DataType,
     channel: hal::format::ChannelType,
 }
 
@@ -133,7 +134,7 @@ fn choose_config(
         }
     }
 
-    Err(hal::UnsupportedBackend)
+    Err(hal::UnsupportedBackend::new("No suitable EGL config"))
 }
 
 impl Inner {
@@ -144,7 +145,7 @@ impl Inner {
     ) -> Result<Self, hal::UnsupportedBackend> {
         let version = egl
             .initialize(display)
-            .map_err(|_| hal::UnsupportedBackend)?;
+            .map_err(|_| hal::UnsupportedBackend::new("Unable to initialize the EGL display"))?;
         let vendor = egl.query_string(Some(display), egl::VENDOR).unwrap();
         let display_extensions = egl
             .query_string(Some(display), egl::EXTENSIONS)
@@ -190,7 +191,9 @@ impl Inner {
             Ok(context) => context,
             Err(e) => {
                 log::warn!("unable to create GLES 3.x context: {:?}", e);
-                return Err(hal::UnsupportedBackend);
+                return Err(hal::UnsupportedBackend::new(
+                    "Unable to create a GLES 3.x context",
+                ));
             }
         };
 
@@ -199,7 +202,7 @@ impl Inner {
             egl.create_pbuffer_surface(display, config, &attributes)
                 .map_err(|e| {
                     log::warn!("Error in create_pbuffer_surface: {:?}", e);
-                    hal::UnsupportedBackend
+                    hal::UnsupportedBackend::new("Unable to create a pbuffer surface")
                 })?
         };
 
@@ -233,7 +236,7 @@ impl hal::Instance<crate::Backend> for Instance {
             Ok(egl) => Starc::new(egl),
             Err(e) => {
                 log::warn!("Unable to open libEGL.so: {:?}", e);
-                return Err(hal::UnsupportedBackend);
+                return Err(hal::UnsupportedBackend::new("Unable to open libEGL.so"));
             }
         };
 
@@ -304,16 +307,16 @@ impl hal::Instance<crate::Backend> for Instance {
             )
             .unwrap();
 
-        let context = unsafe {
-            glow::Context::from_loader_function(|name| {
-                inner
-                    .egl
-                    .get_proc_address(name)
-                    .map_or(ptr::null(), |p| p as *const _)
-            })
+        let loader = |name: &str| {
+            inner
+                .egl
+                .get_proc_address(name)
+                .map_or(ptr::null(), |p| p as *const _)
         };
+        let context = unsafe { glow::Context::from_loader_function(loader) };
+        let ext = ExtFns::load(loader);
         // Create physical device
-        vec![PhysicalDevice::new_adapter(context)]
+        vec![PhysicalDevice::new_adapter(context, ext)]
     }
 
     #[cfg_attr(target_os = "macos", allow(unused, unused_mut, unreachable_code))]
@@ -528,7 +531,8 @@ impl w::PresentationSurface<crate::Backend> for Surface {
             renderbuffer,
             framebuffer,
             extent: config.extent,
-            format: desc.tex_internal,
+            format: desc.tex_external,
+            pixel_type: desc.data_type,
             channel: config.format.base_format().1,
         });
 
@@ -548,8 +552,13 @@ impl w::PresentationSurface<crate::Backend> for Surface {
         _timeout_ns: u64,
     ) -> Result<(Self::SwapchainImage, Option<w::Suboptimal>), w::AcquireError> {
         let sc = self.swapchain.as_ref().unwrap();
-        let sc_image =
-            native::SwapchainImage::new(sc.renderbuffer, sc.format, sc.extent, sc.channel);
+        let sc_image = native::SwapchainImage::new(
+            sc.renderbuffer,
+            sc.format,
+            sc.pixel_type,
+            sc.extent,
+            sc.channel,
+        );
         Ok((sc_image, None))
     }
 }
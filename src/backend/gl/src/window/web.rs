@@ -1,5 +1,6 @@
 use crate::{
-    conv, device::Device, native, Backend as B, GlContainer, PhysicalDevice, QueueFamily, Starc,
+    conv, device::Device, ext::ExtFns, native, Backend as B, GlContainer, PhysicalDevice,
+    QueueFamily, Starc,
 };
 use glow::HasContext;
 use hal::{adapter::Adapter, format as f, image, window};
@@ -12,6 +13,7 @@ pub struct Swapchain {
     pub(crate) extent: window::Extent2D,
     pub(crate) channel: f::ChannelType,
     pub(crate) raw_format: native::TextureFormat,
+    pub(crate) pixel_type: native::DataType,
     pub(crate) framebuffer: native::RawFramebuffer,
 }
 
@@ -59,7 +61,7 @@ impl hal::Instance<B> for Instance {
             None => return Vec::new(),
         };
 
-        let adapter = PhysicalDevice::new_adapter(context);
+        let adapter = PhysicalDevice::new_adapter(context, ExtFns::default());
         vec![adapter]
     }
 
@@ -208,6 +210,7 @@ impl window::PresentationSurface<B> for Surface {
             extent: config.extent,
             channel: config.format.base_format().1,
             raw_format: desc.tex_external,
+            pixel_type: desc.data_type,
             framebuffer,
         });
         Ok(())
@@ -231,6 +234,7 @@ impl window::PresentationSurface<B> for Surface {
         let swapchain_image = native::SwapchainImage::new(
             self.renderbuffer.unwrap(),
             sc.raw_format,
+            sc.pixel_type,
             sc.extent,
             sc.channel,
         );
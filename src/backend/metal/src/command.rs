@@ -2404,6 +2404,18 @@ impl hal::queue::Queue<Backend> for Queue {
         }
     }
 
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        _command_buffers: Ic,
+        _wait_semaphores: Iw,
+        _signal_semaphores: Is,
+        _fence: Option<&mut native::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a CommandBuffer>,
+    {
+        unimplemented!()
+    }
+
     unsafe fn present(
         &mut self,
         _surface: &mut window::Surface,
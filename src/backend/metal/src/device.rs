@@ -988,6 +988,171 @@ impl Device {
             bpc: 8,
         }
     }
+
+    /// Write the descriptors into `set`, starting at `first_binding` and `array_offset`.
+    ///
+    /// Unlike `write_descriptor_set`, the lifetime of the set borrow is independent of
+    /// the one of the descriptors, so a set can be written several times from the same iterator.
+    unsafe fn write_descriptors<'a, I>(
+        &self,
+        set: &mut n::DescriptorSet,
+        first_binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        debug!("write_descriptor_set");
+        match *set {
+            n::DescriptorSet::Emulated {
+                ref pool,
+                ref layouts,
+                ref resources,
+            } => {
+                let mut counters = resources.map(|r| r.start);
+                let mut start = None; //TODO: can pre-compute this
+                for (i, layout) in layouts.iter().enumerate() {
+                    if layout.binding == first_binding && layout.array_index == array_offset {
+                        start = Some(i);
+                        break;
+                    }
+                    counters.add(layout.content);
+                }
+                let mut data = pool.write();
+
+                for (layout, descriptor) in layouts[start.unwrap()..].iter().zip(descriptors) {
+                    trace!("\t{:?}", layout);
+                    match descriptor {
+                        pso::Descriptor::Sampler(sam) => {
+                            debug_assert!(!layout
+                                .content
+                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER));
+                            data.samplers[counters.samplers as usize] = (
+                                layout.stages,
+                                Some(AsNative::from(sam.raw.as_ref().unwrap().as_ref())),
+                            );
+                        }
+                        pso::Descriptor::Image(view, il) => {
+                            data.textures[counters.textures as usize] = (
+                                layout.stages,
+                                Some(AsNative::from(view.texture.as_ref())),
+                                il,
+                            );
+                        }
+                        pso::Descriptor::CombinedImageSampler(view, il, sam) => {
+                            if !layout
+                                .content
+                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER)
+                            {
+                                data.samplers[counters.samplers as usize] = (
+                                    layout.stages,
+                                    Some(AsNative::from(sam.raw.as_ref().unwrap().as_ref())),
+                                );
+                            }
+                            data.textures[counters.textures as usize] = (
+                                layout.stages,
+                                Some(AsNative::from(view.texture.as_ref())),
+                                il,
+                            );
+                        }
+                        pso::Descriptor::TexelBuffer(view) => {
+                            data.textures[counters.textures as usize] = (
+                                layout.stages,
+                                Some(AsNative::from(view.raw.as_ref())),
+                                image::Layout::General,
+                            );
+                        }
+                        pso::Descriptor::Buffer(buf, ref sub) => {
+                            let (raw, range) = buf.as_bound();
+                            debug_assert!(
+                                range.start + sub.offset + sub.size.unwrap_or(0) <= range.end
+                            );
+                            data.buffers[counters.buffers as usize] = (
+                                layout.stages,
+                                Some(AsNative::from(raw)),
+                                range.start + sub.offset,
+                            );
+                        }
+                    }
+                    counters.add(layout.content);
+                }
+            }
+            n::DescriptorSet::ArgumentBuffer {
+                ref raw,
+                raw_offset,
+                ref pool,
+                ref range,
+                ref encoder,
+                ref bindings,
+                ..
+            } => {
+                debug_assert!(self.shared.private_caps.argument_buffers);
+
+                encoder.set_argument_buffer(raw, raw_offset);
+                let mut arg_index = {
+                    let binding = &bindings[&first_binding];
+                    debug_assert!((array_offset as usize) < binding.count);
+                    (binding.res_offset as NSUInteger) + (array_offset as NSUInteger)
+                };
+
+                for (data, descriptor) in pool.write().resources
+                    [range.start as usize + arg_index as usize..range.end as usize]
+                    .iter_mut()
+                    .zip(descriptors)
+                {
+                    match descriptor {
+                        pso::Descriptor::Sampler(sampler) => {
+                            debug_assert!(!bindings[&first_binding]
+                                .content
+                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER));
+                            encoder.set_sampler_state(arg_index, sampler.raw.as_ref().unwrap());
+                            arg_index += 1;
+                        }
+                        pso::Descriptor::Image(image, _layout) => {
+                            let tex_ref = image.texture.as_ref();
+                            encoder.set_texture(arg_index, tex_ref);
+                            data.ptr = (&**tex_ref).as_ptr();
+                            arg_index += 1;
+                        }
+                        pso::Descriptor::CombinedImageSampler(image, _il, sampler) => {
+                            let binding = &bindings[&first_binding];
+                            if !binding
+                                .content
+                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER)
+                            {
+                                //TODO: supporting arrays of combined image-samplers can be tricky.
+                                // We need to scan both sampler and image sections of the encoder
+                                // at the same time.
+                                assert!(
+                                    arg_index
+                                        < (binding.res_offset as NSUInteger)
+                                            + (binding.count as NSUInteger)
+                                );
+                                encoder.set_sampler_state(
+                                    arg_index + binding.count as NSUInteger,
+                                    sampler.raw.as_ref().unwrap(),
+                                );
+                            }
+                            let tex_ref = image.texture.as_ref();
+                            encoder.set_texture(arg_index, tex_ref);
+                            data.ptr = (&**tex_ref).as_ptr();
+                        }
+                        pso::Descriptor::TexelBuffer(view) => {
+                            encoder.set_texture(arg_index, &view.raw);
+                            data.ptr = (&**view.raw).as_ptr();
+                            arg_index += 1;
+                        }
+                        pso::Descriptor::Buffer(buffer, ref sub) => {
+                            let (buf_raw, buf_range) = buffer.as_bound();
+                            encoder.set_buffer(arg_index, buf_raw, buf_range.start + sub.offset);
+                            data.ptr = (&**buf_raw).as_ptr();
+                            arg_index += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl hal::device::Device<Backend> for Device {
@@ -1505,9 +1670,14 @@ impl hal::device::Device<Backend> for Device {
         debug!("create_graphics_pipeline {:#?}", pipeline_desc);
         let pipeline = metal::RenderPipelineDescriptor::new();
         let pipeline_layout = &pipeline_desc.layout;
-        let (rp_attachments, subpass) = {
-            let pass::Subpass { main_pass, index } = pipeline_desc.subpass;
-            (&main_pass.attachments, &main_pass.subpasses[index as usize])
+        let (rp_attachments, subpass) = match pipeline_desc.target {
+            pso::PipelineTarget::Subpass(pass::Subpass { main_pass, index }) => {
+                (&main_pass.attachments, &main_pass.subpasses[index as usize])
+            }
+            pso::PipelineTarget::Rendering { .. } => {
+                error!("Dynamic rendering is not supported");
+                return Err(pso::CreationError::UnsupportedPipeline);
+            }
         };
 
         let (desc_vertex_buffers, attributes, input_assembler, vs) =
@@ -2235,155 +2405,36 @@ impl hal::device::Device<Backend> for Device {
     where
         I: Iterator<Item = pso::Descriptor<'a, Backend>>,
     {
-        debug!("write_descriptor_set");
-        match *op.set {
-            n::DescriptorSet::Emulated {
-                ref pool,
-                ref layouts,
-                ref resources,
-            } => {
-                let mut counters = resources.map(|r| r.start);
-                let mut start = None; //TODO: can pre-compute this
-                for (i, layout) in layouts.iter().enumerate() {
-                    if layout.binding == op.binding && layout.array_index == op.array_offset {
-                        start = Some(i);
-                        break;
-                    }
-                    counters.add(layout.content);
-                }
-                let mut data = pool.write();
-
-                for (layout, descriptor) in layouts[start.unwrap()..].iter().zip(op.descriptors) {
-                    trace!("\t{:?}", layout);
-                    match descriptor {
-                        pso::Descriptor::Sampler(sam) => {
-                            debug_assert!(!layout
-                                .content
-                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER));
-                            data.samplers[counters.samplers as usize] = (
-                                layout.stages,
-                                Some(AsNative::from(sam.raw.as_ref().unwrap().as_ref())),
-                            );
-                        }
-                        pso::Descriptor::Image(view, il) => {
-                            data.textures[counters.textures as usize] = (
-                                layout.stages,
-                                Some(AsNative::from(view.texture.as_ref())),
-                                il,
-                            );
-                        }
-                        pso::Descriptor::CombinedImageSampler(view, il, sam) => {
-                            if !layout
-                                .content
-                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER)
-                            {
-                                data.samplers[counters.samplers as usize] = (
-                                    layout.stages,
-                                    Some(AsNative::from(sam.raw.as_ref().unwrap().as_ref())),
-                                );
-                            }
-                            data.textures[counters.textures as usize] = (
-                                layout.stages,
-                                Some(AsNative::from(view.texture.as_ref())),
-                                il,
-                            );
-                        }
-                        pso::Descriptor::TexelBuffer(view) => {
-                            data.textures[counters.textures as usize] = (
-                                layout.stages,
-                                Some(AsNative::from(view.raw.as_ref())),
-                                image::Layout::General,
-                            );
-                        }
-                        pso::Descriptor::Buffer(buf, ref sub) => {
-                            let (raw, range) = buf.as_bound();
-                            debug_assert!(
-                                range.start + sub.offset + sub.size.unwrap_or(0) <= range.end
-                            );
-                            data.buffers[counters.buffers as usize] = (
-                                layout.stages,
-                                Some(AsNative::from(raw)),
-                                range.start + sub.offset,
-                            );
-                        }
-                    }
-                    counters.add(layout.content);
-                }
-            }
-            n::DescriptorSet::ArgumentBuffer {
-                ref raw,
-                raw_offset,
-                ref pool,
-                ref range,
-                ref encoder,
-                ref bindings,
-                ..
-            } => {
-                debug_assert!(self.shared.private_caps.argument_buffers);
+        self.write_descriptors(op.set, op.binding, op.array_offset, op.descriptors);
+    }
 
-                encoder.set_argument_buffer(raw, raw_offset);
-                let mut arg_index = {
-                    let binding = &bindings[&op.binding];
-                    debug_assert!((op.array_offset as usize) < binding.count);
-                    (binding.res_offset as NSUInteger) + (op.array_offset as NSUInteger)
-                };
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        _layout: &n::DescriptorSetLayout,
+        entries: I,
+    ) -> Result<Vec<pso::DescriptorUpdateTemplateEntry>, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorUpdateTemplateEntry>,
+    {
+        Ok(entries.collect())
+    }
 
-                for (data, descriptor) in pool.write().resources
-                    [range.start as usize + arg_index as usize..range.end as usize]
-                    .iter_mut()
-                    .zip(op.descriptors)
-                {
-                    match descriptor {
-                        pso::Descriptor::Sampler(sampler) => {
-                            debug_assert!(!bindings[&op.binding]
-                                .content
-                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER));
-                            encoder.set_sampler_state(arg_index, sampler.raw.as_ref().unwrap());
-                            arg_index += 1;
-                        }
-                        pso::Descriptor::Image(image, _layout) => {
-                            let tex_ref = image.texture.as_ref();
-                            encoder.set_texture(arg_index, tex_ref);
-                            data.ptr = (&**tex_ref).as_ptr();
-                            arg_index += 1;
-                        }
-                        pso::Descriptor::CombinedImageSampler(image, _il, sampler) => {
-                            let binding = &bindings[&op.binding];
-                            if !binding
-                                .content
-                                .contains(n::DescriptorContent::IMMUTABLE_SAMPLER)
-                            {
-                                //TODO: supporting arrays of combined image-samplers can be tricky.
-                                // We need to scan both sampler and image sections of the encoder
-                                // at the same time.
-                                assert!(
-                                    arg_index
-                                        < (binding.res_offset as NSUInteger)
-                                            + (binding.count as NSUInteger)
-                                );
-                                encoder.set_sampler_state(
-                                    arg_index + binding.count as NSUInteger,
-                                    sampler.raw.as_ref().unwrap(),
-                                );
-                            }
-                            let tex_ref = image.texture.as_ref();
-                            encoder.set_texture(arg_index, tex_ref);
-                            data.ptr = (&**tex_ref).as_ptr();
-                        }
-                        pso::Descriptor::TexelBuffer(view) => {
-                            encoder.set_texture(arg_index, &view.raw);
-                            data.ptr = (&**view.raw).as_ptr();
-                            arg_index += 1;
-                        }
-                        pso::Descriptor::Buffer(buffer, ref sub) => {
-                            let (buf_raw, buf_range) = buffer.as_bound();
-                            encoder.set_buffer(arg_index, buf_raw, buf_range.start + sub.offset);
-                            data.ptr = (&**buf_raw).as_ptr();
-                            arg_index += 1;
-                        }
-                    }
-                }
-            }
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        set: &mut n::DescriptorSet,
+        template: &Vec<pso::DescriptorUpdateTemplateEntry>,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        let mut descriptors = descriptors;
+        for entry in template {
+            self.write_descriptors(
+                set,
+                entry.binding,
+                entry.array_offset,
+                descriptors.by_ref().take(entry.count),
+            );
         }
     }
 
@@ -2395,6 +2446,12 @@ impl hal::device::Device<Backend> for Device {
 
     unsafe fn destroy_descriptor_set_layout(&self, _layout: n::DescriptorSetLayout) {}
 
+    unsafe fn destroy_descriptor_update_template(
+        &self,
+        _template: Vec<pso::DescriptorUpdateTemplateEntry>,
+    ) {
+    }
+
     unsafe fn destroy_pipeline_layout(&self, _pipeline_layout: n::PipelineLayout) {}
 
     unsafe fn destroy_shader_module(&self, _module: n::ShaderModule) {}
@@ -2409,6 +2466,64 @@ impl hal::device::Device<Backend> for Device {
 
     unsafe fn destroy_semaphore(&self, _semaphore: n::Semaphore) {}
 
+    fn create_timeline_semaphore(
+        &self,
+        _initial_value: u64,
+    ) -> Result<n::Semaphore, d::OutOfMemory> {
+        unimplemented!()
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        _semaphore: &n::Semaphore,
+    ) -> Result<u64, d::WaitError> {
+        unimplemented!()
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        _semaphores: I,
+        _wait: d::WaitFor,
+        _timeout_ns: u64,
+    ) -> Result<bool, d::WaitError>
+    where
+        I: Iterator<Item = (&'a n::Semaphore, u64)>,
+    {
+        unimplemented!()
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        _semaphore: &n::Semaphore,
+        _value: u64,
+    ) -> Result<(), d::OutOfMemory> {
+        unimplemented!()
+    }
+
+    fn create_exportable_semaphore(
+        &self,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<n::Semaphore, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        _semaphore: &n::Semaphore,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        _semaphore: &mut n::Semaphore,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
     unsafe fn allocate_memory(
         &self,
         memory_type: hal::MemoryTypeId,
@@ -2442,6 +2557,41 @@ impl hal::device::Device<Backend> for Device {
         Ok(n::Memory::new(heap, size))
     }
 
+    unsafe fn allocate_exportable_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<n::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        _memory: &n::Memory,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_memory(
+        &self,
+        _memory_type: hal::MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<n::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        Ok(0)
+    }
+
     unsafe fn free_memory(&self, memory: n::Memory) {
         debug!("free_memory of size {}", memory.size);
         if let n::MemoryHeap::Public(_, ref cpu_buffer) = memory.heap {
@@ -2573,6 +2723,10 @@ impl hal::device::Device<Backend> for Device {
         Ok(())
     }
 
+    unsafe fn get_buffer_device_address(&self, _buffer: &n::Buffer) -> buffer::DeviceAddress {
+        unreachable!("Feature BUFFER_DEVICE_ADDRESS is not supported")
+    }
+
     unsafe fn destroy_buffer(&self, buffer: n::Buffer) {
         if let n::Buffer::Bound { raw, range, .. } = buffer {
             debug!(
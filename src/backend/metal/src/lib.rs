@@ -473,6 +473,7 @@ impl hal::Backend for Backend {
     type DescriptorSetLayout = native::DescriptorSetLayout;
     type DescriptorPool = native::DescriptorPool;
     type DescriptorSet = native::DescriptorSet;
+    type DescriptorUpdateTemplate = Vec<hal::pso::DescriptorUpdateTemplateEntry>;
 
     type Fence = native::Fence;
     type Semaphore = native::Semaphore;
@@ -3,19 +3,20 @@ use ash::{
     vk,
 };
 use smallvec::SmallVec;
-use std::{collections::hash_map::Entry, ffi::CString, mem, ops::Range, slice, sync::Arc};
+use std::{collections::hash_map::Entry, ffi::CString, mem, ops::Range, ptr, slice, sync::Arc};
 
 use inplace_it::inplace_or_alloc_from_iter;
 
 use crate::{
-    conv, native as n, Backend, DebugMessenger, ExtensionFn, RawDevice, ROUGH_MAX_ATTACHMENT_COUNT,
+    conv, device::DescriptorWrites, ext, native as n, Backend, DebugMessenger, ExtensionFn,
+    RawDevice, ROUGH_MAX_ATTACHMENT_COUNT,
 };
 use hal::{
     buffer, command as com,
-    format::Aspects,
+    format::{Aspects, ChannelType},
     image::{Filter, Layout, SubresourceRange},
-    memory, pso, query, DrawCount, IndexCount, IndexType, InstanceCount, TaskCount, VertexCount,
-    VertexOffset, WorkGroupCount,
+    memory, pass, pso, query, DrawCount, IndexCount, IndexType, InstanceCount, TaskCount,
+    VertexCount, VertexOffset, WorkGroupCount,
 };
 
 #[derive(Debug)]
@@ -32,6 +33,42 @@ fn debug_color(color: u32) -> [f32; 4] {
     result
 }
 
+fn map_rendering_attachment(
+    at: &com::RenderingAttachmentInfo<Backend>,
+    ops: &pass::AttachmentOps,
+) -> ext::RenderingAttachmentInfoKHR {
+    let (resolve_mode, resolve_image_view, resolve_image_layout) = match at.resolve {
+        Some((view, layout)) => {
+            // Integer and depth-stencil attachments can't be averaged.
+            let mode = match at.format.base_format().1 {
+                ChannelType::Uint | ChannelType::Sint => vk::ResolveModeFlags::SAMPLE_ZERO,
+                _ if at.format.is_depth() || at.format.is_stencil() => {
+                    vk::ResolveModeFlags::SAMPLE_ZERO
+                }
+                _ => vk::ResolveModeFlags::AVERAGE,
+            };
+            (mode, view.raw, conv::map_image_layout(layout))
+        }
+        None => (
+            vk::ResolveModeFlags::NONE,
+            vk::ImageView::null(),
+            vk::ImageLayout::UNDEFINED,
+        ),
+    };
+
+    ext::RenderingAttachmentInfoKHR {
+        image_view: at.image_view.raw,
+        image_layout: conv::map_image_layout(at.layout),
+        resolve_mode,
+        resolve_image_view,
+        resolve_image_layout,
+        load_op: conv::map_attachment_load_op(ops.load),
+        store_op: conv::map_attachment_store_op(ops.store),
+        clear_value: unsafe { mem::transmute(at.clear_value) },
+        ..Default::default()
+    }
+}
+
 fn map_subpass_contents(contents: com::SubpassContents) -> vk::SubpassContents {
     match contents {
         com::SubpassContents::Inline => vk::SubpassContents::INLINE,
@@ -174,6 +211,47 @@ impl CommandBuffer {
             });
         });
     }
+
+    unsafe fn push_descriptor_set<'a, I>(
+        &mut self,
+        bind_point: vk::PipelineBindPoint,
+        layout: &n::PipelineLayout,
+        set_index: usize,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        let push_descriptor = self
+            .device
+            .extension_fns
+            .push_descriptor
+            .as_ref()
+            .expect("Feature PUSH_DESCRIPTOR must be enabled to call push_descriptor_set")
+            .unwrap_extension();
+
+        let descriptors = descriptors.collect::<SmallVec<[_; 4]>>();
+        let mut ranges = Vec::new();
+        n::DescriptorWriteRange::split(
+            &layout.set_bindings[set_index],
+            binding,
+            array_offset,
+            descriptors.len(),
+            &mut ranges,
+        );
+
+        // The destination set is ignored for push descriptors.
+        let mut writes = DescriptorWrites::default();
+        writes.add(vk::DescriptorSet::null(), &ranges, descriptors.into_iter());
+        push_descriptor.cmd_push_descriptor_set(
+            self.raw,
+            bind_point,
+            layout.raw,
+            set_index as u32,
+            writes.finish(),
+        );
+    }
 }
 
 impl com::CommandBuffer<Backend> for CommandBuffer {
@@ -305,6 +383,54 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         self.device.raw.cmd_end_render_pass(self.raw);
     }
 
+    unsafe fn begin_rendering(&mut self, info: &com::RenderingInfo<Backend>) {
+        let dynamic_rendering = self
+            .device
+            .extension_fns
+            .dynamic_rendering
+            .as_ref()
+            .expect("Feature DYNAMIC_RENDERING must be enabled to call begin_rendering")
+            .unwrap_extension();
+
+        let colors = info
+            .colors
+            .iter()
+            .map(|at| map_rendering_attachment(at, &at.ops))
+            .collect::<SmallVec<[_; ROUGH_MAX_ATTACHMENT_COUNT]>>();
+        let depth = info
+            .depth_stencil
+            .filter(|at| at.format.is_depth())
+            .map(|at| map_rendering_attachment(at, &at.ops));
+        let stencil = info
+            .depth_stencil
+            .filter(|at| at.format.is_stencil())
+            .map(|at| map_rendering_attachment(at, &at.stencil_ops));
+
+        let raw_info = ext::RenderingInfoKHR {
+            render_area: conv::map_rect(&info.render_area),
+            layer_count: info.layers as u32,
+            color_attachment_count: colors.len() as u32,
+            p_color_attachments: colors.as_ptr(),
+            p_depth_attachment: depth.as_ref().map_or(ptr::null(), |at| at as *const _),
+            p_stencil_attachment: stencil.as_ref().map_or(ptr::null(), |at| at as *const _),
+            ..Default::default()
+        };
+
+        dynamic_rendering.cmd_begin_rendering_khr(self.raw, &raw_info);
+    }
+
+    unsafe fn end_rendering(&mut self) {
+        let dynamic_rendering = self
+            .device
+            .extension_fns
+            .dynamic_rendering
+            .as_ref()
+            .expect("Feature DYNAMIC_RENDERING must be enabled to call end_rendering")
+            .unwrap_extension();
+
+        dynamic_rendering.cmd_end_rendering_khr(self.raw);
+    }
+
     unsafe fn pipeline_barrier<'a, T>(
         &mut self,
         stages: Range<pso::PipelineStage>,
@@ -636,6 +762,26 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         );
     }
 
+    unsafe fn push_graphics_descriptor_set<'a, I>(
+        &mut self,
+        layout: &n::PipelineLayout,
+        set_index: usize,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        self.push_descriptor_set(
+            vk::PipelineBindPoint::GRAPHICS,
+            layout,
+            set_index,
+            binding,
+            array_offset,
+            descriptors,
+        );
+    }
+
     unsafe fn bind_compute_pipeline(&mut self, pipeline: &n::ComputePipeline) {
         self.device
             .raw
@@ -661,6 +807,26 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         );
     }
 
+    unsafe fn push_compute_descriptor_set<'a, I>(
+        &mut self,
+        layout: &n::PipelineLayout,
+        set_index: usize,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        self.push_descriptor_set(
+            vk::PipelineBindPoint::COMPUTE,
+            layout,
+            set_index,
+            binding,
+            array_offset,
+            descriptors,
+        );
+    }
+
     unsafe fn dispatch(&mut self, count: WorkGroupCount) {
         self.device
             .raw
@@ -206,6 +206,14 @@ pub fn map_stage_flags(stages: pso::ShaderStageFlags) -> vk::ShaderStageFlags {
     vk::ShaderStageFlags::from_raw(stages.bits())
 }
 
+pub fn map_vk_stage_flags(stages: vk::ShaderStageFlags) -> pso::ShaderStageFlags {
+    pso::ShaderStageFlags::from_bits_truncate(stages.as_raw())
+}
+
+pub fn map_vk_subgroup_operations(flags: vk::SubgroupFeatureFlags) -> hal::SubgroupOperations {
+    hal::SubgroupOperations::from_bits_truncate(flags.as_raw())
+}
+
 pub fn map_filter(filter: image::Filter) -> vk::Filter {
     vk::Filter::from_raw(filter as i32)
 }
@@ -611,3 +619,52 @@ pub fn map_vk_memory_heap_flags(flags: vk::MemoryHeapFlags) -> hal::memory::Heap
 
     hal_flags
 }
+
+pub fn map_external_memory_handle_type(
+    handle_type: hal::external::HandleType,
+) -> vk::ExternalMemoryHandleTypeFlags {
+    use hal::external::HandleType;
+    match handle_type {
+        HandleType::OpaqueFd => {
+            vk::ExternalMemoryHandleTypeFlags::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD
+        }
+        HandleType::DmaBuf => {
+            vk::ExternalMemoryHandleTypeFlags::EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF
+        }
+    }
+}
+
+pub fn map_vk_external_memory_features(
+    flags: vk::ExternalMemoryFeatureFlags,
+) -> hal::external::ExternalFeatures {
+    use hal::external::ExternalFeatures;
+    let mut features = ExternalFeatures::empty();
+
+    if flags.contains(vk::ExternalMemoryFeatureFlags::EXTERNAL_MEMORY_FEATURE_EXPORTABLE) {
+        features |= ExternalFeatures::EXPORTABLE;
+    }
+    if flags.contains(vk::ExternalMemoryFeatureFlags::EXTERNAL_MEMORY_FEATURE_IMPORTABLE) {
+        features |= ExternalFeatures::IMPORTABLE;
+    }
+    if flags.contains(vk::ExternalMemoryFeatureFlags::EXTERNAL_MEMORY_FEATURE_DEDICATED_ONLY) {
+        features |= ExternalFeatures::DEDICATED_ONLY;
+    }
+
+    features
+}
+
+pub fn map_vk_external_semaphore_features(
+    flags: vk::ExternalSemaphoreFeatureFlags,
+) -> hal::external::ExternalFeatures {
+    use hal::external::ExternalFeatures;
+    let mut features = ExternalFeatures::empty();
+
+    if flags.contains(vk::ExternalSemaphoreFeatureFlags::EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE) {
+        features |= ExternalFeatures::EXPORTABLE;
+    }
+    if flags.contains(vk::ExternalSemaphoreFeatureFlags::EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE) {
+        features |= ExternalFeatures::IMPORTABLE;
+    }
+
+    features
+}
@@ -1,5 +1,9 @@
 use arrayvec::ArrayVec;
-use ash::{extensions::khr, version::DeviceV1_0, vk};
+use ash::{
+    extensions::khr,
+    version::{DeviceV1_0, DeviceV1_2},
+    vk,
+};
 use inplace_it::inplace_or_alloc_from_iter;
 use smallvec::SmallVec;
 
@@ -14,7 +18,10 @@ use hal::{
 
 use std::{ffi::CString, marker::PhantomData, mem, ops::Range, ptr, sync::Arc};
 
-use crate::{command as cmd, conv, native as n, pool::RawCommandPool, window as w, Backend as B};
+use crate::{
+    command as cmd, conv, ext, native as n, pool::RawCommandPool, window as w, Backend as B,
+    ExtensionFn,
+};
 
 #[derive(Debug, Default)]
 struct GraphicsPipelineInfoBuf<'a> {
@@ -436,6 +443,130 @@ impl<'a> ComputePipelineInfoBuf<'a> {
     }
 }
 
+/// Vulkan descriptor writes, along with the storage of the infos they point to.
+#[derive(Default)]
+pub(crate) struct DescriptorWrites {
+    raw: Vec<vk::WriteDescriptorSet>,
+    image_infos: Vec<vk::DescriptorImageInfo>,
+    buffer_infos: Vec<vk::DescriptorBufferInfo>,
+    texel_buffer_views: Vec<vk::BufferView>,
+}
+
+impl DescriptorWrites {
+    /// Add a write for each of the ranges into `dst_set`, taking the descriptors in order.
+    pub(crate) fn add<'a, I>(
+        &mut self,
+        dst_set: vk::DescriptorSet,
+        ranges: &[n::DescriptorWriteRange],
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+        let mut descriptors = descriptors;
+        for range in ranges {
+            let mut count = 0;
+            // The pointers are patched in `finish`, once all the storage is allocated.
+            let p_image_info = self.image_infos.len() as *const vk::DescriptorImageInfo;
+            let p_buffer_info = self.buffer_infos.len() as *const vk::DescriptorBufferInfo;
+            let p_texel_buffer_view = self.texel_buffer_views.len() as *const vk::BufferView;
+
+            for descriptor in descriptors.by_ref().take(range.count) {
+                count += 1;
+                match descriptor {
+                    pso::Descriptor::Sampler(sampler) => {
+                        self.image_infos.push(
+                            vk::DescriptorImageInfo::builder()
+                                .sampler(sampler.0)
+                                .image_view(vk::ImageView::null())
+                                .image_layout(vk::ImageLayout::GENERAL)
+                                .build(),
+                        );
+                    }
+                    pso::Descriptor::Image(view, layout) => {
+                        self.image_infos.push(
+                            vk::DescriptorImageInfo::builder()
+                                .sampler(vk::Sampler::null())
+                                .image_view(view.raw)
+                                .image_layout(conv::map_image_layout(layout))
+                                .build(),
+                        );
+                    }
+                    pso::Descriptor::CombinedImageSampler(view, layout, sampler) => {
+                        self.image_infos.push(
+                            vk::DescriptorImageInfo::builder()
+                                .sampler(sampler.0)
+                                .image_view(view.raw)
+                                .image_layout(conv::map_image_layout(layout))
+                                .build(),
+                        );
+                    }
+                    pso::Descriptor::Buffer(buffer, ref sub) => {
+                        self.buffer_infos.push(
+                            vk::DescriptorBufferInfo::builder()
+                                .buffer(buffer.raw)
+                                .offset(sub.offset)
+                                .range(sub.size.unwrap_or(vk::WHOLE_SIZE))
+                                .build(),
+                        );
+                    }
+                    pso::Descriptor::TexelBuffer(view) => {
+                        self.texel_buffer_views.push(view.raw);
+                    }
+                }
+            }
+
+            if count != 0 {
+                self.raw.push(vk::WriteDescriptorSet {
+                    s_type: vk::StructureType::WRITE_DESCRIPTOR_SET,
+                    p_next: ptr::null(),
+                    dst_set,
+                    dst_binding: range.binding,
+                    dst_array_element: range.array_offset as _,
+                    descriptor_count: count,
+                    descriptor_type: range.ty,
+                    p_image_info,
+                    p_buffer_info,
+                    p_texel_buffer_view,
+                });
+            }
+        }
+    }
+
+    /// Patch the pointers now that we have all the storage allocated, and return the writes.
+    pub(crate) fn finish(&mut self) -> &[vk::WriteDescriptorSet] {
+        for raw in self.raw.iter_mut() {
+            use crate::vk::DescriptorType as Dt;
+            match raw.descriptor_type {
+                Dt::SAMPLER
+                | Dt::SAMPLED_IMAGE
+                | Dt::STORAGE_IMAGE
+                | Dt::COMBINED_IMAGE_SAMPLER
+                | Dt::INPUT_ATTACHMENT => {
+                    raw.p_buffer_info = ptr::null();
+                    raw.p_texel_buffer_view = ptr::null();
+                    raw.p_image_info = self.image_infos[raw.p_image_info as usize..].as_ptr();
+                }
+                Dt::UNIFORM_TEXEL_BUFFER | Dt::STORAGE_TEXEL_BUFFER => {
+                    raw.p_buffer_info = ptr::null();
+                    raw.p_image_info = ptr::null();
+                    raw.p_texel_buffer_view =
+                        self.texel_buffer_views[raw.p_texel_buffer_view as usize..].as_ptr();
+                }
+                Dt::UNIFORM_BUFFER
+                | Dt::STORAGE_BUFFER
+                | Dt::STORAGE_BUFFER_DYNAMIC
+                | Dt::UNIFORM_BUFFER_DYNAMIC => {
+                    raw.p_image_info = ptr::null();
+                    raw.p_texel_buffer_view = ptr::null();
+                    raw.p_buffer_info = self.buffer_infos[raw.p_buffer_info as usize..].as_ptr();
+                }
+                _ => panic!("unknown descriptor type"),
+            }
+        }
+        &self.raw
+    }
+}
+
 impl d::Device<B> for super::Device {
     unsafe fn allocate_memory(
         &self,
@@ -446,7 +577,7 @@ impl d::Device<B> for super::Device {
             .allocation_size(size)
             .memory_type_index(self.get_ash_memory_type_index(mem_type));
 
-        let result = self.shared.raw.allocate_memory(&info, None);
+        let result = self.allocate_raw_memory(info);
 
         match result {
             Ok(memory) => Ok(n::Memory { raw: memory }),
@@ -613,7 +744,11 @@ impl d::Device<B> for super::Device {
         Is: Iterator<Item = &'a n::DescriptorSetLayout>,
         Ic: Iterator<Item = (pso::ShaderStageFlags, Range<u32>)>,
     {
-        let vk_set_layouts_iter = set_layouts.map(|set| set.raw);
+        let mut set_bindings = Vec::new();
+        let vk_set_layouts_iter = set_layouts.map(|set| {
+            set_bindings.push(Arc::clone(&set.bindings));
+            set.raw
+        });
 
         let push_constant_ranges_iter =
             push_constant_ranges.map(|(s, ref r)| vk::PushConstantRange {
@@ -637,7 +772,7 @@ impl d::Device<B> for super::Device {
         });
 
         match result {
-            Ok(raw) => Ok(n::PipelineLayout { raw }),
+            Ok(raw) => Ok(n::PipelineLayout { raw, set_bindings }),
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device),
             _ => unreachable!(),
@@ -719,6 +854,8 @@ impl d::Device<B> for super::Device {
     ) -> Result<n::GraphicsPipeline, pso::CreationError> {
         debug!("create_graphics_pipeline {:?}", desc);
         let buf = GraphicsPipelineInfoBuf::new(desc, &self.shared);
+        let mut rendering_formats = SmallVec::<[vk::Format; 4]>::new();
+        let mut rendering_info = ext::PipelineRenderingCreateInfoKHR::default();
 
         let info = {
             let (base_handle, base_index) = match desc.parent {
@@ -757,17 +894,41 @@ impl d::Device<B> for super::Device {
                 Some(t) => builder.tessellation_state(t),
                 None => builder,
             };
-            builder
+            let builder = builder
                 .viewport_state(&buf.viewport_state)
                 .multisample_state(&buf.multisample_state)
                 .depth_stencil_state(&buf.depth_stencil_state)
                 .color_blend_state(&buf.color_blend_state)
                 .dynamic_state(&buf.pipeline_dynamic_state)
                 .layout(desc.layout.raw)
-                .render_pass(desc.subpass.main_pass.raw)
-                .subpass(desc.subpass.index as _)
                 .base_pipeline_handle(base_handle)
-                .base_pipeline_index(base_index)
+                .base_pipeline_index(base_index);
+            match desc.target {
+                pso::PipelineTarget::Subpass(ref subpass) => builder
+                    .render_pass(subpass.main_pass.raw)
+                    .subpass(subpass.index as _),
+                pso::PipelineTarget::Rendering {
+                    colors,
+                    depth_stencil,
+                } => {
+                    assert!(
+                        self.shared.features.contains(Features::DYNAMIC_RENDERING),
+                        "Feature DYNAMIC_RENDERING must be enabled to create pipelines without a subpass"
+                    );
+                    rendering_formats.extend(colors.iter().map(|&format| conv::map_format(format)));
+                    rendering_info.color_attachment_count = rendering_formats.len() as u32;
+                    rendering_info.p_color_attachment_formats = rendering_formats.as_ptr();
+                    if let Some(format) = depth_stencil {
+                        if format.is_depth() {
+                            rendering_info.depth_attachment_format = conv::map_format(format);
+                        }
+                        if format.is_stencil() {
+                            rendering_info.stencil_attachment_format = conv::map_format(format);
+                        }
+                    }
+                    builder.push_next(&mut rendering_info)
+                }
+            }
         };
 
         let mut pipeline = vk::Pipeline::null();
@@ -794,14 +955,27 @@ impl d::Device<B> for super::Device {
     ) -> Result<n::ComputePipeline, pso::CreationError> {
         debug!("create_graphics_pipeline {:?}", desc);
         let buf = ComputePipelineInfoBuf::new(desc);
+        let mut required_subgroup_size_info =
+            vk::PipelineShaderStageRequiredSubgroupSizeCreateInfoEXT::builder();
 
         let info = {
-            let stage = vk::PipelineShaderStageCreateInfo::builder()
+            let mut stage = vk::PipelineShaderStageCreateInfo::builder()
                 .flags(vk::PipelineShaderStageCreateFlags::empty())
                 .stage(vk::ShaderStageFlags::COMPUTE)
                 .module(desc.shader.module.raw)
                 .name(buf.c_string.as_c_str())
                 .specialization_info(&buf.specialization);
+            if let Some(size) = desc.required_subgroup_size {
+                assert!(
+                    self.shared
+                        .features
+                        .contains(Features::SUBGROUP_SIZE_CONTROL),
+                    "Feature SUBGROUP_SIZE_CONTROL must be enabled to require a subgroup size"
+                );
+                required_subgroup_size_info =
+                    required_subgroup_size_info.required_subgroup_size(size);
+                stage = stage.push_next(&mut required_subgroup_size_info);
+            }
 
             let (base_handle, base_index) = match desc.parent {
                 pso::BasePipeline::Pipeline(pipeline) => (pipeline.0, -1),
@@ -1053,6 +1227,23 @@ impl d::Device<B> for super::Device {
         }
     }
 
+    unsafe fn get_buffer_device_address(&self, buffer: &n::Buffer) -> buffer::DeviceAddress {
+        let info = vk::BufferDeviceAddressInfo::builder().buffer(buffer.raw);
+        match self
+            .shared
+            .extension_fns
+            .buffer_device_address
+            .as_ref()
+            .expect(
+                "Feature BUFFER_DEVICE_ADDRESS must be enabled to call get_buffer_device_address",
+            ) {
+            ExtensionFn::Extension(t) => {
+                t.get_buffer_device_address_khr(self.shared.raw.handle(), &*info)
+            }
+            ExtensionFn::Promoted => self.shared.raw.get_buffer_device_address(&info),
+        }
+    }
+
     unsafe fn create_buffer_view(
         &self,
         buffer: &n::Buffer,
@@ -1253,181 +1444,84 @@ impl d::Device<B> for super::Device {
         I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
         J: Iterator<Item = &'a n::Sampler>,
     {
-        let vk_immutable_samplers_iter = immutable_samplers.map(|is| is.0);
-        let mut sampler_offset = 0;
-
-        let mut bindings = binding_iter.collect::<Vec<_>>();
-        // Sorting will come handy in `write_descriptor_sets`.
-        bindings.sort_by_key(|b| b.binding);
-
-        let result = inplace_or_alloc_from_iter(vk_immutable_samplers_iter, |immutable_samplers| {
-            let raw_bindings_iter = bindings.iter().map(|b| vk::DescriptorSetLayoutBinding {
-                binding: b.binding,
-                descriptor_type: conv::map_descriptor_type(b.ty),
-                descriptor_count: b.count as _,
-                stage_flags: conv::map_stage_flags(b.stage_flags),
-                p_immutable_samplers: if b.immutable_samplers {
-                    let slice = &immutable_samplers[sampler_offset..];
-                    sampler_offset += b.count;
-                    slice.as_ptr()
-                } else {
-                    ptr::null()
-                },
-            });
-
-            inplace_or_alloc_from_iter(raw_bindings_iter, |raw_bindings| {
-                // TODO raw_bindings doesnt implement fmt::Debug
-                // debug!("create_descriptor_set_layout {:?}", raw_bindings);
-
-                let info = vk::DescriptorSetLayoutCreateInfo::builder()
-                    .flags(vk::DescriptorSetLayoutCreateFlags::empty())
-                    .bindings(&raw_bindings);
-
-                self.shared.raw.create_descriptor_set_layout(&info, None)
-            })
-        });
+        self.create_descriptor_set_layout_with_flags(
+            binding_iter,
+            immutable_samplers,
+            vk::DescriptorSetLayoutCreateFlags::empty(),
+        )
+    }
 
-        match result {
-            Ok(layout) => Ok(n::DescriptorSetLayout {
-                raw: layout,
-                bindings: Arc::new(bindings),
-            }),
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
-            _ => unreachable!(),
-        }
+    unsafe fn create_push_descriptor_set_layout<'a, I, J>(
+        &self,
+        binding_iter: I,
+        immutable_samplers: J,
+    ) -> Result<n::DescriptorSetLayout, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
+        J: Iterator<Item = &'a n::Sampler>,
+    {
+        assert!(
+            self.shared.features.contains(Features::PUSH_DESCRIPTOR),
+            "Feature PUSH_DESCRIPTOR must be enabled to call create_push_descriptor_set_layout"
+        );
+        self.create_descriptor_set_layout_with_flags(
+            binding_iter,
+            immutable_samplers,
+            vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR,
+        )
     }
 
     unsafe fn write_descriptor_set<'a, I>(&self, op: pso::DescriptorSetWrite<'a, B, I>)
     where
         I: Iterator<Item = pso::Descriptor<'a, B>>,
     {
-        let descriptors = op.descriptors;
-        let mut raw_writes =
-            Vec::<vk::WriteDescriptorSet>::with_capacity(descriptors.size_hint().0);
-        let mut image_infos = Vec::new();
-        let mut buffer_infos = Vec::new();
-        let mut texel_buffer_views = Vec::new();
-
-        // gfx-hal allows the type and stages to be different between the descriptor
-        // in a single write, while Vulkan requires them to be the same.
-        let mut last_type = vk::DescriptorType::SAMPLER;
-        let mut last_stages = pso::ShaderStageFlags::empty();
-
-        let mut binding_pos = op
-            .set
-            .bindings
-            .binary_search_by_key(&op.binding, |b| b.binding)
-            .expect("Descriptor set writes don't match the set layout!");
-        let mut array_offset = op.array_offset;
-
-        for descriptor in descriptors {
-            let layout_binding = &op.set.bindings[binding_pos];
-            array_offset += 1;
-            if array_offset == layout_binding.count {
-                array_offset = 0;
-                binding_pos += 1;
-            }
-
-            let descriptor_type = conv::map_descriptor_type(layout_binding.ty);
-            if descriptor_type == last_type && layout_binding.stage_flags == last_stages {
-                raw_writes.last_mut().unwrap().descriptor_count += 1;
-            } else {
-                last_type = descriptor_type;
-                last_stages = layout_binding.stage_flags;
-                raw_writes.push(vk::WriteDescriptorSet {
-                    s_type: vk::StructureType::WRITE_DESCRIPTOR_SET,
-                    p_next: ptr::null(),
-                    dst_set: op.set.raw,
-                    dst_binding: layout_binding.binding,
-                    dst_array_element: if layout_binding.binding == op.binding {
-                        op.array_offset as _
-                    } else {
-                        0
-                    },
-                    descriptor_count: 1,
-                    descriptor_type,
-                    p_image_info: image_infos.len() as _,
-                    p_buffer_info: buffer_infos.len() as _,
-                    p_texel_buffer_view: texel_buffer_views.len() as _,
-                });
-            }
+        let descriptors = op.descriptors.collect::<SmallVec<[_; 4]>>();
+        let mut ranges = Vec::new();
+        n::DescriptorWriteRange::split(
+            &op.set.bindings,
+            op.binding,
+            op.array_offset,
+            descriptors.len(),
+            &mut ranges,
+        );
 
-            match descriptor {
-                pso::Descriptor::Sampler(sampler) => {
-                    image_infos.push(
-                        vk::DescriptorImageInfo::builder()
-                            .sampler(sampler.0)
-                            .image_view(vk::ImageView::null())
-                            .image_layout(vk::ImageLayout::GENERAL)
-                            .build(),
-                    );
-                }
-                pso::Descriptor::Image(view, layout) => {
-                    image_infos.push(
-                        vk::DescriptorImageInfo::builder()
-                            .sampler(vk::Sampler::null())
-                            .image_view(view.raw)
-                            .image_layout(conv::map_image_layout(layout))
-                            .build(),
-                    );
-                }
-                pso::Descriptor::CombinedImageSampler(view, layout, sampler) => {
-                    image_infos.push(
-                        vk::DescriptorImageInfo::builder()
-                            .sampler(sampler.0)
-                            .image_view(view.raw)
-                            .image_layout(conv::map_image_layout(layout))
-                            .build(),
-                    );
-                }
-                pso::Descriptor::Buffer(buffer, ref sub) => {
-                    buffer_infos.push(
-                        vk::DescriptorBufferInfo::builder()
-                            .buffer(buffer.raw)
-                            .offset(sub.offset)
-                            .range(sub.size.unwrap_or(vk::WHOLE_SIZE))
-                            .build(),
-                    );
-                }
-                pso::Descriptor::TexelBuffer(view) => {
-                    texel_buffer_views.push(view.raw);
-                }
-            }
-        }
+        let mut writes = DescriptorWrites::default();
+        writes.add(op.set.raw, &ranges, descriptors.into_iter());
+        self.shared.raw.update_descriptor_sets(writes.finish(), &[]);
+    }
 
-        // Patch the pointers now that we have all the storage allocated.
-        for raw in raw_writes.iter_mut() {
-            use crate::vk::DescriptorType as Dt;
-            match raw.descriptor_type {
-                Dt::SAMPLER
-                | Dt::SAMPLED_IMAGE
-                | Dt::STORAGE_IMAGE
-                | Dt::COMBINED_IMAGE_SAMPLER
-                | Dt::INPUT_ATTACHMENT => {
-                    raw.p_buffer_info = ptr::null();
-                    raw.p_texel_buffer_view = ptr::null();
-                    raw.p_image_info = image_infos[raw.p_image_info as usize..].as_ptr();
-                }
-                Dt::UNIFORM_TEXEL_BUFFER | Dt::STORAGE_TEXEL_BUFFER => {
-                    raw.p_buffer_info = ptr::null();
-                    raw.p_image_info = ptr::null();
-                    raw.p_texel_buffer_view =
-                        texel_buffer_views[raw.p_texel_buffer_view as usize..].as_ptr();
-                }
-                Dt::UNIFORM_BUFFER
-                | Dt::STORAGE_BUFFER
-                | Dt::STORAGE_BUFFER_DYNAMIC
-                | Dt::UNIFORM_BUFFER_DYNAMIC => {
-                    raw.p_image_info = ptr::null();
-                    raw.p_texel_buffer_view = ptr::null();
-                    raw.p_buffer_info = buffer_infos[raw.p_buffer_info as usize..].as_ptr();
-                }
-                _ => panic!("unknown descriptor type"),
-            }
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        layout: &n::DescriptorSetLayout,
+        entries: I,
+    ) -> Result<n::DescriptorUpdateTemplate, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorUpdateTemplateEntry>,
+    {
+        let mut ranges = Vec::new();
+        for entry in entries {
+            n::DescriptorWriteRange::split(
+                &layout.bindings,
+                entry.binding,
+                entry.array_offset,
+                entry.count,
+                &mut ranges,
+            );
         }
+        Ok(n::DescriptorUpdateTemplate { ranges })
+    }
 
-        self.shared.raw.update_descriptor_sets(&raw_writes, &[]);
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        set: &mut n::DescriptorSet,
+        template: &n::DescriptorUpdateTemplate,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+        let mut writes = DescriptorWrites::default();
+        writes.add(set.raw, &template.ranges, descriptors);
+        self.shared.raw.update_descriptor_sets(writes.finish(), &[]);
     }
 
     unsafe fn copy_descriptor_set<'a>(&self, op: pso::DescriptorSetCopy<'a, B>) {
@@ -1516,6 +1610,242 @@ impl d::Device<B> for super::Device {
         }
     }
 
+    fn create_timeline_semaphore(
+        &self,
+        initial_value: u64,
+    ) -> Result<n::Semaphore, d::OutOfMemory> {
+        let mut type_info = vk::SemaphoreTypeCreateInfo::builder()
+            .semaphore_type(vk::SemaphoreType::TIMELINE)
+            .initial_value(initial_value);
+        let info = vk::SemaphoreCreateInfo::builder()
+            .flags(vk::SemaphoreCreateFlags::empty())
+            .push_next(&mut type_info);
+
+        let result = unsafe { self.shared.raw.create_semaphore(&info, None) };
+
+        match result {
+            Ok(semaphore) => Ok(n::Semaphore(semaphore)),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        semaphore: &n::Semaphore,
+    ) -> Result<u64, d::WaitError> {
+        let result = match self
+            .shared
+            .extension_fns
+            .timeline_semaphore
+            .as_ref()
+            .expect(
+                "Feature TIMELINE_SEMAPHORE must be enabled to call get_semaphore_counter_value",
+            ) {
+            ExtensionFn::Extension(t) => {
+                let mut value = 0;
+                match t.get_semaphore_counter_value_khr(
+                    self.shared.raw.handle(),
+                    semaphore.0,
+                    &mut value,
+                ) {
+                    vk::Result::SUCCESS => Ok(value),
+                    err => Err(err),
+                }
+            }
+            ExtensionFn::Promoted => self.shared.raw.get_semaphore_counter_value(semaphore.0),
+        };
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(vk::Result::ERROR_DEVICE_LOST) => Err(d::DeviceLost.into()),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        semaphores: I,
+        wait: d::WaitFor,
+        timeout_ns: u64,
+    ) -> Result<bool, d::WaitError>
+    where
+        I: Iterator<Item = (&'a n::Semaphore, u64)>,
+    {
+        let (semaphores, values): (SmallVec<[vk::Semaphore; 4]>, SmallVec<[u64; 4]>) = semaphores
+            .map(|(semaphore, value)| (semaphore.0, value))
+            .unzip();
+        let flags = match wait {
+            d::WaitFor::Any => vk::SemaphoreWaitFlags::ANY,
+            d::WaitFor::All => vk::SemaphoreWaitFlags::empty(),
+        };
+        let info = vk::SemaphoreWaitInfo::builder()
+            .flags(flags)
+            .semaphores(&semaphores)
+            .values(&values);
+
+        let result = match self
+            .shared
+            .extension_fns
+            .timeline_semaphore
+            .as_ref()
+            .expect("Feature TIMELINE_SEMAPHORE must be enabled to call wait_for_semaphores")
+        {
+            ExtensionFn::Extension(t) => {
+                match t.wait_semaphores_khr(self.shared.raw.handle(), &*info, timeout_ns) {
+                    vk::Result::SUCCESS => Ok(()),
+                    err => Err(err),
+                }
+            }
+            ExtensionFn::Promoted => self.shared.raw.wait_semaphores(&info, timeout_ns),
+        };
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(vk::Result::TIMEOUT) => Ok(false),
+            Err(vk::Result::ERROR_DEVICE_LOST) => Err(d::DeviceLost.into()),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        semaphore: &n::Semaphore,
+        value: u64,
+    ) -> Result<(), d::OutOfMemory> {
+        let info = vk::SemaphoreSignalInfo::builder()
+            .semaphore(semaphore.0)
+            .value(value);
+
+        let result = match self
+            .shared
+            .extension_fns
+            .timeline_semaphore
+            .as_ref()
+            .expect("Feature TIMELINE_SEMAPHORE must be enabled to call signal_semaphore")
+        {
+            ExtensionFn::Extension(t) => {
+                match t.signal_semaphore_khr(self.shared.raw.handle(), &*info) {
+                    vk::Result::SUCCESS => Ok(()),
+                    err => Err(err),
+                }
+            }
+            ExtensionFn::Promoted => self.shared.raw.signal_semaphore(&info),
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device),
+            _ => unreachable!(),
+        }
+    }
+
+    fn create_exportable_semaphore(
+        &self,
+        handle_type: hal::external::HandleType,
+    ) -> Result<n::Semaphore, hal::external::ExternalError> {
+        let handle_types = match handle_type {
+            hal::external::HandleType::OpaqueFd => {
+                vk::ExternalSemaphoreHandleTypeFlags::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD
+            }
+            hal::external::HandleType::DmaBuf => {
+                return Err(hal::external::ExternalError::UnsupportedHandleType)
+            }
+        };
+        let mut export_info = vk::ExportSemaphoreCreateInfo::builder().handle_types(handle_types);
+        let info = vk::SemaphoreCreateInfo::builder()
+            .flags(vk::SemaphoreCreateFlags::empty())
+            .push_next(&mut export_info);
+
+        let result = unsafe { self.shared.raw.create_semaphore(&info, None) };
+
+        match result {
+            Ok(semaphore) => Ok(n::Semaphore(semaphore)),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        semaphore: &n::Semaphore,
+        handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        let handle_type = match handle_type {
+            hal::external::HandleType::OpaqueFd => {
+                vk::ExternalSemaphoreHandleTypeFlags::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD
+            }
+            hal::external::HandleType::DmaBuf => {
+                return Err(hal::external::ExternalError::UnsupportedHandleType)
+            }
+        };
+        let info = vk::SemaphoreGetFdInfoKHR::builder()
+            .semaphore(semaphore.0)
+            .handle_type(handle_type);
+
+        let mut fd = -1;
+        let result = self
+            .shared
+            .extension_fns
+            .external_semaphore_fd
+            .as_ref()
+            .expect("Feature EXTERNAL_FD must be enabled to call export_semaphore")
+            .unwrap_extension()
+            .get_semaphore_fd_khr(self.shared.raw.handle(), &*info, &mut fd);
+
+        match result {
+            vk::Result::SUCCESS => Ok(fd),
+            vk::Result::ERROR_TOO_MANY_OBJECTS => Err(hal::external::ExternalError::TooManyObjects),
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => Err(d::OutOfMemory::Host.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        semaphore: &mut n::Semaphore,
+        handle_type: hal::external::HandleType,
+        fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        let handle_type = match handle_type {
+            hal::external::HandleType::OpaqueFd => {
+                vk::ExternalSemaphoreHandleTypeFlags::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD
+            }
+            hal::external::HandleType::DmaBuf => {
+                return Err(hal::external::ExternalError::UnsupportedHandleType)
+            }
+        };
+        let info = vk::ImportSemaphoreFdInfoKHR::builder()
+            .semaphore(semaphore.0)
+            .handle_type(handle_type)
+            .fd(fd);
+
+        let result = self
+            .shared
+            .extension_fns
+            .external_semaphore_fd
+            .as_ref()
+            .expect("Feature EXTERNAL_FD must be enabled to call import_semaphore")
+            .unwrap_extension()
+            .import_semaphore_fd_khr(self.shared.raw.handle(), &*info);
+
+        match result {
+            vk::Result::SUCCESS => Ok(()),
+            vk::Result::ERROR_INVALID_EXTERNAL_HANDLE => {
+                Err(hal::external::ExternalError::InvalidHandle)
+            }
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => Err(d::OutOfMemory::Host.into()),
+            _ => unreachable!(),
+        }
+    }
+
     fn create_fence(&self, signaled: bool) -> Result<n::Fence, d::OutOfMemory> {
         let info = vk::FenceCreateInfo::builder().flags(if signaled {
             vk::FenceCreateFlags::SIGNALED
@@ -1561,6 +1891,7 @@ impl d::Device<B> for super::Device {
         let result = inplace_or_alloc_from_iter(vk_fences_iter, |fences| {
             self.shared.raw.wait_for_fences(fences, all, timeout_ns)
         });
+        self.shared.instance.check_validation_error();
 
         match result {
             Ok(()) => Ok(true),
@@ -1625,6 +1956,130 @@ impl d::Device<B> for super::Device {
         }
     }
 
+    unsafe fn allocate_exportable_memory(
+        &self,
+        mem_type: MemoryTypeId,
+        size: u64,
+        handle_type: hal::external::HandleType,
+    ) -> Result<n::Memory, hal::external::ExternalError> {
+        let mut export_info = vk::ExportMemoryAllocateInfo::builder()
+            .handle_types(conv::map_external_memory_handle_type(handle_type));
+        let info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(size)
+            .memory_type_index(self.get_ash_memory_type_index(mem_type))
+            .push_next(&mut export_info);
+
+        let result = self.allocate_raw_memory(info);
+
+        match result {
+            Ok(memory) => Ok(n::Memory { raw: memory }),
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => {
+                Err(hal::external::ExternalError::TooManyObjects)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn export_memory(
+        &self,
+        memory: &n::Memory,
+        handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        let info = vk::MemoryGetFdInfoKHR::builder()
+            .memory(memory.raw)
+            .handle_type(conv::map_external_memory_handle_type(handle_type));
+
+        let result = self
+            .shared
+            .extension_fns
+            .external_memory_fd
+            .as_ref()
+            .expect("Feature EXTERNAL_FD must be enabled to call export_memory")
+            .unwrap_extension()
+            .get_memory_fd(&info);
+
+        match result {
+            Ok(fd) => Ok(fd),
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => {
+                Err(hal::external::ExternalError::TooManyObjects)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn import_memory(
+        &self,
+        mem_type: MemoryTypeId,
+        size: u64,
+        handle_type: hal::external::HandleType,
+        fd: hal::external::Fd,
+    ) -> Result<n::Memory, hal::external::ExternalError> {
+        let mut import_info = vk::ImportMemoryFdInfoKHR::builder()
+            .handle_type(conv::map_external_memory_handle_type(handle_type))
+            .fd(fd);
+        let info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(size)
+            .memory_type_index(self.get_ash_memory_type_index(mem_type))
+            .push_next(&mut import_info);
+
+        // Imported memory isn't bound to buffers accessed by their device address,
+        // see `allocate_raw_memory`.
+        let result = self.shared.raw.allocate_memory(&info, None);
+
+        match result {
+            Ok(memory) => Ok(n::Memory { raw: memory }),
+            Err(vk::Result::ERROR_INVALID_EXTERNAL_HANDLE) => {
+                Err(hal::external::ExternalError::InvalidHandle)
+            }
+            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => {
+                Err(hal::external::ExternalError::TooManyObjects)
+            }
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        handle_type: hal::external::HandleType,
+        fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        let external_memory_fd = self
+            .shared
+            .extension_fns
+            .external_memory_fd
+            .as_ref()
+            .expect("Feature EXTERNAL_FD must be enabled to call external_memory_type_mask")
+            .unwrap_extension();
+
+        // The properties of opaque file descriptors can't be queried,
+        // they are only valid for the memory type they were allocated from.
+        let ash_mask = match handle_type {
+            hal::external::HandleType::OpaqueFd => !0,
+            hal::external::HandleType::DmaBuf => {
+                match external_memory_fd.get_memory_fd_properties_khr(
+                    conv::map_external_memory_handle_type(handle_type),
+                    fd,
+                ) {
+                    Ok(properties) => properties.memory_type_bits,
+                    Err(vk::Result::ERROR_INVALID_EXTERNAL_HANDLE) => {
+                        return Err(hal::external::ExternalError::InvalidHandle)
+                    }
+                    Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
+                        return Err(d::OutOfMemory::Host.into())
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        };
+
+        Ok(self.filter_memory_requirements(ash_mask))
+    }
+
     unsafe fn free_memory(&self, memory: n::Memory) {
         self.shared.raw.free_memory(memory.raw, None);
     }
@@ -1761,6 +2216,8 @@ impl d::Device<B> for super::Device {
             .destroy_descriptor_set_layout(layout.raw, None);
     }
 
+    unsafe fn destroy_descriptor_update_template(&self, _template: n::DescriptorUpdateTemplate) {}
+
     unsafe fn destroy_fence(&self, fence: n::Fence) {
         self.shared.raw.destroy_fence(fence.0, None);
     }
@@ -1774,7 +2231,9 @@ impl d::Device<B> for super::Device {
     }
 
     fn wait_idle(&self) -> Result<(), d::OutOfMemory> {
-        match unsafe { self.shared.raw.device_wait_idle() } {
+        let result = unsafe { self.shared.raw.device_wait_idle() };
+        self.shared.instance.check_validation_error();
+        match result {
             Ok(()) => Ok(()),
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device),
@@ -1886,6 +2345,84 @@ impl super::Device {
         panic!("Unable to get Ash memory type for {:?}", hal_type);
     }
 
+    /// Allocate memory, adding the allocation flags required by the enabled features.
+    unsafe fn allocate_raw_memory(
+        &self,
+        info: vk::MemoryAllocateInfoBuilder<'_>,
+    ) -> Result<vk::DeviceMemory, vk::Result> {
+        // Any allocation may end up bound to a buffer that is accessed by its device address,
+        // so the flag is set on all of them once the feature is enabled. Devices that don't
+        // need it aren't affected, as the feature is only enabled on request.
+        let mut flags_info =
+            vk::MemoryAllocateFlagsInfo::builder().flags(vk::MemoryAllocateFlags::DEVICE_ADDRESS);
+        let info = if self
+            .shared
+            .features
+            .contains(Features::BUFFER_DEVICE_ADDRESS)
+        {
+            info.push_next(&mut flags_info)
+        } else {
+            info
+        };
+
+        self.shared.raw.allocate_memory(&info, None)
+    }
+
+    unsafe fn create_descriptor_set_layout_with_flags<'a, I, J>(
+        &self,
+        binding_iter: I,
+        immutable_samplers: J,
+        flags: vk::DescriptorSetLayoutCreateFlags,
+    ) -> Result<n::DescriptorSetLayout, d::OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
+        J: Iterator<Item = &'a n::Sampler>,
+    {
+        let vk_immutable_samplers_iter = immutable_samplers.map(|is| is.0);
+        let mut sampler_offset = 0;
+
+        let mut bindings = binding_iter.collect::<Vec<_>>();
+        // Sorting will come handy in `DescriptorWriteRange::split`.
+        bindings.sort_by_key(|b| b.binding);
+
+        let result = inplace_or_alloc_from_iter(vk_immutable_samplers_iter, |immutable_samplers| {
+            let raw_bindings_iter = bindings.iter().map(|b| vk::DescriptorSetLayoutBinding {
+                binding: b.binding,
+                descriptor_type: conv::map_descriptor_type(b.ty),
+                descriptor_count: b.count as _,
+                stage_flags: conv::map_stage_flags(b.stage_flags),
+                p_immutable_samplers: if b.immutable_samplers {
+                    let slice = &immutable_samplers[sampler_offset..];
+                    sampler_offset += b.count;
+                    slice.as_ptr()
+                } else {
+                    ptr::null()
+                },
+            });
+
+            inplace_or_alloc_from_iter(raw_bindings_iter, |raw_bindings| {
+                // TODO raw_bindings doesnt implement fmt::Debug
+                // debug!("create_descriptor_set_layout {:?}", raw_bindings);
+
+                let info = vk::DescriptorSetLayoutCreateInfo::builder()
+                    .flags(flags)
+                    .bindings(&raw_bindings);
+
+                self.shared.raw.create_descriptor_set_layout(&info, None)
+            })
+        });
+
+        match result {
+            Ok(layout) => Ok(n::DescriptorSetLayout {
+                raw: layout,
+                bindings: Arc::new(bindings),
+            }),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            _ => unreachable!(),
+        }
+    }
+
     pub(crate) unsafe fn create_swapchain(
         &self,
         surface: &mut w::Surface,
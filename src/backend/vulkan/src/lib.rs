@@ -33,6 +33,8 @@ use ash::{
         self,
         ext::{DebugReport, DebugUtils},
         khr::DrawIndirectCount,
+        khr::ExternalMemoryFd,
+        khr::PushDescriptor,
         khr::Swapchain,
         nv::MeshShader,
     },
@@ -54,17 +56,20 @@ use std::{
     borrow::Cow,
     cmp,
     ffi::{CStr, CString},
-    fmt, slice,
+    fmt, panic, process, slice,
     sync::Arc,
     thread, unreachable,
 };
 
+use parking_lot::Mutex;
+
 #[cfg(feature = "use-rtld-next")]
 use ash::EntryCustom;
 
 mod command;
 mod conv;
 mod device;
+mod ext;
 mod info;
 mod native;
 mod physical_device;
@@ -79,7 +84,11 @@ const ROUGH_MAX_ATTACHMENT_COUNT: usize = 5;
 pub struct RawInstance {
     inner: ash::Instance,
     debug_messenger: Option<DebugMessenger>,
+    /// Passed to the debug callbacks, must outlive `debug_messenger`.
+    debug_state: Box<DebugState>,
     get_physical_device_properties: Option<vk::KhrGetPhysicalDeviceProperties2Fn>,
+    external_memory_capabilities: Option<vk::KhrExternalMemoryCapabilitiesFn>,
+    external_semaphore_capabilities: Option<vk::KhrExternalSemaphoreCapabilitiesFn>,
 }
 
 pub enum DebugMessenger {
@@ -104,6 +113,23 @@ impl Drop for RawInstance {
     }
 }
 
+impl RawInstance {
+    /// Take the first validation error reported since the last call.
+    pub fn take_validation_error(&self) -> Option<String> {
+        self.debug_state.first_error.lock().take()
+    }
+
+    /// Panic with the first validation error reported, if `DebugOptions::panic_on_error` is set.
+    fn check_validation_error(&self) {
+        if !self.debug_state.options.panic_on_error || thread::panicking() {
+            return;
+        }
+        if let Some(error) = self.take_validation_error() {
+            panic!("Vulkan validation error:\n{}", error);
+        }
+    }
+}
+
 /// Helper wrapper around `vk::make_version`.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(transparent)]
@@ -242,77 +268,171 @@ unsafe fn display_debug_utils_object_name_info_ext(
     )
 }
 
+/// Message reported through `VK_EXT_debug_utils`, mostly by the validation layers.
+#[derive(Debug)]
+pub struct DebugMessage<'a> {
+    /// Severity of the message, a single bit.
+    pub severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    /// Kind of event the message is about: general, validation or performance.
+    pub ty: vk::DebugUtilsMessageTypeFlagsEXT,
+    /// Identifier of the check that triggered the message, empty if not provided.
+    pub id_name: Cow<'a, str>,
+    /// Numeric identifier of the check that triggered the message.
+    pub id_number: i32,
+    /// The message itself.
+    pub message: Cow<'a, str>,
+    /// Labels of the queue the message relates to.
+    pub queue_labels: Option<String>,
+    /// Labels of the command buffer the message relates to.
+    pub cmd_buf_labels: Option<String>,
+    /// Types, handles and names of the objects the message relates to.
+    pub objects: Option<String>,
+}
+
+impl DebugMessage<'_> {
+    fn is_validation_error(&self) -> bool {
+        self.severity == vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
+            && self
+                .ty
+                .contains(vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION)
+    }
+}
+
+impl fmt::Display for DebugMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} [{} (0x{:x})] : {}",
+            self.ty, self.id_name, self.id_number, self.message
+        )?;
+
+        let additional_info = [
+            ("queue info", &self.queue_labels),
+            ("cmd buf info", &self.cmd_buf_labels),
+            ("object info", &self.objects),
+        ];
+        for &(info_label, info) in additional_info.iter() {
+            if let Some(ref data) = *info {
+                write!(f, "\n{}: {}", info_label, data)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// User callback receiving the debug messages.
+pub type DebugCallback = dyn Fn(&DebugMessage) + Send + Sync;
+
+/// Handling of the debug messages of an `Instance`.
+///
+/// Messages are only reported if `VK_EXT_debug_utils` is available, or if
+/// `VK_EXT_debug_report` is available and enabled, which is the case with
+/// `InstanceDesc::validation` or when requested in `InstanceDesc::extensions`.
+/// Only the former supports `callback`.
+#[derive(Default)]
+pub struct DebugOptions {
+    /// Called with every message, instead of forwarding them to `log`.
+    ///
+    /// The callback is invoked from within the Vulkan calls, which can't be unwound through,
+    /// so a panic in the callback aborts the process.
+    pub callback: Option<Box<DebugCallback>>,
+    /// Panic on the first validation error.
+    ///
+    /// The error can't be raised from within the Vulkan call reporting it, so it is recorded
+    /// and the panic happens at the next `Queue::submit`, `Queue::wait_idle`,
+    /// `Device::wait_for_fences` or `Device::wait_idle` instead.
+    /// This allows tests to fail on validation errors.
+    pub panic_on_error: bool,
+}
+
+impl fmt::Debug for DebugOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugOptions")
+            .field("callback", &self.callback.is_some())
+            .field("panic_on_error", &self.panic_on_error)
+            .finish()
+    }
+}
+
+/// State shared with the debug callbacks.
+struct DebugState {
+    options: DebugOptions,
+    /// First validation error not yet taken.
+    first_error: Mutex<Option<String>>,
+}
+
+impl DebugState {
+    fn record_error(&self, error: impl FnOnce() -> String) {
+        let mut first_error = self.first_error.lock();
+        if first_error.is_none() {
+            *first_error = Some(error());
+        }
+    }
+}
+
 unsafe extern "system" fn debug_utils_messenger_callback(
     message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
     message_type: vk::DebugUtilsMessageTypeFlagsEXT,
     p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
-    _user_data: *mut std::os::raw::c_void,
+    user_data: *mut std::os::raw::c_void,
 ) -> vk::Bool32 {
     if thread::panicking() {
         return vk::FALSE;
     }
+    let state = &*(user_data as *const DebugState);
     let callback_data = *p_callback_data;
 
-    let message_severity = match message_severity {
-        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => log::Level::Error,
-        vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => log::Level::Warn,
-        vk::DebugUtilsMessageSeverityFlagsEXT::INFO => log::Level::Info,
-        vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE => log::Level::Trace,
-        _ => log::Level::Warn,
-    };
-    let message_type = &format!("{:?}", message_type);
-    let message_id_number: i32 = callback_data.message_id_number as i32;
-
-    let message_id_name = if callback_data.p_message_id_name.is_null() {
-        Cow::from("")
-    } else {
-        CStr::from_ptr(callback_data.p_message_id_name).to_string_lossy()
-    };
-
-    let message = if callback_data.p_message.is_null() {
-        Cow::from("")
-    } else {
-        CStr::from_ptr(callback_data.p_message).to_string_lossy()
-    };
-
-    let additional_info: [(&str, Option<String>); 3] = [
-        (
-            "queue info",
-            display_debug_utils_label_ext(
-                callback_data.p_queue_labels as *mut _,
-                callback_data.queue_label_count as usize,
-            ),
+    let message = DebugMessage {
+        severity: message_severity,
+        ty: message_type,
+        id_name: if callback_data.p_message_id_name.is_null() {
+            Cow::from("")
+        } else {
+            CStr::from_ptr(callback_data.p_message_id_name).to_string_lossy()
+        },
+        id_number: callback_data.message_id_number as i32,
+        message: if callback_data.p_message.is_null() {
+            Cow::from("")
+        } else {
+            CStr::from_ptr(callback_data.p_message).to_string_lossy()
+        },
+        queue_labels: display_debug_utils_label_ext(
+            callback_data.p_queue_labels as *mut _,
+            callback_data.queue_label_count as usize,
         ),
-        (
-            "cmd buf info",
-            display_debug_utils_label_ext(
-                callback_data.p_cmd_buf_labels as *mut _,
-                callback_data.cmd_buf_label_count as usize,
-            ),
+        cmd_buf_labels: display_debug_utils_label_ext(
+            callback_data.p_cmd_buf_labels as *mut _,
+            callback_data.cmd_buf_label_count as usize,
         ),
-        (
-            "object info",
-            display_debug_utils_object_name_info_ext(
-                callback_data.p_objects as *mut _,
-                callback_data.object_count as usize,
-            ),
+        objects: display_debug_utils_object_name_info_ext(
+            callback_data.p_objects as *mut _,
+            callback_data.object_count as usize,
         ),
-    ];
-
-    log!(message_severity, "{}\n", {
-        let mut msg = format!(
-            "\n{} [{} (0x{:x})] : {}",
-            message_type, message_id_name, message_id_number, message
-        );
+    };
 
-        for &(info_label, ref info) in additional_info.iter() {
-            if let Some(ref data) = *info {
-                msg = format!("{}\n{}: {}", msg, info_label, data);
+    match state.options.callback {
+        Some(ref callback) => {
+            if panic::catch_unwind(panic::AssertUnwindSafe(|| callback(&message))).is_err() {
+                eprintln!("Vulkan debug callback panicked, aborting");
+                process::abort();
             }
         }
+        None => {
+            let level = match message_severity {
+                vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => log::Level::Error,
+                vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => log::Level::Warn,
+                vk::DebugUtilsMessageSeverityFlagsEXT::INFO => log::Level::Info,
+                vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE => log::Level::Trace,
+                _ => log::Level::Warn,
+            };
+            log!(level, "\n{}\n", message);
+        }
+    }
 
-        msg
-    });
+    if message.is_validation_error() {
+        state.record_error(|| message.to_string());
+    }
 
     vk::FALSE
 }
@@ -325,11 +445,12 @@ unsafe extern "system" fn debug_report_callback(
     _msg_code: i32,
     layer_prefix: *const std::os::raw::c_char,
     description: *const std::os::raw::c_char,
-    _user_data: *mut std::os::raw::c_void,
+    user_data: *mut std::os::raw::c_void,
 ) -> vk::Bool32 {
     if thread::panicking() {
         return vk::FALSE;
     }
+    let state = &*(user_data as *const DebugState);
 
     let level = match type_ {
         vk::DebugReportFlagsEXT::ERROR => log::Level::Error,
@@ -339,20 +460,29 @@ unsafe extern "system" fn debug_report_callback(
         _ => log::Level::Warn,
     };
 
-    let layer_prefix = CStr::from_ptr(layer_prefix).to_str().unwrap();
-    let description = CStr::from_ptr(description).to_str().unwrap();
+    let layer_prefix = CStr::from_ptr(layer_prefix).to_string_lossy();
+    let description = CStr::from_ptr(description).to_string_lossy();
     log!(level, "[{}] {}", layer_prefix, description);
+
+    if type_ == vk::DebugReportFlagsEXT::ERROR {
+        state.record_error(|| format!("[{}] {}", layer_prefix, description));
+    }
+
     vk::FALSE
 }
 
-impl hal::Instance<Backend> for Instance {
-    fn create(name: &str, version: u32) -> Result<Self, hal::UnsupportedBackend> {
+impl Instance {
+    /// Create a new instance, reporting the debug messages according to `debug_options`.
+    pub fn create_with_debug_options(
+        desc: &hal::InstanceDesc,
+        debug_options: DebugOptions,
+    ) -> Result<Self, hal::UnsupportedBackend> {
         #[cfg(not(feature = "use-rtld-next"))]
         let entry = match Entry::new() {
             Ok(entry) => entry,
             Err(err) => {
                 info!("Missing Vulkan entry points: {:?}", err);
-                return Err(hal::UnsupportedBackend);
+                return Err(hal::UnsupportedBackend::new("Missing Vulkan entry points"));
             }
         };
 
@@ -371,16 +501,32 @@ impl hal::Instance<Backend> for Instance {
             // Ignore out of memory since it's unlikely to happen and `Instance::create` doesn't have a way to express it in the return value.
             Err(err) if err == vk::Result::ERROR_OUT_OF_HOST_MEMORY => {
                 warn!("vkEnumerateInstanceVersion returned VK_ERROR_OUT_OF_HOST_MEMORY");
-                return Err(hal::UnsupportedBackend);
+                return Err(hal::UnsupportedBackend::new(
+                    "Out of host memory when querying the Vulkan version",
+                ));
             }
 
             Err(_) => unreachable!(),
         };
 
-        let app_name = CString::new(name).unwrap();
+        if let Some((major, minor)) = desc.min_api_version {
+            let min_api_version = Version(vk::make_version(major, minor, 0));
+            // See the maximum version below.
+            if min_api_version > cmp::min(driver_api_version, Version::V1_2) {
+                return Err(hal::UnsupportedBackend::new(format!(
+                    "Vulkan {}.{} is not supported, the driver supports {}.{}",
+                    major,
+                    minor,
+                    driver_api_version.major(),
+                    driver_api_version.minor()
+                )));
+            }
+        }
+
+        let app_name = CString::new(desc.name).unwrap();
         let app_info = vk::ApplicationInfo::builder()
             .application_name(app_name.as_c_str())
-            .application_version(version)
+            .application_version(desc.version)
             .engine_name(CStr::from_bytes_with_nul(b"gfx-rs\0").unwrap())
             .engine_version(1)
             .api_version({
@@ -405,45 +551,50 @@ impl hal::Instance<Backend> for Instance {
             .enumerate_instance_extension_properties()
             .map_err(|e| {
                 info!("Unable to enumerate instance extensions: {:?}", e);
-                hal::UnsupportedBackend
+                hal::UnsupportedBackend::new("Unable to enumerate the instance extensions")
             })?;
 
         let instance_layers = entry.enumerate_instance_layer_properties().map_err(|e| {
             info!("Unable to enumerate instance layers: {:?}", e);
-            hal::UnsupportedBackend
+            hal::UnsupportedBackend::new("Unable to enumerate the instance layers")
         })?;
 
         // Check our extensions against the available extensions
         let extensions = {
             let mut extensions: Vec<&'static CStr> = Vec::new();
             extensions.push(extensions::khr::Surface::name());
+            extensions.push(vk::ExtHeadlessSurfaceFn::name());
 
             // Platform-specific WSI extensions
-            if cfg!(all(
-                unix,
-                not(target_os = "android"),
-                not(target_os = "macos")
-            )) {
-                extensions.push(extensions::khr::XlibSurface::name());
-                extensions.push(extensions::khr::XcbSurface::name());
-                extensions.push(extensions::khr::WaylandSurface::name());
-            }
-            if cfg!(target_os = "android") {
-                extensions.push(extensions::khr::AndroidSurface::name());
-            }
-            if cfg!(target_os = "windows") {
-                extensions.push(extensions::khr::Win32Surface::name());
-            }
-            if cfg!(target_os = "macos") {
-                extensions.push(extensions::mvk::MacOSSurface::name());
+            if !desc.headless {
+                if cfg!(all(
+                    unix,
+                    not(target_os = "android"),
+                    not(target_os = "macos")
+                )) {
+                    extensions.push(extensions::khr::XlibSurface::name());
+                    extensions.push(extensions::khr::XcbSurface::name());
+                    extensions.push(extensions::khr::WaylandSurface::name());
+                }
+                if cfg!(target_os = "android") {
+                    extensions.push(extensions::khr::AndroidSurface::name());
+                }
+                if cfg!(target_os = "windows") {
+                    extensions.push(extensions::khr::Win32Surface::name());
+                }
+                if cfg!(target_os = "macos") {
+                    extensions.push(extensions::mvk::MacOSSurface::name());
+                }
             }
 
             extensions.push(DebugUtils::name());
-            if cfg!(debug_assertions) {
+            if desc.validation {
                 extensions.push(DebugReport::name());
             }
 
             extensions.push(vk::KhrGetPhysicalDeviceProperties2Fn::name());
+            extensions.push(vk::KhrExternalMemoryCapabilitiesFn::name());
+            extensions.push(vk::KhrExternalSemaphoreCapabilitiesFn::name());
 
             // Only keep available extensions.
             extensions.retain(|&ext| {
@@ -463,10 +614,35 @@ impl hal::Instance<Backend> for Instance {
             extensions
         };
 
+        // Extensions requested by the user are required.
+        let user_extensions = desc
+            .extensions
+            .iter()
+            .filter(|&&name| {
+                !extensions
+                    .iter()
+                    .any(|ext| ext.to_str().map_or(false, |ext| ext == name))
+            })
+            .map(|&name| {
+                let ext = CString::new(name).unwrap();
+                let available = instance_extensions.iter().any(|inst_ext| unsafe {
+                    CStr::from_ptr(inst_ext.extension_name.as_ptr()) == ext.as_c_str()
+                });
+                if available {
+                    Ok(ext)
+                } else {
+                    Err(hal::UnsupportedBackend::new(format!(
+                        "Instance extension {} is not available",
+                        name
+                    )))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         // Check requested layers against the available layers
         let layers = {
             let mut layers: Vec<&'static CStr> = Vec::new();
-            if cfg!(debug_assertions) {
+            if desc.validation {
                 layers.push(CStr::from_bytes_with_nul(b"VK_LAYER_KHRONOS_validation\0").unwrap());
             }
 
@@ -496,6 +672,7 @@ impl hal::Instance<Backend> for Instance {
                     // Safe because `layers` and `extensions` entries have static lifetime.
                     s.as_ptr()
                 })
+                .chain(user_extensions.iter().map(|s| s.as_ptr()))
                 .collect::<Vec<_>>();
 
             let create_info = vk::InstanceCreateInfo::builder()
@@ -506,7 +683,7 @@ impl hal::Instance<Backend> for Instance {
 
             unsafe { entry.create_instance(&create_info, None) }.map_err(|e| {
                 warn!("Unable to create Vulkan instance: {:?}", e);
-                hal::UnsupportedBackend
+                hal::UnsupportedBackend::new(format!("Unable to create Vulkan instance: {}", e))
             })?
         };
 
@@ -521,28 +698,56 @@ impl hal::Instance<Backend> for Instance {
                 })
             });
 
+        let external_memory_capabilities = extensions
+            .iter()
+            .find(|&&ext| ext == vk::KhrExternalMemoryCapabilitiesFn::name())
+            .map(|_| {
+                vk::KhrExternalMemoryCapabilitiesFn::load(|name| unsafe {
+                    std::mem::transmute(
+                        entry.get_instance_proc_addr(instance.handle(), name.as_ptr()),
+                    )
+                })
+            });
+
+        let external_semaphore_capabilities = extensions
+            .iter()
+            .find(|&&ext| ext == vk::KhrExternalSemaphoreCapabilitiesFn::name())
+            .map(|_| {
+                vk::KhrExternalSemaphoreCapabilitiesFn::load(|name| unsafe {
+                    std::mem::transmute(
+                        entry.get_instance_proc_addr(instance.handle(), name.as_ptr()),
+                    )
+                })
+            });
+
+        let debug_state = Box::new(DebugState {
+            options: debug_options,
+            first_error: Mutex::new(None),
+        });
+        let user_data = &*debug_state as *const DebugState as *mut std::os::raw::c_void;
+
+        // The debug extensions can only be used if they were enabled on the instance.
+        let is_enabled = |name: &CStr| {
+            extensions.contains(&name) || user_extensions.iter().any(|ext| ext.as_c_str() == name)
+        };
+
         let debug_messenger = {
-            // make sure VK_EXT_debug_utils is available
-            if instance_extensions.iter().any(|props| unsafe {
-                CStr::from_ptr(props.extension_name.as_ptr()) == DebugUtils::name()
-            }) {
+            if is_enabled(DebugUtils::name()) {
                 let ext = DebugUtils::new(&entry, &instance);
                 let info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
                     .flags(vk::DebugUtilsMessengerCreateFlagsEXT::empty())
                     .message_severity(vk::DebugUtilsMessageSeverityFlagsEXT::all())
                     .message_type(vk::DebugUtilsMessageTypeFlagsEXT::all())
-                    .pfn_user_callback(Some(debug_utils_messenger_callback));
+                    .pfn_user_callback(Some(debug_utils_messenger_callback))
+                    .user_data(user_data);
                 let handle = unsafe { ext.create_debug_utils_messenger(&info, None) }.unwrap();
                 Some(DebugMessenger::Utils(ext, handle))
-            } else if cfg!(debug_assertions)
-                && instance_extensions.iter().any(|props| unsafe {
-                    CStr::from_ptr(props.extension_name.as_ptr()) == DebugReport::name()
-                })
-            {
+            } else if is_enabled(DebugReport::name()) {
                 let ext = DebugReport::new(&entry, &instance);
                 let info = vk::DebugReportCallbackCreateInfoEXT::builder()
                     .flags(vk::DebugReportFlagsEXT::all())
-                    .pfn_callback(Some(debug_report_callback));
+                    .pfn_callback(Some(debug_report_callback))
+                    .user_data(user_data);
                 let handle = unsafe { ext.create_debug_report_callback(&info, None) }.unwrap();
                 Some(DebugMessenger::Report(ext, handle))
             } else {
@@ -554,12 +759,25 @@ impl hal::Instance<Backend> for Instance {
             raw: Arc::new(RawInstance {
                 inner: instance,
                 debug_messenger,
+                debug_state,
                 get_physical_device_properties,
+                external_memory_capabilities,
+                external_semaphore_capabilities,
             }),
             extensions,
             entry,
         })
     }
+}
+
+impl hal::Instance<Backend> for Instance {
+    fn create(name: &str, version: u32) -> Result<Self, hal::UnsupportedBackend> {
+        Self::create_with_desc(&hal::InstanceDesc::new(name, version))
+    }
+
+    fn create_with_desc(desc: &hal::InstanceDesc) -> Result<Self, hal::UnsupportedBackend> {
+        Self::create_with_debug_options(desc, DebugOptions::default())
+    }
 
     fn enumerate_adapters(&self) -> Vec<adapter::Adapter<Backend>> {
         let devices = match unsafe { self.raw.inner.enumerate_physical_devices() } {
@@ -623,18 +841,32 @@ impl hal::Instance<Backend> for Instance {
                 Ok(self.create_surface_from_xcb(handle.connection as *mut _, handle.window))
             }
             #[cfg(target_os = "android")]
-            RawWindowHandle::Android(handle) => {
+            RawWindowHandle::Android(handle)
+                if self
+                    .extensions
+                    .contains(&extensions::khr::AndroidSurface::name()) =>
+            {
                 Ok(self.create_surface_android(handle.a_native_window))
             }
             #[cfg(windows)]
-            RawWindowHandle::Windows(handle) => {
+            RawWindowHandle::Windows(handle)
+                if self
+                    .extensions
+                    .contains(&extensions::khr::Win32Surface::name()) =>
+            {
                 use winapi::um::libloaderapi::GetModuleHandleW;
 
                 let hinstance = GetModuleHandleW(std::ptr::null());
                 Ok(self.create_surface_from_hwnd(hinstance as *mut _, handle.hwnd))
             }
             #[cfg(target_os = "macos")]
-            RawWindowHandle::MacOS(handle) => Ok(self.create_surface_from_ns_view(handle.ns_view)),
+            RawWindowHandle::MacOS(handle)
+                if self
+                    .extensions
+                    .contains(&extensions::mvk::MacOSSurface::name()) =>
+            {
+                Ok(self.create_surface_from_ns_view(handle.ns_view))
+            }
             _ => Err(hal::window::InitError::UnsupportedWindowHandle),
         }
     }
@@ -669,6 +901,12 @@ impl queue::QueueFamily for QueueFamily {
 struct DeviceExtensionFunctions {
     mesh_shaders: Option<ExtensionFn<MeshShader>>,
     draw_indirect_count: Option<ExtensionFn<DrawIndirectCount>>,
+    timeline_semaphore: Option<ExtensionFn<vk::KhrTimelineSemaphoreFn>>,
+    push_descriptor: Option<ExtensionFn<PushDescriptor>>,
+    external_memory_fd: Option<ExtensionFn<ExternalMemoryFd>>,
+    external_semaphore_fd: Option<ExtensionFn<vk::KhrExternalSemaphoreFdFn>>,
+    buffer_device_address: Option<ExtensionFn<vk::KhrBufferDeviceAddressFn>>,
+    dynamic_rendering: Option<ExtensionFn<ext::KhrDynamicRenderingFn>>,
 }
 
 // TODO there's no reason why this can't be unified--the function pointers should all be the same--it's not clear how to do this with `ash`.
@@ -825,6 +1063,58 @@ impl queue::Queue<Backend> for Queue {
         if let Err(e) = result {
             error!("Submit resulted in {:?}", e);
         }
+        self.device.instance.check_validation_error();
+    }
+
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        command_buffers: Ic,
+        wait_semaphores: Iw,
+        signal_semaphores: Is,
+        fence: Option<&mut native::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a command::CommandBuffer>,
+        Iw: Iterator<Item = (&'a native::Semaphore, u64, PipelineStage)>,
+        Is: Iterator<Item = (&'a native::Semaphore, u64)>,
+    {
+        //TODO: avoid heap allocations
+        let mut waits = Vec::new();
+        let mut wait_values = Vec::new();
+        let mut stages = Vec::new();
+        let mut signals = Vec::new();
+        let mut signal_values = Vec::new();
+
+        let buffers = command_buffers.map(|cmd| cmd.raw).collect::<Vec<_>>();
+        for (semaphore, value, stage) in wait_semaphores {
+            waits.push(semaphore.0);
+            wait_values.push(value);
+            stages.push(conv::map_pipeline_stage(stage));
+        }
+        for (semaphore, value) in signal_semaphores {
+            signals.push(semaphore.0);
+            signal_values.push(value);
+        }
+
+        let mut timeline_info = vk::TimelineSemaphoreSubmitInfo::builder()
+            .wait_semaphore_values(&wait_values)
+            .signal_semaphore_values(&signal_values);
+        let mut info = vk::SubmitInfo::builder()
+            .wait_semaphores(&waits)
+            .command_buffers(&buffers)
+            .signal_semaphores(&signals)
+            .push_next(&mut timeline_info);
+        // If count is zero, AMD driver crashes if nullptr is not set for stage masks
+        if !stages.is_empty() {
+            info = info.wait_dst_stage_mask(&stages);
+        }
+
+        let fence_raw = fence.map(|fence| fence.0).unwrap_or(vk::Fence::null());
+
+        let result = self.device.raw.queue_submit(*self.raw, &[*info], fence_raw);
+        if let Err(e) = result {
+            error!("Submit resulted in {:?}", e);
+        }
+        self.device.instance.check_validation_error();
     }
 
     unsafe fn bind_sparse<'a, Iw, Is, Ibi, Ib, Iii, Io, Ii>(
@@ -992,8 +1282,8 @@ impl queue::Queue<Backend> for Queue {
             .image_indices(image_indices);
 
         match self.swapchain_fn.queue_present(*self.raw, &present_info) {
-            Ok(true) => Ok(None),
-            Ok(false) => Ok(Some(Suboptimal)),
+            Ok(false) => Ok(None),
+            Ok(true) => Ok(Some(Suboptimal)),
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::Host.into()),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::Device.into()),
             Err(vk::Result::ERROR_DEVICE_LOST) => Err(DeviceLost.into()),
@@ -1004,7 +1294,9 @@ impl queue::Queue<Backend> for Queue {
     }
 
     fn wait_idle(&mut self) -> Result<(), OutOfMemory> {
-        match unsafe { self.device.raw.queue_wait_idle(*self.raw) } {
+        let result = unsafe { self.device.raw.queue_wait_idle(*self.raw) };
+        self.device.instance.check_validation_error();
+        match result {
             Ok(()) => Ok(()),
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::Host),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::Device),
@@ -1058,6 +1350,7 @@ impl hal::Backend for Backend {
     type DescriptorSetLayout = native::DescriptorSetLayout;
     type DescriptorPool = native::DescriptorPool;
     type DescriptorSet = native::DescriptorSet;
+    type DescriptorUpdateTemplate = native::DescriptorUpdateTemplate;
 
     type Fence = native::Fence;
     type Semaphore = native::Semaphore;
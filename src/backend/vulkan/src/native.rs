@@ -1,4 +1,4 @@
-use crate::{Backend, RawDevice, ROUGH_MAX_ATTACHMENT_COUNT};
+use crate::{conv, Backend, RawDevice, ROUGH_MAX_ATTACHMENT_COUNT};
 use ash::{version::DeviceV1_0, vk};
 use hal::{
     device::OutOfMemory,
@@ -93,9 +93,78 @@ pub struct DescriptorSet {
     pub(crate) bindings: SortedBindings,
 }
 
-#[derive(Debug, Hash)]
+#[derive(Debug)]
 pub struct PipelineLayout {
     pub(crate) raw: vk::PipelineLayout,
+    /// Bindings of the set layouts, needed to resolve the push descriptor writes.
+    pub(crate) set_bindings: Vec<SortedBindings>,
+}
+
+/// A single Vulkan descriptor write, covering consecutive descriptors
+/// of the same type and stages.
+#[derive(Debug)]
+pub(crate) struct DescriptorWriteRange {
+    pub(crate) binding: pso::DescriptorBinding,
+    pub(crate) array_offset: pso::DescriptorArrayIndex,
+    pub(crate) count: usize,
+    pub(crate) ty: vk::DescriptorType,
+}
+
+impl DescriptorWriteRange {
+    /// Split the write of `count` descriptors, starting at the given binding and
+    /// array offset, into the ranges Vulkan accepts.
+    ///
+    /// `bindings` has to be sorted by the binding index.
+    pub(crate) fn split(
+        bindings: &[pso::DescriptorSetLayoutBinding],
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+        count: usize,
+        ranges: &mut Vec<Self>,
+    ) {
+        let mut binding_pos = bindings
+            .binary_search_by_key(&binding, |b| b.binding)
+            .expect("Descriptor set writes don't match the set layout!");
+        let mut array_offset = array_offset;
+        let mut remaining = count;
+        // gfx-hal allows the type and stages to be different between the descriptor
+        // in a single write, while Vulkan requires them to be the same.
+        let mut last_stages = None;
+
+        while remaining != 0 {
+            let layout_binding = &bindings[binding_pos];
+            let ty = conv::map_descriptor_type(layout_binding.ty);
+            let written = remaining.min(layout_binding.count - array_offset);
+
+            // Empty bindings are skipped by the consecutive binding updates.
+            if written != 0 {
+                match ranges.last_mut() {
+                    Some(range)
+                        if range.ty == ty && last_stages == Some(layout_binding.stage_flags) =>
+                    {
+                        range.count += written
+                    }
+                    _ => ranges.push(DescriptorWriteRange {
+                        binding: layout_binding.binding,
+                        array_offset,
+                        count: written,
+                        ty,
+                    }),
+                }
+                last_stages = Some(layout_binding.stage_flags);
+            }
+
+            remaining -= written;
+            array_offset = 0;
+            binding_pos += 1;
+        }
+    }
+}
+
+/// The Vulkan descriptor writes of a template, resolved against the set layout.
+#[derive(Debug)]
+pub struct DescriptorUpdateTemplate {
+    pub(crate) ranges: Vec<DescriptorWriteRange>,
 }
 
 #[derive(Debug)]
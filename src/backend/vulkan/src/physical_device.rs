@@ -1,21 +1,25 @@
 use ash::{
-    extensions::{self, khr::DrawIndirectCount, khr::Swapchain, nv::MeshShader},
+    extensions::{
+        self,
+        khr::{DrawIndirectCount, ExternalMemoryFd, PushDescriptor, Swapchain},
+        nv::MeshShader,
+    },
     version::{DeviceV1_0, InstanceV1_0},
     vk,
 };
 
 use hal::{
-    adapter,
+    adapter, buffer,
     device::{CreationError, OutOfMemory},
-    format, image,
-    pso::PatchSize,
+    external, format, image,
+    pso::{self, PatchSize},
     queue, DescriptorLimits, DynamicStates, Features, Limits, PhysicalDeviceProperties,
 };
 
 use std::{ffi::CStr, fmt, mem, ptr, sync::Arc, unreachable};
 
 use crate::{
-    conv, info, Backend, Device, DeviceExtensionFunctions, ExtensionFn, Queue, QueueFamily,
+    conv, ext, info, Backend, Device, DeviceExtensionFunctions, ExtensionFn, Queue, QueueFamily,
     RawDevice, RawInstance, Version,
 };
 
@@ -27,6 +31,10 @@ pub struct PhysicalDeviceFeatures {
     descriptor_indexing: Option<vk::PhysicalDeviceDescriptorIndexingFeaturesEXT>,
     mesh_shader: Option<vk::PhysicalDeviceMeshShaderFeaturesNV>,
     imageless_framebuffer: Option<vk::PhysicalDeviceImagelessFramebufferFeaturesKHR>,
+    timeline_semaphore: Option<vk::PhysicalDeviceTimelineSemaphoreFeaturesKHR>,
+    buffer_device_address: Option<vk::PhysicalDeviceBufferDeviceAddressFeaturesKHR>,
+    subgroup_size_control: Option<vk::PhysicalDeviceSubgroupSizeControlFeaturesEXT>,
+    dynamic_rendering: Option<ext::PhysicalDeviceDynamicRenderingFeaturesKHR>,
 }
 
 // This is safe because the structs have `p_next: *mut c_void`, which we null out/never read.
@@ -53,6 +61,18 @@ impl PhysicalDeviceFeatures {
         if let Some(ref mut feature) = self.imageless_framebuffer {
             info = info.push_next(feature);
         }
+        if let Some(ref mut feature) = self.timeline_semaphore {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.buffer_device_address {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.subgroup_size_control {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.dynamic_rendering {
+            info = info.push_next(feature);
+        }
 
         info
     }
@@ -167,6 +187,8 @@ impl PhysicalDeviceFeatures {
                         )
                         .sampler_filter_minmax(true)
                         .imageless_framebuffer(true)
+                        .timeline_semaphore(features.contains(Features::TIMELINE_SEMAPHORE))
+                        .buffer_device_address(features.contains(Features::BUFFER_DEVICE_ADDRESS))
                         .build(),
                 )
             } else {
@@ -212,6 +234,46 @@ impl PhysicalDeviceFeatures {
             } else {
                 None
             },
+            timeline_semaphore: if enabled_extensions.contains(&vk::KhrTimelineSemaphoreFn::name())
+            {
+                Some(
+                    vk::PhysicalDeviceTimelineSemaphoreFeaturesKHR::builder()
+                        .timeline_semaphore(features.contains(Features::TIMELINE_SEMAPHORE))
+                        .build(),
+                )
+            } else {
+                None
+            },
+            buffer_device_address: if enabled_extensions
+                .contains(&vk::KhrBufferDeviceAddressFn::name())
+            {
+                Some(
+                    vk::PhysicalDeviceBufferDeviceAddressFeaturesKHR::builder()
+                        .buffer_device_address(features.contains(Features::BUFFER_DEVICE_ADDRESS))
+                        .build(),
+                )
+            } else {
+                None
+            },
+            subgroup_size_control: if enabled_extensions
+                .contains(&vk::ExtSubgroupSizeControlFn::name())
+            {
+                Some(
+                    vk::PhysicalDeviceSubgroupSizeControlFeaturesEXT::builder()
+                        .subgroup_size_control(features.contains(Features::SUBGROUP_SIZE_CONTROL))
+                        .build(),
+                )
+            } else {
+                None
+            },
+            dynamic_rendering: if enabled_extensions.contains(&ext::KhrDynamicRenderingFn::name()) {
+                Some(ext::PhysicalDeviceDynamicRenderingFeaturesKHR {
+                    dynamic_rendering: features.contains(Features::DYNAMIC_RENDERING) as _,
+                    ..Default::default()
+                })
+            } else {
+                None
+            },
         }
     }
 
@@ -410,6 +472,10 @@ impl PhysicalDeviceFeatures {
             bits |= Features::DRAW_INDIRECT_COUNT
         }
 
+        if info.supports_extension(PushDescriptor::name()) {
+            bits |= Features::PUSH_DESCRIPTOR;
+        }
+
         if let Some(ref vulkan_1_2) = self.vulkan_1_2 {
             if vulkan_1_2.shader_sampled_image_array_non_uniform_indexing != 0 {
                 bits |= Features::SAMPLED_TEXTURE_DESCRIPTOR_INDEXING;
@@ -426,6 +492,12 @@ impl PhysicalDeviceFeatures {
             if vulkan_1_2.draw_indirect_count != 0 {
                 bits |= Features::DRAW_INDIRECT_COUNT
             }
+            if vulkan_1_2.timeline_semaphore != 0 {
+                bits |= Features::TIMELINE_SEMAPHORE;
+            }
+            if vulkan_1_2.buffer_device_address != 0 {
+                bits |= Features::BUFFER_DEVICE_ADDRESS;
+            }
         }
 
         if let Some(ref descriptor_indexing) = self.descriptor_indexing {
@@ -440,6 +512,30 @@ impl PhysicalDeviceFeatures {
             }
         }
 
+        if let Some(ref timeline_semaphore) = self.timeline_semaphore {
+            if timeline_semaphore.timeline_semaphore != 0 {
+                bits |= Features::TIMELINE_SEMAPHORE;
+            }
+        }
+
+        if let Some(ref buffer_device_address) = self.buffer_device_address {
+            if buffer_device_address.buffer_device_address != 0 {
+                bits |= Features::BUFFER_DEVICE_ADDRESS;
+            }
+        }
+
+        if let Some(ref subgroup_size_control) = self.subgroup_size_control {
+            if subgroup_size_control.subgroup_size_control != 0 {
+                bits |= Features::SUBGROUP_SIZE_CONTROL;
+            }
+        }
+
+        if let Some(ref dynamic_rendering) = self.dynamic_rendering {
+            if dynamic_rendering.dynamic_rendering != 0 {
+                bits |= Features::DYNAMIC_RENDERING;
+            }
+        }
+
         if let Some(ref mesh_shader) = self.mesh_shader {
             if mesh_shader.task_shader != 0 {
                 bits |= Features::TASK_SHADER;
@@ -470,6 +566,15 @@ impl PhysicalDeviceInfo {
             .any(|ep| unsafe { CStr::from_ptr(ep.extension_name.as_ptr()) } == extension)
     }
 
+    /// Check if memory and semaphores can be shared through file descriptors.
+    fn supports_external_fd(&self) -> bool {
+        (self.api_version() >= Version::V1_1
+            || (self.supports_extension(vk::KhrExternalMemoryFn::name())
+                && self.supports_extension(vk::KhrExternalSemaphoreFn::name())))
+            && self.supports_extension(ExternalMemoryFd::name())
+            && self.supports_extension(vk::KhrExternalSemaphoreFdFn::name())
+    }
+
     /// Map `requested_features` to the list of Vulkan extension strings required to create the logical device.
     fn get_required_extensions(&self, requested_features: Features) -> Vec<&'static CStr> {
         let mut requested_extensions = Vec::new();
@@ -527,6 +632,51 @@ impl PhysicalDeviceInfo {
             requested_extensions.push(DrawIndirectCount::name());
         }
 
+        if self.api_version() < Version::V1_2
+            && requested_features.contains(Features::TIMELINE_SEMAPHORE)
+        {
+            requested_extensions.push(vk::KhrTimelineSemaphoreFn::name());
+        }
+
+        if self.api_version() < Version::V1_2
+            && requested_features.contains(Features::BUFFER_DEVICE_ADDRESS)
+        {
+            requested_extensions.push(vk::KhrBufferDeviceAddressFn::name());
+        }
+
+        if requested_features.contains(Features::SUBGROUP_SIZE_CONTROL) {
+            requested_extensions.push(vk::ExtSubgroupSizeControlFn::name());
+        }
+
+        if requested_features.contains(Features::PUSH_DESCRIPTOR) {
+            requested_extensions.push(PushDescriptor::name());
+        }
+
+        if requested_features.contains(Features::DYNAMIC_RENDERING) {
+            // `VK_KHR_dynamic_rendering` depends on `VK_KHR_depth_stencil_resolve`, which is core in 1.2.
+            if self.api_version() < Version::V1_2 {
+                requested_extensions.push(vk::KhrDepthStencilResolveFn::name());
+                requested_extensions.push(vk::KhrCreateRenderpass2Fn::name());
+                if self.api_version() < Version::V1_1 {
+                    requested_extensions.push(vk::KhrMultiviewFn::name());
+                }
+            }
+            requested_extensions.push(ext::KhrDynamicRenderingFn::name());
+        }
+
+        if requested_features.contains(Features::EXTERNAL_FD) {
+            if self.api_version() < Version::V1_1 {
+                requested_extensions.push(vk::KhrExternalMemoryFn::name());
+                requested_extensions.push(vk::KhrExternalSemaphoreFn::name());
+            }
+            requested_extensions.push(ExternalMemoryFd::name());
+            requested_extensions.push(vk::KhrExternalSemaphoreFdFn::name());
+            // Optional, `HandleType::DmaBuf` is reported as unsupported without it.
+            if self.supports_extension(vk::ExtExternalMemoryDmaBufFn::name()) {
+                requested_extensions.push(vk::ExtExternalMemoryDmaBufFn::name());
+            }
+        }
+
         requested_extensions
     }
 
@@ -585,6 +735,49 @@ impl PhysicalDeviceInfo {
                 mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
             }
 
+            // `VK_KHR_timeline_semaphore` is promoted to 1.2, where the feature is part of the 1.2 features.
+            if device_properties.api_version() < Version::V1_2
+                && device_properties.supports_extension(vk::KhrTimelineSemaphoreFn::name())
+            {
+                features.timeline_semaphore =
+                    Some(vk::PhysicalDeviceTimelineSemaphoreFeaturesKHR::builder().build());
+
+                let mut_ref = features.timeline_semaphore.as_mut().unwrap();
+                mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
+            }
+
+            // `VK_KHR_buffer_device_address` is promoted to 1.2, where the feature is part of the 1.2 features.
+            // The memory allocation flags it relies on are only core since 1.1.
+            if device_properties.api_version() >= Version::V1_1
+                && device_properties.api_version() < Version::V1_2
+                && device_properties.supports_extension(vk::KhrBufferDeviceAddressFn::name())
+            {
+                features.buffer_device_address =
+                    Some(vk::PhysicalDeviceBufferDeviceAddressFeaturesKHR::builder().build());
+
+                let mut_ref = features.buffer_device_address.as_mut().unwrap();
+                mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
+            }
+
+            // `VK_EXT_subgroup_size_control` requires 1.1.
+            if device_properties.api_version() >= Version::V1_1
+                && device_properties.supports_extension(vk::ExtSubgroupSizeControlFn::name())
+            {
+                features.subgroup_size_control =
+                    Some(vk::PhysicalDeviceSubgroupSizeControlFeaturesEXT::builder().build());
+
+                let mut_ref = features.subgroup_size_control.as_mut().unwrap();
+                mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
+            }
+
+            if device_properties.supports_extension(ext::KhrDynamicRenderingFn::name()) {
+                features.dynamic_rendering =
+                    Some(ext::PhysicalDeviceDynamicRenderingFeaturesKHR::default());
+
+                let mut_ref = features.dynamic_rendering.as_mut().unwrap();
+                mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
+            }
+
             unsafe {
                 get_device_properties
                     .get_physical_device_features2_khr(device, &mut features2 as *mut _);
@@ -608,6 +801,10 @@ impl PhysicalDeviceInfo {
             null_p_next(&mut features.descriptor_indexing);
             null_p_next(&mut features.mesh_shader);
             null_p_next(&mut features.imageless_framebuffer);
+            null_p_next(&mut features.timeline_semaphore);
+            null_p_next(&mut features.buffer_device_address);
+            null_p_next(&mut features.subgroup_size_control);
+            null_p_next(&mut features.dynamic_rendering);
         }
 
         (device_properties, features)
@@ -806,10 +1003,94 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             None
         };
 
+        let timeline_semaphore_fn =
+            if enabled_extensions.contains(&vk::KhrTimelineSemaphoreFn::name()) {
+                Some(ExtensionFn::Extension(vk::KhrTimelineSemaphoreFn::load(
+                    |name| {
+                        mem::transmute(
+                            self.instance
+                                .inner
+                                .get_device_proc_addr(device_raw.handle(), name.as_ptr()),
+                        )
+                    },
+                )))
+            } else if self.device_info.api_version() >= Version::V1_2 {
+                Some(ExtensionFn::Promoted)
+            } else {
+                None
+            };
+
+        let push_descriptor_fn = if enabled_extensions.contains(&PushDescriptor::name()) {
+            Some(ExtensionFn::Extension(PushDescriptor::new(
+                &self.instance.inner,
+                &device_raw,
+            )))
+        } else {
+            None
+        };
+
+        let external_memory_fd_fn = if enabled_extensions.contains(&ExternalMemoryFd::name()) {
+            Some(ExtensionFn::Extension(ExternalMemoryFd::new(
+                &self.instance.inner,
+                &device_raw,
+            )))
+        } else {
+            None
+        };
+
+        let external_semaphore_fd_fn =
+            if enabled_extensions.contains(&vk::KhrExternalSemaphoreFdFn::name()) {
+                Some(ExtensionFn::Extension(vk::KhrExternalSemaphoreFdFn::load(
+                    |name| {
+                        mem::transmute(
+                            self.instance
+                                .inner
+                                .get_device_proc_addr(device_raw.handle(), name.as_ptr()),
+                        )
+                    },
+                )))
+            } else {
+                None
+            };
+
+        let buffer_device_address_fn =
+            if enabled_extensions.contains(&vk::KhrBufferDeviceAddressFn::name()) {
+                Some(ExtensionFn::Extension(vk::KhrBufferDeviceAddressFn::load(
+                    |name| {
+                        mem::transmute(
+                            self.instance
+                                .inner
+                                .get_device_proc_addr(device_raw.handle(), name.as_ptr()),
+                        )
+                    },
+                )))
+            } else if self.device_info.api_version() >= Version::V1_2
+                && requested_features.contains(Features::BUFFER_DEVICE_ADDRESS)
+            {
+                Some(ExtensionFn::Promoted)
+            } else {
+                None
+            };
+
+        let dynamic_rendering_fn =
+            if enabled_extensions.contains(&ext::KhrDynamicRenderingFn::name()) {
+                Some(ExtensionFn::Extension(ext::KhrDynamicRenderingFn::load(
+                    |name| {
+                        mem::transmute(
+                            self.instance
+                                .inner
+                                .get_device_proc_addr(device_raw.handle(), name.as_ptr()),
+                        )
+                    },
+                )))
+            } else {
+                None
+            };
+
         #[cfg(feature = "naga")]
         let naga_options = {
             use naga::back::spv;
-            let capabilities = [
+            let mut capabilities = vec![
                 spv::Capability::Shader,
                 spv::Capability::Matrix,
                 spv::Capability::InputAttachment,
@@ -820,10 +1101,10 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
                 spv::Capability::ImageQuery,
                 spv::Capability::DerivativeControl,
                 //TODO: fill out the rest
-            ]
-            .iter()
-            .cloned()
-            .collect();
+            ];
+            if requested_features.contains(Features::BUFFER_DEVICE_ADDRESS) {
+                capabilities.push(spv::Capability::PhysicalStorageBufferAddresses);
+            }
             let mut flags = spv::WriterFlags::empty();
             if cfg!(debug_assertions) {
                 flags |= spv::WriterFlags::DEBUG;
@@ -831,7 +1112,7 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             spv::Options {
                 lang_version: (1, 0),
                 flags,
-                capabilities,
+                capabilities: capabilities.into_iter().collect(),
             }
         };
 
@@ -843,6 +1124,12 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
                 extension_fns: DeviceExtensionFunctions {
                     mesh_shaders: mesh_fn,
                     draw_indirect_count: indirect_count_fn,
+                    timeline_semaphore: timeline_semaphore_fn,
+                    push_descriptor: push_descriptor_fn,
+                    external_memory_fd: external_memory_fd_fn,
+                    external_semaphore_fd: external_semaphore_fd_fn,
+                    buffer_device_address: buffer_device_address_fn,
+                    dynamic_rendering: dynamic_rendering_fn,
                 },
                 flip_y_requires_shift: self.device_info.api_version() >= Version::V1_1
                     || self
@@ -991,6 +1278,32 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         }
     }
 
+    fn memory_budget(&self) -> Option<Vec<adapter::MemoryHeapBudget>> {
+        let get_device_properties = self.instance.get_physical_device_properties.as_ref()?;
+        if !self
+            .device_info
+            .supports_extension(vk::ExtMemoryBudgetFn::name())
+        {
+            return None;
+        }
+
+        let mut budget = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let mut properties = vk::PhysicalDeviceMemoryProperties2::builder().push_next(&mut budget);
+        unsafe {
+            get_device_properties
+                .get_physical_device_memory_properties2_khr(self.handle, &mut *properties);
+        }
+        let heap_count = properties.memory_properties.memory_heap_count as usize;
+
+        Some(
+            budget.heap_budget[..heap_count]
+                .iter()
+                .zip(budget.heap_usage[..heap_count].iter())
+                .map(|(&budget, &usage)| adapter::MemoryHeapBudget { budget, usage })
+                .collect(),
+        )
+    }
+
     fn features(&self) -> Features {
         let mut bits = self.device_features.to_hal_features(&self.device_info);
 
@@ -1005,6 +1318,14 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             bits = bits & !Features::DUAL_SRC_BLENDING;
         }
 
+        // The capabilities of the external handles are queried through the instance.
+        if self.instance.external_memory_capabilities.is_some()
+            && self.instance.external_semaphore_capabilities.is_some()
+            && self.device_info.supports_external_fd()
+        {
+            bits |= Features::EXTERNAL_FD;
+        }
+
         bits
     }
 
@@ -1122,6 +1443,7 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
 
         let mut descriptor_indexing_capabilities = hal::DescriptorIndexingProperties::default();
         let mut mesh_shader_capabilities = hal::MeshShaderProperties::default();
+        let mut subgroup_capabilities = hal::SubgroupProperties::default();
 
         if let Some(get_physical_device_properties) =
             self.instance.get_physical_device_properties.as_ref()
@@ -1129,15 +1451,29 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             let mut descriptor_indexing_properties =
                 vk::PhysicalDeviceDescriptorIndexingPropertiesEXT::builder();
             let mut mesh_shader_properties = vk::PhysicalDeviceMeshShaderPropertiesNV::builder();
+            let mut subgroup_properties = vk::PhysicalDeviceSubgroupProperties::builder();
+            let mut subgroup_size_control_properties =
+                vk::PhysicalDeviceSubgroupSizeControlPropertiesEXT::builder();
+
+            let mut properties2 = vk::PhysicalDeviceProperties2::builder()
+                .push_next(&mut mesh_shader_properties)
+                .push_next(&mut descriptor_indexing_properties);
+            // The subgroup properties are core since 1.1.
+            let supports_subgroups = self.device_info.api_version() >= Version::V1_1;
+            if supports_subgroups {
+                properties2 = properties2.push_next(&mut subgroup_properties);
+            }
+            let supports_subgroup_size_control = supports_subgroups
+                && self
+                    .device_info
+                    .supports_extension(vk::ExtSubgroupSizeControlFn::name());
+            if supports_subgroup_size_control {
+                properties2 = properties2.push_next(&mut subgroup_size_control_properties);
+            }
 
             unsafe {
-                get_physical_device_properties.get_physical_device_properties2_khr(
-                    self.handle,
-                    &mut vk::PhysicalDeviceProperties2::builder()
-                        .push_next(&mut mesh_shader_properties)
-                        .push_next(&mut descriptor_indexing_properties)
-                        .build() as *mut _,
-                );
+                get_physical_device_properties
+                    .get_physical_device_properties2_khr(self.handle, &mut *properties2);
             }
 
             descriptor_indexing_capabilities = hal::DescriptorIndexingProperties {
@@ -1185,17 +1521,109 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
                 mesh_output_per_primitive_granularity: mesh_shader_properties
                     .mesh_output_per_primitive_granularity,
             };
+
+            if supports_subgroups {
+                subgroup_capabilities = hal::SubgroupProperties {
+                    size: subgroup_properties.subgroup_size,
+                    min_size: subgroup_properties.subgroup_size,
+                    max_size: subgroup_properties.subgroup_size,
+                    supported_stages: conv::map_vk_stage_flags(
+                        subgroup_properties.supported_stages,
+                    ),
+                    supported_operations: conv::map_vk_subgroup_operations(
+                        subgroup_properties.supported_operations,
+                    ),
+                    quad_operations_in_all_stages: subgroup_properties
+                        .quad_operations_in_all_stages
+                        == vk::TRUE,
+                    required_size_stages: pso::ShaderStageFlags::empty(),
+                };
+            }
+            if supports_subgroup_size_control {
+                subgroup_capabilities.min_size = subgroup_size_control_properties.min_subgroup_size;
+                subgroup_capabilities.max_size = subgroup_size_control_properties.max_subgroup_size;
+                subgroup_capabilities.required_size_stages = conv::map_vk_stage_flags(
+                    subgroup_size_control_properties.required_subgroup_size_stages,
+                );
+            }
         }
 
         PhysicalDeviceProperties {
             limits,
             descriptor_indexing: descriptor_indexing_capabilities,
             mesh_shader: mesh_shader_capabilities,
+            subgroup: subgroup_capabilities,
             performance_caveats: Default::default(),
             dynamic_pipeline_states: DynamicStates::all(),
         }
     }
 
+    fn external_buffer_features(
+        &self,
+        usage: buffer::Usage,
+        handle_type: external::HandleType,
+    ) -> external::ExternalFeatures {
+        let capabilities = match self.instance.external_memory_capabilities {
+            Some(ref capabilities) if self.features().contains(Features::EXTERNAL_FD) => {
+                capabilities
+            }
+            _ => return external::ExternalFeatures::empty(),
+        };
+        if handle_type == external::HandleType::DmaBuf
+            && !self
+                .device_info
+                .supports_extension(vk::ExtExternalMemoryDmaBufFn::name())
+        {
+            return external::ExternalFeatures::empty();
+        }
+
+        let info = vk::PhysicalDeviceExternalBufferInfo::builder()
+            .usage(conv::map_buffer_usage(usage))
+            .handle_type(conv::map_external_memory_handle_type(handle_type));
+        let mut properties = vk::ExternalBufferProperties::default();
+        unsafe {
+            capabilities.get_physical_device_external_buffer_properties_khr(
+                self.handle,
+                &*info,
+                &mut properties,
+            );
+        }
+        conv::map_vk_external_memory_features(
+            properties
+                .external_memory_properties
+                .external_memory_features,
+        )
+    }
+
+    fn external_semaphore_features(
+        &self,
+        handle_type: external::HandleType,
+    ) -> external::ExternalFeatures {
+        let capabilities = match self.instance.external_semaphore_capabilities {
+            Some(ref capabilities) if self.features().contains(Features::EXTERNAL_FD) => {
+                capabilities
+            }
+            _ => return external::ExternalFeatures::empty(),
+        };
+        let vk_handle_type = match handle_type {
+            external::HandleType::OpaqueFd => {
+                vk::ExternalSemaphoreHandleTypeFlags::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD
+            }
+            external::HandleType::DmaBuf => return external::ExternalFeatures::empty(),
+        };
+
+        let info = vk::PhysicalDeviceExternalSemaphoreInfo::builder().handle_type(vk_handle_type);
+        let mut properties = vk::ExternalSemaphoreProperties::default();
+        unsafe {
+            capabilities.get_physical_device_external_semaphore_properties_khr(
+                self.handle,
+                &*info,
+                &mut properties,
+            );
+        }
+        conv::map_vk_external_semaphore_features(properties.external_semaphore_features)
+    }
+
     fn is_valid_cache(&self, cache: &[u8]) -> bool {
         const HEADER_SIZE: usize = 16 + vk::UUID_SIZE;
 
@@ -1,7 +1,11 @@
-use std::{borrow::Borrow, fmt, os::raw::c_void, sync::Arc, time::Instant};
+use std::{borrow::Borrow, fmt, mem, os::raw::c_void, ptr, sync::Arc, time::Instant};
 
-use ash::{extensions::khr, version::DeviceV1_0 as _, vk};
-use hal::{format::Format, window as w};
+use ash::{
+    extensions::khr,
+    version::{DeviceV1_0 as _, EntryV1_0 as _, InstanceV1_0 as _},
+    vk,
+};
+use hal::{device::OutOfMemory, format::Format, window as w};
 
 use crate::{
     conv, info, native, Backend, Device, Instance, PhysicalDevice, QueueFamily, RawDevice,
@@ -213,6 +217,50 @@ impl Instance {
         self.create_surface_from_vk_surface_khr(surface)
     }
 
+    /// Create a surface that isn't associated with any window, using `VK_EXT_headless_surface`.
+    ///
+    /// The swapchains of such a surface work like the ones of a window, which
+    /// allows testing the presentation without a window system.
+    /// The extent of the swapchain images is decided by the `SwapchainConfig`.
+    pub fn create_headless_surface(&self) -> Result<Surface, w::InitError> {
+        if !self.extensions.contains(&vk::ExtHeadlessSurfaceFn::name()) {
+            warn!("Vulkan driver does not support VK_EXT_headless_surface");
+            return Err(w::InitError::UnsupportedWindowHandle);
+        }
+
+        let surface = {
+            let headless_fn = vk::ExtHeadlessSurfaceFn::load(|name| unsafe {
+                mem::transmute(
+                    self.entry
+                        .get_instance_proc_addr(self.raw.inner.handle(), name.as_ptr()),
+                )
+            });
+            let info = vk::HeadlessSurfaceCreateInfoEXT::builder()
+                .flags(vk::HeadlessSurfaceCreateFlagsEXT::empty());
+            let mut surface = vk::SurfaceKHR::null();
+
+            let result = unsafe {
+                headless_fn.create_headless_surface_ext(
+                    self.raw.inner.handle(),
+                    &*info,
+                    ptr::null(),
+                    &mut surface,
+                )
+            };
+            match result {
+                vk::Result::SUCCESS => surface,
+                vk::Result::ERROR_OUT_OF_HOST_MEMORY => return Err(OutOfMemory::Host.into()),
+                vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => return Err(OutOfMemory::Device.into()),
+                other => {
+                    warn!("Unable to create headless surface: {:?}", other);
+                    return Err(w::InitError::UnsupportedWindowHandle);
+                }
+            }
+        };
+
+        Ok(self.create_surface_from_vk_surface_khr(surface))
+    }
+
     pub fn create_surface_from_vk_surface_khr(&self, surface: vk::SurfaceKHR) -> Surface {
         let functor = khr::Surface::new(&self.entry, &self.raw.inner);
 
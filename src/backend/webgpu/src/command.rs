@@ -35,6 +35,18 @@ impl hal::queue::Queue<Backend> for Queue {
         todo!()
     }
 
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        _command_buffers: Ic,
+        _wait_semaphores: Iw,
+        _signal_semaphores: Is,
+        _fence: Option<&mut <Backend as hal::Backend>::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a <Backend as hal::Backend>::CommandBuffer>,
+    {
+        todo!()
+    }
+
     unsafe fn present(
         &mut self,
         _surface: &mut <Backend as hal::Backend>::Surface,
@@ -30,6 +30,41 @@ impl hal::device::Device<Backend> for Device {
         todo!()
     }
 
+    unsafe fn allocate_exportable_memory(
+        &self,
+        _memory_type: MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<<Backend as hal::Backend>::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_memory(
+        &self,
+        _memory: &<Backend as hal::Backend>::Memory,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_memory(
+        &self,
+        _memory_type: MemoryTypeId,
+        _size: u64,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<<Backend as hal::Backend>::Memory, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn external_memory_type_mask(
+        &self,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<u32, hal::external::ExternalError> {
+        Ok(0)
+    }
+
     unsafe fn free_memory(&self, _memory: <Backend as hal::Backend>::Memory) {
         todo!()
     }
@@ -188,6 +223,13 @@ impl hal::device::Device<Backend> for Device {
         todo!()
     }
 
+    unsafe fn get_buffer_device_address(
+        &self,
+        _buf: &<Backend as hal::Backend>::Buffer,
+    ) -> buffer::DeviceAddress {
+        unreachable!("Feature BUFFER_DEVICE_ADDRESS is not supported")
+    }
+
     unsafe fn destroy_buffer(&self, _buffer: <Backend as hal::Backend>::Buffer) {
         todo!()
     }
@@ -314,6 +356,32 @@ impl hal::device::Device<Backend> for Device {
         todo!()
     }
 
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        _layout: &<Backend as hal::Backend>::DescriptorSetLayout,
+        _entries: I,
+    ) -> Result<<Backend as hal::Backend>::DescriptorUpdateTemplate, OutOfMemory> {
+        todo!()
+    }
+
+    unsafe fn destroy_descriptor_update_template(
+        &self,
+        _template: <Backend as hal::Backend>::DescriptorUpdateTemplate,
+    ) {
+        todo!()
+    }
+
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        _set: &mut <Backend as hal::Backend>::DescriptorSet,
+        _template: &<Backend as hal::Backend>::DescriptorUpdateTemplate,
+        _descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, Backend>>,
+    {
+        todo!()
+    }
+
     unsafe fn map_memory(
         &self,
         _memory: &mut <Backend as hal::Backend>::Memory,
@@ -348,6 +416,64 @@ impl hal::device::Device<Backend> for Device {
         todo!()
     }
 
+    fn create_timeline_semaphore(
+        &self,
+        _initial_value: u64,
+    ) -> Result<<Backend as hal::Backend>::Semaphore, OutOfMemory> {
+        todo!()
+    }
+
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        _semaphore: &<Backend as hal::Backend>::Semaphore,
+    ) -> Result<u64, WaitError> {
+        todo!()
+    }
+
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        _semaphores: I,
+        _wait: hal::device::WaitFor,
+        _timeout_ns: u64,
+    ) -> Result<bool, WaitError>
+    where
+        I: Iterator<Item = (&'a <Backend as hal::Backend>::Semaphore, u64)>,
+    {
+        todo!()
+    }
+
+    unsafe fn signal_semaphore(
+        &self,
+        _semaphore: &<Backend as hal::Backend>::Semaphore,
+        _value: u64,
+    ) -> Result<(), OutOfMemory> {
+        todo!()
+    }
+
+    fn create_exportable_semaphore(
+        &self,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<<Backend as hal::Backend>::Semaphore, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn export_semaphore(
+        &self,
+        _semaphore: &<Backend as hal::Backend>::Semaphore,
+        _handle_type: hal::external::HandleType,
+    ) -> Result<hal::external::Fd, hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
+    unsafe fn import_semaphore(
+        &self,
+        _semaphore: &mut <Backend as hal::Backend>::Semaphore,
+        _handle_type: hal::external::HandleType,
+        _fd: hal::external::Fd,
+    ) -> Result<(), hal::external::ExternalError> {
+        Err(hal::external::ExternalError::UnsupportedHandleType)
+    }
+
     fn create_fence(
         &self,
         _signaled: bool,
@@ -51,6 +51,7 @@ impl hal::Backend for Backend {
     type DescriptorSetLayout = ();
     type DescriptorPool = DescriptorPool;
     type DescriptorSet = ();
+    type DescriptorUpdateTemplate = ();
 
     type Fence = ();
     type Semaphore = ();
@@ -9,7 +9,7 @@
 //! of that [backend][crate::Backend].
 
 use crate::{
-    device, format, image, memory,
+    buffer, device, external, format, image, memory,
     queue::{QueueGroup, QueuePriority},
     Backend, Features, PhysicalDeviceProperties,
 };
@@ -37,6 +37,17 @@ pub struct MemoryHeap {
     pub flags: memory::HeapFlags,
 }
 
+/// Current budget and usage of a memory heap.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryHeapBudget {
+    /// Estimate of the memory the process can use from the heap without
+    /// failing allocations or degrading the performance.
+    pub budget: u64,
+    /// Estimate of the memory currently used by the process from the heap.
+    pub usage: u64,
+}
+
 /// Types of memory supported by this adapter and available memory.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -115,6 +126,15 @@ pub trait PhysicalDevice<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Fetch details for the memory regions provided by the device.
     fn memory_properties(&self) -> MemoryProperties;
 
+    /// Fetch the current budget and usage of the memory heaps, in the order of
+    /// [`MemoryProperties::memory_heaps`].
+    ///
+    /// Unlike the heap sizes, these change over time and are queried on every call.
+    /// Returns `None` if the backend can't track them.
+    fn memory_budget(&self) -> Option<Vec<MemoryHeapBudget>> {
+        None
+    }
+
     /// Returns the features of this `PhysicalDevice`. This usually depends on the graphics API being
     /// used, as well as the actual platform underneath.
     fn features(&self) -> Features;
@@ -127,6 +147,24 @@ pub trait PhysicalDevice<B: Backend>: fmt::Debug + Any + Send + Sync {
     fn is_valid_cache(&self, _cache: &[u8]) -> bool {
         false
     }
+
+    /// Fetch the operations supported when sharing the memory of buffers
+    /// with the given usage through external handles.
+    fn external_buffer_features(
+        &self,
+        _usage: buffer::Usage,
+        _handle_type: external::HandleType,
+    ) -> external::ExternalFeatures {
+        external::ExternalFeatures::empty()
+    }
+
+    /// Fetch the operations supported when sharing semaphores through external handles.
+    fn external_semaphore_features(
+        &self,
+        _handle_type: external::HandleType,
+    ) -> external::ExternalFeatures {
+        external::ExternalFeatures::empty()
+    }
 }
 
 /// The type of a physical graphics device
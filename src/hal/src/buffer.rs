@@ -13,6 +13,9 @@ pub type Offset = u64;
 /// An stride between elements inside a buffer, in bytes.
 pub type Stride = u32;
 
+/// An address of a buffer in the device address space, usable as a pointer in shaders.
+pub type DeviceAddress = u64;
+
 /// A subrange of the buffer.
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -85,6 +88,10 @@ bitflags!(
         const VERTEX = 0x80;
         ///
         const INDIRECT = 0x100;
+        /// The buffer address can be queried with `Device::get_buffer_device_address`.
+        ///
+        /// Requires [`Features::BUFFER_DEVICE_ADDRESS`][crate::Features::BUFFER_DEVICE_ADDRESS].
+        const DEVICE_ADDRESS = 0x2_0000;
     }
 );
 
@@ -17,8 +17,8 @@ mod clear;
 mod structs;
 
 use crate::{
-    buffer,
-    image::{Filter, Layout, SubresourceRange},
+    buffer, format,
+    image::{Filter, Layer, Layout, SubresourceRange},
     memory::{Barrier, Dependencies},
     pass, pso, query, Backend, DrawCount, IndexCount, IndexType, InstanceCount, TaskCount,
     VertexCount, VertexOffset, WorkGroupCount,
@@ -81,6 +81,39 @@ pub struct RenderAttachmentInfo<'a, B: Backend> {
     pub clear_value: ClearValue,
 }
 
+/// An attachment of a dynamic rendering scope, provided to `begin_rendering`.
+#[derive(Debug)]
+pub struct RenderingAttachmentInfo<'a, B: Backend> {
+    /// View of the attachment image.
+    pub image_view: &'a B::ImageView,
+    /// Format of the view, matching the formats the pipelines are created with.
+    pub format: format::Format,
+    /// Layout of the image during the rendering.
+    pub layout: Layout,
+    /// Load and store operations of the color or depth aspect.
+    pub ops: pass::AttachmentOps,
+    /// Load and store operations of the stencil aspect.
+    pub stencil_ops: pass::AttachmentOps,
+    /// Clear value, used by the `Clear` load operations.
+    pub clear_value: ClearValue,
+    /// View and layout of the image the multisampled attachment is resolved into
+    /// at the end of the rendering.
+    pub resolve: Option<(&'a B::ImageView, Layout)>,
+}
+
+/// Description of a dynamic rendering scope, provided to `begin_rendering`.
+#[derive(Debug)]
+pub struct RenderingInfo<'a, B: Backend> {
+    /// Section of the attachments to render to.
+    pub render_area: pso::Rect,
+    /// Number of layers rendered to.
+    pub layers: Layer,
+    /// Color attachments, bound to the fragment shader outputs in order.
+    pub colors: &'a [RenderingAttachmentInfo<'a, B>],
+    /// Depth-stencil attachment.
+    pub depth_stencil: Option<&'a RenderingAttachmentInfo<'a, B>>,
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct CommandBufferInheritanceInfo<'a, B: Backend> {
@@ -307,6 +340,18 @@ pub trait CommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Finishes recording commands for the current a render pass.
     unsafe fn end_render_pass(&mut self);
 
+    /// Begins recording commands rendering to the attachments of `info`,
+    /// without render pass and framebuffer objects.
+    ///
+    /// The graphics pipelines used must be created with a matching
+    /// [`PipelineTarget::Rendering`][crate::pso::PipelineTarget::Rendering].
+    /// Requires [`Features::DYNAMIC_RENDERING`][crate::Features::DYNAMIC_RENDERING],
+    /// backends without it don't record anything.
+    unsafe fn begin_rendering(&mut self, _info: &RenderingInfo<B>) {}
+
+    /// Finishes recording commands for the current dynamic rendering.
+    unsafe fn end_rendering(&mut self) {}
+
     /// Bind a graphics pipeline.
     ///
     /// # Errors
@@ -330,6 +375,27 @@ pub trait CommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
         I: Iterator<Item = &'a B::DescriptorSet>,
         J: Iterator<Item = DescriptorSetOffset>;
 
+    /// Write descriptors into the graphics push descriptor set `set_index` of the pipeline layout.
+    ///
+    /// The set layout has to be created with `Device::create_push_descriptor_set_layout`.
+    /// Descriptors are spilled onto the next bindings the same way as with
+    /// [`DescriptorSetWrite`][pso::DescriptorSetWrite], and stay bound until the set
+    /// is pushed or bound again.
+    ///
+    /// Requires [`Features::PUSH_DESCRIPTOR`][crate::Features::PUSH_DESCRIPTOR],
+    /// backends without it don't record anything.
+    unsafe fn push_graphics_descriptor_set<'a, I>(
+        &mut self,
+        _layout: &B::PipelineLayout,
+        _set_index: usize,
+        _binding: pso::DescriptorBinding,
+        _array_offset: pso::DescriptorArrayIndex,
+        _descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+    }
+
     /// Bind a compute pipeline.
     ///
     /// # Errors
@@ -353,6 +419,25 @@ pub trait CommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
         I: Iterator<Item = &'a B::DescriptorSet>,
         J: Iterator<Item = DescriptorSetOffset>;
 
+    /// Write descriptors into the compute push descriptor set `set_index` of the pipeline layout.
+    ///
+    /// See [`push_graphics_descriptor_set`][CommandBuffer::push_graphics_descriptor_set]
+    /// for the details.
+    ///
+    /// Requires [`Features::PUSH_DESCRIPTOR`][crate::Features::PUSH_DESCRIPTOR],
+    /// backends without it don't record anything.
+    unsafe fn push_compute_descriptor_set<'a, I>(
+        &mut self,
+        _layout: &B::PipelineLayout,
+        _set_index: usize,
+        _binding: pso::DescriptorBinding,
+        _array_offset: pso::DescriptorArrayIndex,
+        _descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>,
+    {
+    }
+
     /// Execute a workgroup in the compute pipeline. `x`, `y` and `z` are the
     /// number of local workgroups to dispatch along each "axis"; a total of `x`*`y`*`z`
     /// local workgroups will be created.
@@ -12,7 +12,7 @@
 //! and is used to actually do things.
 
 use crate::{
-    buffer, format, image, memory,
+    buffer, external, format, image, memory,
     memory::{Requirements, Segment},
     pass,
     pool::CommandPoolCreateFlags,
@@ -195,6 +195,11 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
     ///
     /// * `memory_type` - Index of the memory type in the memory properties of the associated physical device.
     /// * `size` - Size of the allocation.
+    ///
+    /// With [`Features::BUFFER_DEVICE_ADDRESS`][crate::Features::BUFFER_DEVICE_ADDRESS] enabled,
+    /// the allocations can back buffers with [`buffer::Usage::DEVICE_ADDRESS`], which may make
+    /// them slightly more expensive on some implementations. Only request the feature when
+    /// it's used.
     unsafe fn allocate_memory(
         &self,
         memory_type: MemoryTypeId,
@@ -204,6 +209,64 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Free device memory
     unsafe fn free_memory(&self, memory: B::Memory);
 
+    /// Allocate a memory segment of a specified type, which can be exported
+    /// with [`export_memory`][Device::export_memory].
+    ///
+    /// Requires [`Features::EXTERNAL_FD`][crate::Features::EXTERNAL_FD].
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_type` - Index of the memory type in the memory properties of the associated physical device.
+    /// * `size` - Size of the allocation.
+    /// * `handle_type` - Type of the handles the memory will be exported to.
+    ///
+    /// Handle types that memory can't be exported to are reported as
+    /// [`UnsupportedHandleType`][external::ExternalError::UnsupportedHandleType].
+    unsafe fn allocate_exportable_memory(
+        &self,
+        memory_type: MemoryTypeId,
+        size: u64,
+        handle_type: external::HandleType,
+    ) -> Result<B::Memory, external::ExternalError>;
+
+    /// Export memory to a new file descriptor, owned by the caller.
+    unsafe fn export_memory(
+        &self,
+        memory: &B::Memory,
+        handle_type: external::HandleType,
+    ) -> Result<external::Fd, external::ExternalError>;
+
+    /// Import memory from a file descriptor.
+    ///
+    /// On success, the file descriptor is owned by the implementation,
+    /// and must not be used by the caller anymore.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_type` - Index of the memory type, which must be in the mask returned by
+    ///   [`external_memory_type_mask`][Device::external_memory_type_mask].
+    /// * `size` - Size of the allocation.
+    /// * `handle_type` - Type of the file descriptor.
+    /// * `fd` - File descriptor to import.
+    unsafe fn import_memory(
+        &self,
+        memory_type: MemoryTypeId,
+        size: u64,
+        handle_type: external::HandleType,
+        fd: external::Fd,
+    ) -> Result<B::Memory, external::ExternalError>;
+
+    /// Get the mask of the memory types that memory can be imported into
+    /// from a file descriptor.
+    ///
+    /// Opaque file descriptors must be imported into the memory type they were allocated from.
+    /// The mask is empty if the file descriptor can't be imported at all.
+    unsafe fn external_memory_type_mask(
+        &self,
+        handle_type: external::HandleType,
+        fd: external::Fd,
+    ) -> Result<u32, external::ExternalError>;
+
     /// Create a new [command pool][crate::pool::CommandPool] for a given queue family.
     ///
     /// *Note*: the family has to be associated with one of [the queue groups
@@ -393,6 +456,15 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
         buf: &mut B::Buffer,
     ) -> Result<(), BindError>;
 
+    /// Get the address of a buffer in the device address space.
+    ///
+    /// The buffer must be created with `buffer::Usage::DEVICE_ADDRESS` and be bound to memory.
+    /// The address stays valid until the buffer is destroyed. Memory is allocated with the flags
+    /// needed for the device addresses when the feature is enabled, so any memory type can be bound.
+    ///
+    /// Requires [`Features::BUFFER_DEVICE_ADDRESS`][crate::Features::BUFFER_DEVICE_ADDRESS].
+    unsafe fn get_buffer_device_address(&self, buffer: &B::Buffer) -> buffer::DeviceAddress;
+
     /// Destroy a buffer.
     ///
     /// The buffer shouldn't be destroyed before any submitted command buffer,
@@ -505,6 +577,27 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
         I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
         J: Iterator<Item = &'a B::Sampler>;
 
+    /// Create a descriptor set layout for push descriptors.
+    ///
+    /// Sets of this layout are not allocated from a pool, instead their descriptors
+    /// are recorded directly into command buffers with
+    /// `CommandBuffer::push_graphics_descriptor_set` and `CommandBuffer::push_compute_descriptor_set`.
+    /// At most one set of a pipeline layout can be a push descriptor set.
+    ///
+    /// Requires [`Features::PUSH_DESCRIPTOR`][crate::Features::PUSH_DESCRIPTOR],
+    /// backends without it create a regular descriptor set layout.
+    unsafe fn create_push_descriptor_set_layout<'a, I, J>(
+        &self,
+        bindings: I,
+        immutable_samplers: J,
+    ) -> Result<B::DescriptorSetLayout, OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorSetLayoutBinding>,
+        J: Iterator<Item = &'a B::Sampler>,
+    {
+        self.create_descriptor_set_layout(bindings, immutable_samplers)
+    }
+
     /// Destroy a descriptor set layout object
     unsafe fn destroy_descriptor_set_layout(&self, layout: B::DescriptorSetLayout);
 
@@ -516,6 +609,34 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Structure specifying a copy descriptor set operation.
     unsafe fn copy_descriptor_set<'a>(&self, op: pso::DescriptorSetCopy<'a, B>);
 
+    /// Create a descriptor update template.
+    ///
+    /// The template records the shape of a repeated descriptor set write into sets
+    /// of the given layout, so that the writes don't have to be resolved against
+    /// the layout every time.
+    unsafe fn create_descriptor_update_template<I>(
+        &self,
+        layout: &B::DescriptorSetLayout,
+        entries: I,
+    ) -> Result<B::DescriptorUpdateTemplate, OutOfMemory>
+    where
+        I: Iterator<Item = pso::DescriptorUpdateTemplateEntry>;
+
+    /// Destroy a descriptor update template.
+    unsafe fn destroy_descriptor_update_template(&self, template: B::DescriptorUpdateTemplate);
+
+    /// Write descriptors into a set following a descriptor update template.
+    ///
+    /// The set has to be of the layout the template was created with, and
+    /// `descriptors` has to provide the descriptors of all the template entries, in order.
+    unsafe fn update_descriptor_set_with_template<'a, I>(
+        &self,
+        set: &mut B::DescriptorSet,
+        template: &B::DescriptorUpdateTemplate,
+        descriptors: I,
+    ) where
+        I: Iterator<Item = pso::Descriptor<'a, B>>;
+
     /// Map a memory object into application address space
     ///
     /// Call `map_memory()` to retrieve a host virtual address pointer to a region of a mappable memory object
@@ -544,6 +665,79 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Destroy a semaphore object.
     unsafe fn destroy_semaphore(&self, semaphore: B::Semaphore);
 
+    /// Create a new timeline semaphore object.
+    ///
+    /// Timeline semaphores hold a 64-bit counter, which only ever increases.
+    /// The counter **can** be signaled and waited on from the host, with
+    /// [`signal_semaphore`][Device::signal_semaphore] and
+    /// [`wait_for_semaphores`][Device::wait_for_semaphores],
+    /// and from the device, with [`submit_timeline`][crate::queue::Queue::submit_timeline].
+    ///
+    /// Requires [`Features::TIMELINE_SEMAPHORE`][crate::Features::TIMELINE_SEMAPHORE].
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_value` - the initial value of the counter.
+    fn create_timeline_semaphore(&self, initial_value: u64) -> Result<B::Semaphore, OutOfMemory>;
+
+    /// Get the current counter value of a timeline semaphore.
+    unsafe fn get_semaphore_counter_value(
+        &self,
+        semaphore: &B::Semaphore,
+    ) -> Result<u64, WaitError>;
+
+    /// Blocks until all or one of the given timeline semaphores reach their values.
+    /// Returns true if the semaphores were signaled before the timeout.
+    unsafe fn wait_for_semaphores<'a, I>(
+        &self,
+        semaphores: I,
+        wait: WaitFor,
+        timeout_ns: u64,
+    ) -> Result<bool, WaitError>
+    where
+        I: Iterator<Item = (&'a B::Semaphore, u64)>;
+
+    /// Set the counter value of a timeline semaphore from the host.
+    ///
+    /// The value **must** be greater than the current value of the counter,
+    /// and less than the values of any pending signal operations.
+    unsafe fn signal_semaphore(
+        &self,
+        semaphore: &B::Semaphore,
+        value: u64,
+    ) -> Result<(), OutOfMemory>;
+
+    /// Create a new semaphore object, which can be exported
+    /// with [`export_semaphore`][Device::export_semaphore].
+    ///
+    /// Requires [`Features::EXTERNAL_FD`][crate::Features::EXTERNAL_FD], and the handle type
+    /// to be exportable according to
+    /// [`external_semaphore_features`][crate::adapter::PhysicalDevice::external_semaphore_features].
+    /// Handle types that semaphores can't be exported to are reported as
+    /// [`UnsupportedHandleType`][external::ExternalError::UnsupportedHandleType].
+    fn create_exportable_semaphore(
+        &self,
+        handle_type: external::HandleType,
+    ) -> Result<B::Semaphore, external::ExternalError>;
+
+    /// Export a semaphore to a new file descriptor, owned by the caller.
+    unsafe fn export_semaphore(
+        &self,
+        semaphore: &B::Semaphore,
+        handle_type: external::HandleType,
+    ) -> Result<external::Fd, external::ExternalError>;
+
+    /// Import the payload of a semaphore from a file descriptor, replacing its current payload.
+    ///
+    /// On success, the file descriptor is owned by the implementation,
+    /// and must not be used by the caller anymore.
+    unsafe fn import_semaphore(
+        &self,
+        semaphore: &mut B::Semaphore,
+        handle_type: external::HandleType,
+        fd: external::Fd,
+    ) -> Result<(), external::ExternalError>;
+
     /// Create a new fence object.
     ///
     /// Fences are a synchronization primitive that **can** be used to insert a dependency from
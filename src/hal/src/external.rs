@@ -0,0 +1,53 @@
+//! Sharing of memory and semaphores with other APIs and processes.
+//!
+//! Memory and semaphores can be exported to file descriptors, and imported back
+//! by another device, API or process.
+//! Requires [`Features::EXTERNAL_FD`][crate::Features::EXTERNAL_FD].
+
+use crate::device::OutOfMemory;
+
+/// Raw file descriptor of an external handle.
+pub type Fd = std::os::raw::c_int;
+
+/// Type of an external handle.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HandleType {
+    /// POSIX file descriptor, only meaningful to the same driver and device.
+    OpaqueFd,
+    /// Linux dma-buf file descriptor, shareable with other drivers and devices.
+    ///
+    /// Only memory can be exported to dma-bufs.
+    DmaBuf,
+}
+
+bitflags!(
+    /// Operations supported by an external handle type.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct ExternalFeatures: u32 {
+        /// Objects can be exported to handles of this type.
+        const EXPORTABLE = 0x1;
+        /// Objects can be imported from handles of this type.
+        const IMPORTABLE = 0x2;
+        /// Memory shared with handles of this type needs a dedicated allocation,
+        /// which is not supported yet.
+        const DEDICATED_ONLY = 0x4;
+    }
+);
+
+/// Error exporting or importing an external handle.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ExternalError {
+    /// Out of either host or device memory.
+    #[error(transparent)]
+    OutOfMemory(#[from] OutOfMemory),
+    /// Cannot create any more objects, or file descriptors.
+    #[error("Too many objects")]
+    TooManyObjects,
+    /// The handle is not valid for the requested operation.
+    #[error("Invalid external handle")]
+    InvalidHandle,
+    /// The handle type is not supported for this object.
+    #[error("Unsupported external handle type")]
+    UnsupportedHandleType,
+}
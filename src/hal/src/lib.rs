@@ -46,12 +46,13 @@ extern crate bitflags;
 #[macro_use]
 extern crate serde;
 
-use std::{any::Any, fmt, hash::Hash};
+use std::{any::Any, borrow::Cow, fmt, hash::Hash};
 
 pub mod adapter;
 pub mod buffer;
 pub mod command;
 pub mod device;
+pub mod external;
 pub mod format;
 pub mod image;
 pub mod memory;
@@ -276,6 +277,20 @@ bitflags! {
         const MESH_SHADER = 0x0000_0002 << 96;
         /// Mask for all the features associated with mesh shader stages.
         const MESH_SHADER_MASK = Features::TASK_SHADER.bits | Features::MESH_SHADER.bits;
+        /// Supports timeline semaphores.
+        const TIMELINE_SEMAPHORE = 0x0000_0004 << 96;
+        /// Supports sharing memory and semaphores through file descriptors.
+        const EXTERNAL_FD = 0x0000_0008 << 96;
+        /// Supports rendering without render pass and framebuffer objects,
+        /// with [`CommandBuffer::begin_rendering`][crate::command::CommandBuffer::begin_rendering].
+        const DYNAMIC_RENDERING = 0x0000_0010 << 96;
+        /// Supports pushing descriptors directly into command buffers.
+        const PUSH_DESCRIPTOR = 0x0000_0020 << 96;
+        /// Supports querying the device addresses of buffers, to access them through pointers in shaders.
+        const BUFFER_DEVICE_ADDRESS = 0x0000_0040 << 96;
+        /// Supports requiring the subgroup size of compute pipelines,
+        /// with [`ComputePipelineDesc::required_subgroup_size`][crate::pso::ComputePipelineDesc::required_subgroup_size].
+        const SUBGROUP_SIZE_CONTROL = 0x0000_0080 << 96;
     }
 }
 
@@ -329,6 +344,8 @@ pub struct PhysicalDeviceProperties {
     pub descriptor_indexing: DescriptorIndexingProperties,
     /// Mesh Shader properties.
     pub mesh_shader: MeshShaderProperties,
+    /// Subgroup properties.
+    pub subgroup: SubgroupProperties,
     /// Performance caveats.
     pub performance_caveats: PerformanceCaveats,
     /// Dynamic pipeline states.
@@ -558,6 +575,51 @@ pub struct MeshShaderProperties {
     pub mesh_output_per_primitive_granularity: u32,
 }
 
+bitflags! {
+    /// Classes of operations supported on subgroups (matches Vulkan).
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct SubgroupOperations: u32 {
+        /// Electing an invocation and synchronizing the subgroup.
+        const BASIC = 0x1;
+        /// Votes, such as all or any invocation passing a condition.
+        const VOTE = 0x2;
+        /// Reductions and scans, such as adding values over the subgroup.
+        const ARITHMETIC = 0x4;
+        /// Ballots and broadcasts of values to the subgroup.
+        const BALLOT = 0x8;
+        /// Shuffles of values between arbitrary invocations.
+        const SHUFFLE = 0x10;
+        /// Shuffles of values between invocations at relative offsets.
+        const SHUFFLE_RELATIVE = 0x20;
+        /// Arithmetic operations over clusters of invocations.
+        const CLUSTERED = 0x40;
+        /// Operations between the invocations of quads.
+        const QUAD = 0x80;
+    }
+}
+
+/// Properties of the subgroups, the sets of shader invocations executed together.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubgroupProperties {
+    /// Default number of invocations in a subgroup.
+    pub size: u32,
+    /// Minimum number of invocations in a subgroup.
+    pub min_size: u32,
+    /// Maximum number of invocations in a subgroup.
+    pub max_size: u32,
+    /// Shader stages supporting subgroup operations.
+    pub supported_stages: pso::ShaderStageFlags,
+    /// Classes of subgroup operations supported in these stages.
+    pub supported_operations: SubgroupOperations,
+    /// Whether quad operations are supported in all the stages,
+    /// and not only in the fragment and compute ones.
+    pub quad_operations_in_all_stages: bool,
+    /// Shader stages that can require a subgroup size.
+    pub required_size_stages: pso::ShaderStageFlags,
+}
+
 /// An enum describing the type of an index value in a slice's index buffer
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -570,9 +632,66 @@ pub enum IndexType {
 
 /// Error creating an instance of a backend on the platform that
 /// doesn't support this backend.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, thiserror::Error)]
-#[error("Backend is not supported on this platform")]
-pub struct UnsupportedBackend;
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("Backend is not supported on this platform: {reason}")]
+pub struct UnsupportedBackend {
+    /// Why the backend can't be used.
+    pub reason: Cow<'static, str>,
+}
+
+impl UnsupportedBackend {
+    /// Create a new error with the given reason.
+    pub fn new(reason: impl Into<Cow<'static, str>>) -> Self {
+        UnsupportedBackend {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Default for UnsupportedBackend {
+    /// Create an error without a specific reason.
+    fn default() -> Self {
+        UnsupportedBackend::new("no reason given")
+    }
+}
+
+/// Description of an [instance][Instance] to create.
+#[derive(Clone, Debug)]
+pub struct InstanceDesc<'a> {
+    /// Name of the application using the API.
+    pub name: &'a str,
+    /// Free form representation of the application's version.
+    pub version: u32,
+    /// Enable the validation of the API usage, if available.
+    ///
+    /// Defaults to `true` in debug builds.
+    pub validation: bool,
+    /// Backend-specific extensions of the underlying API to enable.
+    ///
+    /// Creation fails if any of them is not available.
+    pub extensions: &'a [&'a str],
+    /// Minimum version of the underlying API, as `(major, minor)`.
+    ///
+    /// Creation fails if the platform doesn't support it.
+    pub min_api_version: Option<(u32, u32)>,
+    /// Don't connect to the window system, so the instance can't create
+    /// surfaces from windows.
+    pub headless: bool,
+}
+
+impl<'a> InstanceDesc<'a> {
+    /// Create a description with the default options.
+    pub fn new(name: &'a str, version: u32) -> Self {
+        InstanceDesc {
+            name,
+            version,
+            validation: cfg!(debug_assertions),
+            extensions: &[],
+            min_api_version: None,
+            headless: false,
+        }
+    }
+}
 
 /// An instantiated backend.
 ///
@@ -613,6 +732,29 @@ pub trait Instance<B: Backend>: Any + Send + Sync + Sized {
     /// on the current platform][UnsupportedBackend].
     fn create(name: &str, version: u32) -> Result<Self, UnsupportedBackend>;
 
+    /// Create a new instance with the given options.
+    ///
+    /// Backends that don't support the extensions and API version options
+    /// fail to create instances requesting them, and ignore the other options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` variant if the requested backend [is not supported
+    /// on the current platform][UnsupportedBackend], or doesn't support the options.
+    fn create_with_desc(desc: &InstanceDesc) -> Result<Self, UnsupportedBackend> {
+        if !desc.extensions.is_empty() {
+            return Err(UnsupportedBackend::new(
+                "Instance extensions are not supported by this backend",
+            ));
+        }
+        if desc.min_api_version.is_some() {
+            return Err(UnsupportedBackend::new(
+                "API version selection is not supported by this backend",
+            ));
+        }
+        Self::create(desc.name, desc.version)
+    }
+
     /// Return all available [graphics adapters][adapter::Adapter].
     fn enumerate_adapters(&self) -> Vec<adapter::Adapter<B>>;
 
@@ -716,6 +858,8 @@ pub trait Backend: 'static + Sized + Eq + Clone + Hash + fmt::Debug + Any + Send
     type DescriptorSet: fmt::Debug + Any + Send + Sync;
     /// The corresponding descriptor set layout type for this backend.
     type DescriptorSetLayout: fmt::Debug + Any + Send + Sync;
+    /// The corresponding descriptor update template type for this backend.
+    type DescriptorUpdateTemplate: fmt::Debug + Any + Send + Sync;
 
     /// The corresponding fence type for this backend.
     type Fence: fmt::Debug + Any + Send + Sync;
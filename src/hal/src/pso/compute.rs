@@ -18,6 +18,13 @@ pub struct ComputePipelineDesc<'a, B: Backend> {
     pub flags: PipelineCreationFlags,
     /// The parent pipeline to this one, if any.
     pub parent: BasePipeline<'a, B::ComputePipeline>,
+    /// The subgroup size the shader has to be executed with, if any.
+    ///
+    /// It must be a power of two between [`SubgroupProperties::min_size`][crate::SubgroupProperties::min_size]
+    /// and [`SubgroupProperties::max_size`][crate::SubgroupProperties::max_size].
+    ///
+    /// Requires [`Features::SUBGROUP_SIZE_CONTROL`][crate::Features::SUBGROUP_SIZE_CONTROL].
+    pub required_subgroup_size: Option<u32>,
 }
 
 impl<'a, B: Backend> ComputePipelineDesc<'a, B> {
@@ -29,6 +36,7 @@ impl<'a, B: Backend> ComputePipelineDesc<'a, B> {
             layout,
             flags: PipelineCreationFlags::empty(),
             parent: BasePipeline::None,
+            required_subgroup_size: None,
         }
     }
 }
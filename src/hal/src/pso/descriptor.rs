@@ -237,6 +237,26 @@ where
     pub descriptors: I,
 }
 
+/// A range of descriptors written by a descriptor update template.
+///
+/// Should be provided to the `create_descriptor_update_template` method of a `Device`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DescriptorUpdateTemplateEntry {
+    /// Binding index to start writing at.
+    ///
+    /// *Note*: like with [`DescriptorSetWrite`], the updates are spilled
+    /// onto the next binding when there are more descriptors
+    /// than array elements left in the specified binding.
+    ///
+    /// [`DescriptorSetWrite`]: struct.DescriptorSetWrite.html
+    pub binding: DescriptorBinding,
+    /// Offset into the array to copy to.
+    pub array_offset: DescriptorArrayIndex,
+    /// Number of descriptors to write.
+    pub count: DescriptorArrayIndex,
+}
+
 /// A handle to a specific shader resource that can be bound for use in a `DescriptorSet`.
 /// Usually provided in a [`DescriptorSetWrite`]
 ///
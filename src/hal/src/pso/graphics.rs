@@ -1,7 +1,7 @@
 //! Graphics pipeline descriptor.
 
 use crate::{
-    image, pass,
+    format, image, pass,
     pso::{
         input_assembler::{AttributeDesc, InputAssemblerDesc, VertexBufferDesc},
         output_merger::{ColorBlendDesc, DepthStencilDesc, Face},
@@ -104,6 +104,28 @@ pub enum PrimitiveAssemblerDesc<'a, B: Backend> {
         mesh: EntryPoint<'a, B>,
     },
 }
+/// Attachments a graphics pipeline renders to.
+#[derive(Debug)]
+pub enum PipelineTarget<'a, B: Backend> {
+    /// A subpass of a render pass.
+    Subpass(pass::Subpass<'a, B>),
+    /// A dynamic rendering scope with attachments of the given formats.
+    ///
+    /// Requires [`Features::DYNAMIC_RENDERING`][crate::Features::DYNAMIC_RENDERING].
+    Rendering {
+        /// Formats of the color attachments.
+        colors: &'a [format::Format],
+        /// Format of the depth-stencil attachment.
+        depth_stencil: Option<format::Format>,
+    },
+}
+
+impl<'a, B: Backend> From<pass::Subpass<'a, B>> for PipelineTarget<'a, B> {
+    fn from(subpass: pass::Subpass<'a, B>) -> Self {
+        PipelineTarget::Subpass(subpass)
+    }
+}
+
 /// A description of all the settings that can be altered
 /// when creating a graphics pipeline.
 #[derive(Debug)]
@@ -134,8 +156,8 @@ pub struct GraphicsPipelineDesc<'a, B: Backend> {
     pub baked_states: BakedStates,
     /// Pipeline layout.
     pub layout: &'a B::PipelineLayout,
-    /// Subpass in which the pipeline can be executed.
-    pub subpass: pass::Subpass<'a, B>,
+    /// Subpass or attachments the pipeline renders to.
+    pub target: PipelineTarget<'a, B>,
     /// Options that may be set to alter pipeline properties.
     pub flags: PipelineCreationFlags,
     /// The parent pipeline, which may be
@@ -150,7 +172,7 @@ impl<'a, B: Backend> GraphicsPipelineDesc<'a, B> {
         rasterizer: Rasterizer,
         fragment: Option<EntryPoint<'a, B>>,
         layout: &'a B::PipelineLayout,
-        subpass: pass::Subpass<'a, B>,
+        target: impl Into<PipelineTarget<'a, B>>,
     ) -> Self {
         GraphicsPipelineDesc {
             label: None,
@@ -162,7 +184,7 @@ impl<'a, B: Backend> GraphicsPipelineDesc<'a, B> {
             multisampling: None,
             baked_states: BakedStates::default(),
             layout,
-            subpass,
+            target: target.into(),
             flags: PipelineCreationFlags::empty(),
             parent: BasePipeline::None,
         }
@@ -129,6 +129,37 @@ pub trait Queue<B: Backend>: fmt::Debug + Any + Send + Sync {
         Iw: Iterator<Item = (&'a B::Semaphore, pso::PipelineStage)>,
         Is: Iterator<Item = &'a B::Semaphore>;
 
+    /// Submit command buffers to queue for execution, waiting on and signaling
+    /// timeline semaphores.
+    ///
+    /// Requires [`Features::TIMELINE_SEMAPHORE`][crate::Features::TIMELINE_SEMAPHORE].
+    ///
+    /// # Arguments
+    ///
+    /// * `command_buffers` - command buffers to submit.
+    /// * `wait_semaphores` - semaphores to wait being signalled before submission,
+    ///   with the counter value to wait for.
+    /// * `signal_semaphores` - semaphores to signal after all command buffers
+    ///   in the submission have finished execution, with the counter value to set.
+    /// * `fence` - must be in unsignaled state, and will be signaled after
+    ///   all command buffers in the submission have finished execution.
+    ///
+    /// The values are ignored for binary semaphores.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe for the same reasons as [`submit`][Queue::submit].
+    unsafe fn submit_timeline<'a, Ic, Iw, Is>(
+        &mut self,
+        command_buffers: Ic,
+        wait_semaphores: Iw,
+        signal_semaphores: Is,
+        fence: Option<&mut B::Fence>,
+    ) where
+        Ic: Iterator<Item = &'a B::CommandBuffer>,
+        Iw: Iterator<Item = (&'a B::Semaphore, u64, pso::PipelineStage)>,
+        Is: Iterator<Item = (&'a B::Semaphore, u64)>;
+
     /// Present a swapchain image directly to a surface, after waiting on `wait_semaphore`.
     ///
     /// # Safety
@@ -464,9 +464,12 @@ pub enum PresentError {
 }
 
 /// Error occurred during surface creation.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
 pub enum InitError {
     /// Window handle is not supported by the backend.
     #[error("Specified window handle is unsupported")]
     UnsupportedWindowHandle,
+    /// Out of either host or device memory.
+    #[error(transparent)]
+    OutOfMemory(#[from] device::OutOfMemory),
 }
@@ -942,14 +942,14 @@ impl<B: hal::Backend> Scene<B> {
                         baked_states: pso::BakedStates::default(), //TODO
                         multisampling: None,                       // TODO
                         layout: &resources.pipeline_layouts[layout],
-                        subpass: hal::pass::Subpass {
+                        target: pso::PipelineTarget::Subpass(hal::pass::Subpass {
                             main_pass: &resources
                                 .render_passes
                                 .get(&subpass.parent)
                                 .expect(&format!("Missing render pass: {}", subpass.parent))
                                 .handle,
                             index: subpass.index,
-                        },
+                        }),
                         flags: pso::PipelineCreationFlags::empty(),
                         parent: pso::BasePipeline::None,
                     };
@@ -976,6 +976,7 @@ impl<B: hal::Backend> Scene<B> {
                             .expect(&format!("Missing pipeline layout: {}", layout)),
                         flags: pso::PipelineCreationFlags::empty(),
                         parent: pso::BasePipeline::None,
+                        required_subgroup_size: None,
                     };
                     let pso = unsafe { device.create_compute_pipeline(&desc, None) }.unwrap();
                     resources